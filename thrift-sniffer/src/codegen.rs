@@ -0,0 +1,281 @@
+use crate::schema::upper_camel;
+use clap::ValueEnum;
+use std::fmt::Write;
+use thrift_sniffer::decode::{Message, ThriftValue};
+use thrift_sniffer::idl::{Field, Idl, Type};
+
+// --emit-code 支持的语言
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Language {
+    Rust,
+}
+
+// 把一次 Call 还原成构造请求并调用客户端的代码片段，便于复现抓到的请求
+// 字段名与类型取自 --idl，命名与 volo-gen 生成的代码一致；生成的类型需要自行 use 进来
+// 抓到的值与 IDL 对不上（缺字段、类型不符）时填 Default::default() 并在行尾注明，片段仍能编译
+pub fn emit(language: Language, idl: &Idl, msg: &Message) -> String {
+    match language {
+        Language::Rust => emit_rust(idl, msg),
+    }
+}
+
+fn emit_rust(idl: &Idl, msg: &Message) -> String {
+    let mut out = String::new();
+    let Some(method) = idl.method(msg.method()) else {
+        let _ = writeln!(
+            out,
+            "// {} (seq {}): method not found in IDL",
+            msg.name, msg.seq_id
+        );
+        return out;
+    };
+    let _ = writeln!(out, "// {} (seq {})", msg.name, msg.seq_id);
+    let mut args = Vec::new();
+    for arg in &method.args {
+        let name = rust_ident(&snake_case(&arg.name));
+        let (code, note) = field_value(idl, arg, find(&msg.body, arg.id), 0);
+        let _ = writeln!(out, "let {} = {};{}", name, code, note);
+        args.push(name);
+    }
+    for (id, value) in &msg.body {
+        if !method.args.iter().any(|arg| arg.id == *id) {
+            let _ = writeln!(out, "// argument {} ({}) not in IDL", id, value_kind(value));
+        }
+    }
+    let call = format!(
+        "client.{}({}).await?;",
+        rust_ident(&snake_case(&method.name)),
+        args.join(", ")
+    );
+    if method.returns.is_some() {
+        let _ = writeln!(out, "let resp = {}", call);
+    } else {
+        let _ = writeln!(out, "{}", call);
+    }
+    out
+}
+
+fn find(fields: &[(i16, ThriftValue)], id: i16) -> Option<&ThriftValue> {
+    fields.iter().find(|(i, _)| *i == id).map(|(_, v)| v)
+}
+
+// 字段或参数的值与行尾注释
+fn field_value(
+    idl: &Idl,
+    field: &Field,
+    value: Option<&ThriftValue>,
+    indent: usize,
+) -> (String, String) {
+    let Some(value) = value else {
+        let code = if field.optional {
+            "None"
+        } else {
+            "Default::default()"
+        };
+        return (
+            code.to_string(),
+            " // not in the captured message".to_string(),
+        );
+    };
+    match rust_value(idl, &field.ty, value, indent) {
+        Some(code) if field.optional => (format!("Some({})", code), String::new()),
+        Some(code) => (code, String::new()),
+        None => (
+            "Default::default()".to_string(),
+            format!(
+                " // captured {}, IDL declares {}",
+                value_kind(value),
+                type_name(&field.ty)
+            ),
+        ),
+    }
+}
+
+// 值与 IDL 类型不符时返回 None
+fn rust_value(idl: &Idl, ty: &Type, value: &ThriftValue, indent: usize) -> Option<String> {
+    let code = match (idl.resolve(ty), value) {
+        (Type::Bool, ThriftValue::Bool(v)) => v.to_string(),
+        (Type::Byte, ThriftValue::Byte(v)) => v.to_string(),
+        (Type::I16, ThriftValue::I16(v)) => v.to_string(),
+        (Type::I32, ThriftValue::I32(v)) => v.to_string(),
+        (Type::I64, ThriftValue::I64(v)) => v.to_string(),
+        (Type::Double, ThriftValue::Double(v)) => rust_double(*v),
+        (Type::String, ThriftValue::String(v)) => {
+            format!("{:?}.into()", String::from_utf8_lossy(v))
+        }
+        (Type::String, ThriftValue::TruncatedString(v, len)) => format!(
+            "{:?}.into() /* truncated, {} bytes on the wire */",
+            String::from_utf8_lossy(v),
+            len
+        ),
+        (Type::Binary, ThriftValue::String(v)) => {
+            format!("::pilota::Bytes::from_static(b\"{}\")", v.escape_ascii())
+        }
+        (Type::Named(name), ThriftValue::I32(v)) if idl.is_enum(name) => {
+            match idl.enum_name(name, *v) {
+                Some(variant) => format!("{}::{}", upper_camel(name), variant),
+                None => format!("{}::from({})", upper_camel(name), v),
+            }
+        }
+        (Type::Named(name), ThriftValue::Struct(fields)) => {
+            struct_literal(idl, name, fields, indent)?
+        }
+        (Type::List(elem), ThriftValue::List(_, elems)) => {
+            format!("vec![{}]", rust_elems(idl, elem, elems, indent)?)
+        }
+        (Type::Set(_), ThriftValue::Set(_, elems)) if elems.is_empty() => {
+            "Default::default()".to_string()
+        }
+        (Type::Set(elem), ThriftValue::Set(_, elems)) => format!(
+            "[{}].into_iter().collect()",
+            rust_elems(idl, elem, elems, indent)?
+        ),
+        (Type::Map(..), ThriftValue::Map(_, _, entries)) if entries.is_empty() => {
+            "Default::default()".to_string()
+        }
+        (Type::Map(key, val), ThriftValue::Map(_, _, entries)) => {
+            let entries = entries
+                .iter()
+                .map(|(k, v)| {
+                    Some(format!(
+                        "({}, {})",
+                        rust_value(idl, key, k, indent)?,
+                        rust_value(idl, val, v, indent)?
+                    ))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            format!("[{}].into_iter().collect()", entries.join(", "))
+        }
+        _ => return None,
+    };
+    Some(code)
+}
+
+// 列出 IDL 中的全部字段，结构体字面量才能编译；抓到但 IDL 中没有的字段以注释列出
+fn struct_literal(
+    idl: &Idl,
+    name: &str,
+    fields: &[(i16, ThriftValue)],
+    indent: usize,
+) -> Option<String> {
+    let defs = idl.struct_fields(name)?;
+    let pad = "    ".repeat(indent + 1);
+    let mut out = format!("{} {{\n", upper_camel(name));
+    for def in defs {
+        let (code, note) = field_value(idl, def, find(fields, def.id), indent + 1);
+        let _ = writeln!(
+            out,
+            "{}{}: {},{}",
+            pad,
+            rust_ident(&snake_case(&def.name)),
+            code,
+            note
+        );
+    }
+    for (id, value) in fields {
+        if !defs.iter().any(|def| def.id == *id) {
+            let _ = writeln!(
+                out,
+                "{}// field {} ({}) not in IDL",
+                pad,
+                id,
+                value_kind(value)
+            );
+        }
+    }
+    let _ = write!(out, "{}}}", "    ".repeat(indent));
+    Some(out)
+}
+
+fn rust_elems(idl: &Idl, ty: &Type, elems: &[ThriftValue], indent: usize) -> Option<String> {
+    let elems = elems
+        .iter()
+        .map(|v| rust_value(idl, ty, v, indent))
+        .collect::<Option<Vec<_>>>()?;
+    Some(elems.join(", "))
+}
+
+fn rust_double(v: f64) -> String {
+    if v.is_nan() {
+        "f64::NAN".to_string()
+    } else if v.is_infinite() {
+        if v > 0.0 {
+            "f64::INFINITY"
+        } else {
+            "f64::NEG_INFINITY"
+        }
+        .to_string()
+    } else {
+        format!("{:?}", v)
+    }
+}
+
+fn value_kind(value: &ThriftValue) -> &'static str {
+    match value {
+        ThriftValue::Bool(_) => "bool",
+        ThriftValue::Byte(_) => "byte",
+        ThriftValue::Double(_) => "double",
+        ThriftValue::I16(_) => "i16",
+        ThriftValue::I32(_) => "i32",
+        ThriftValue::I64(_) => "i64",
+        ThriftValue::String(_) | ThriftValue::TruncatedString(..) => "string",
+        ThriftValue::Struct(_) => "struct",
+        ThriftValue::Map(..) => "map",
+        ThriftValue::Set(..) => "set",
+        ThriftValue::List(..) => "list",
+    }
+}
+
+fn type_name(ty: &Type) -> String {
+    match ty {
+        Type::Bool => "bool".to_string(),
+        Type::Byte => "byte".to_string(),
+        Type::I16 => "i16".to_string(),
+        Type::I32 => "i32".to_string(),
+        Type::I64 => "i64".to_string(),
+        Type::Double => "double".to_string(),
+        Type::String => "string".to_string(),
+        Type::Binary => "binary".to_string(),
+        Type::List(elem) => format!("list<{}>", type_name(elem)),
+        Type::Set(elem) => format!("set<{}>", type_name(elem)),
+        Type::Map(key, value) => format!("map<{}, {}>", type_name(key), type_name(value)),
+        Type::Named(name) => name.clone(),
+    }
+}
+
+// 与 Rust 关键字同名的字段在生成的代码中是 raw identifier
+fn rust_ident(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern",
+        "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+        "pub", "ref", "return", "static", "struct", "trait", "true", "type", "unsafe", "use",
+        "where", "while",
+    ];
+    if KEYWORDS.contains(&name) {
+        format!("r#{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+// IDL 中的 GetItem、itemId、HTTPStatus 在生成的代码中是 get_item、item_id、http_status
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() {
+            let prev = i.checked_sub(1).map(|p| chars[p]);
+            let next = chars.get(i + 1);
+            let boundary = prev.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit())
+                || (prev.is_some_and(|p| p.is_ascii_uppercase())
+                    && next.is_some_and(|n| n.is_ascii_lowercase()));
+            if boundary && !out.ends_with('_') {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
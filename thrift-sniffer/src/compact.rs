@@ -0,0 +1,75 @@
+use crate::PrintOptions;
+use std::collections::HashSet;
+use thrift_sniffer::decode::{self, DecodeError, Message, ThriftValue};
+
+// CompactProtocol 报文的输出，格式与 parse_thrift_binary 一致
+// 解码统一由 decode::decode_message 完成，这里只负责打印；解码结果不含字段 offset，字段行没有 @0x 前缀
+pub fn print_message(decoded: Result<&Message, &DecodeError>, opts: &PrintOptions) {
+    let msg = match decoded {
+        Ok(msg) => msg,
+        Err(e) => {
+            println!("Failed to decode compact message: {}", e);
+            return;
+        }
+    };
+    println!(
+        "Message Type: {} (0x{:02X})",
+        decode::message_type_name(msg.message_type),
+        msg.message_type
+    );
+    let (service, method) = decode::split_multiplexed(&msg.name);
+    if let Some(service) = service {
+        println!("Service Name: {}", service);
+    }
+    println!("Method Name: {}", method);
+    println!("Sequence ID: {}", msg.seq_id);
+
+    // Exception 消息体是标准的 TApplicationException；Reply 中非 0 的字段为 IDL 声明的异常
+    if let Some(exception) = msg.exception() {
+        println!("{}", exception);
+    }
+    if let Some(field) = msg.declared_exception_field() {
+        println!("Reply carries a declared exception in field {}.", field);
+    }
+
+    println!("\n--- Begin Fields ---");
+    print_fields(&msg.body, opts);
+    println!("Field STOP (0x00)");
+    println!("--- End Fields ---\n");
+}
+
+// 嵌套深度已由解码器限制在 decode::MAX_DEPTH 以内
+fn print_fields(fields: &[(i16, ThriftValue)], opts: &PrintOptions) {
+    let mut seen = HashSet::new();
+    for (field_id, value) in fields {
+        if !seen.insert(*field_id) {
+            println!("Warning: duplicate field id {}.", field_id);
+        }
+        match value {
+            ThriftValue::Struct(fields) => {
+                println!("field {} Start of struct:", field_id);
+                print_fields(fields, opts);
+                println!("End of struct (STOP).");
+            }
+            ThriftValue::I64(v) => {
+                let suffix = opts.i64_suffix(*field_id as u16, *v);
+                println!("field {} (i64): {}{}", field_id, v, suffix);
+            }
+            ThriftValue::String(v) => {
+                println!("field {} (string): {}", field_id, String::from_utf8_lossy(v));
+            }
+            ThriftValue::TruncatedString(v, len) => println!(
+                "field {} (string): {}... ({} bytes, truncated)",
+                field_id,
+                String::from_utf8_lossy(v),
+                len
+            ),
+            _ => println!(
+                "field {} ({}): {}",
+                field_id,
+                decode::type_name(value.type_code()),
+                value
+            ),
+        }
+    }
+}
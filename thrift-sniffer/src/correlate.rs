@@ -0,0 +1,124 @@
+use crate::flow::Flow;
+use clap::ValueEnum;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+use thrift_sniffer::decode::Message;
+
+// 请求 id 所在的 THeader 键；persistent header 在线上带有 RPC_PERSIST_ 等前缀，按后缀匹配
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+// 未完成的调用超过这个数量时，清理超过 PENDING_TTL 仍未收到回包的调用
+const EVICT_THRESHOLD: usize = 10_000;
+const PENDING_TTL: Duration = Duration::from_secs(60);
+
+// Call 与 Reply 的配对方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum CorrelateBy {
+    /// 按消息层的 seq id 配对（同一连接上的 seq id 可能被复用）
+    SeqId,
+    /// 按 THeader 中的 x-request-id 配对，缺失时退回 seq id
+    RequestId,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Key {
+    SeqId(i32),
+    RequestId(String),
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Key::SeqId(id) => write!(f, "seq id {}", id),
+            Key::RequestId(id) => write!(f, "request id {}", id),
+        }
+    }
+}
+
+struct Pending {
+    method: String,
+    seq_id: i32,
+    at: Instant,
+}
+
+// 以 (客户端 -> 服务端 流, key) 记录未完成的调用，回包沿反方向的流查找
+pub struct Correlator {
+    by: CorrelateBy,
+    pending: HashMap<(Flow, Key), Pending>,
+}
+
+impl Correlator {
+    pub fn new(by: CorrelateBy) -> Self {
+        Self {
+            by,
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn observe(&mut self, flow: &Flow, msg: &Message, headers: &[(String, String)]) {
+        let key = self.key(msg, headers);
+        if msg.is_call() {
+            if self.pending.len() >= EVICT_THRESHOLD {
+                self.pending.retain(|_, call| call.at.elapsed() < PENDING_TTL);
+            }
+            self.pending.insert(
+                (*flow, key),
+                Pending {
+                    method: msg.method().to_string(),
+                    seq_id: msg.seq_id,
+                    at: Instant::now(),
+                },
+            );
+            return;
+        }
+
+        let call_flow = Flow {
+            src: flow.dst,
+            dst: flow.src,
+            vlan: flow.vlan,
+        };
+        // 服务端不一定在回包中带回 request id，此时按 seq id 查找
+        let mut found = self.pending.remove_entry(&(call_flow, key.clone()));
+        if found.is_none() {
+            if let Key::RequestId(_) = key {
+                found = self.take_by_seq_id(call_flow, msg.seq_id);
+            }
+        }
+        match found {
+            Some(((_, key), call)) => println!(
+                "Correlated reply to {} by {}: {:.1} ms",
+                call.method,
+                key,
+                call.at.elapsed().as_secs_f64() * 1000.0
+            ),
+            None => println!("No matching call found for reply by {}.", key),
+        }
+    }
+
+    fn key(&self, msg: &Message, headers: &[(String, String)]) -> Key {
+        if self.by == CorrelateBy::RequestId {
+            if let Some(id) = request_id(headers) {
+                return Key::RequestId(id.to_string());
+            }
+        }
+        Key::SeqId(msg.seq_id)
+    }
+
+    // 调用按 request id 记录、回包却没有 request id 时，找同一连接上 seq id 相同的调用
+    fn take_by_seq_id(&mut self, flow: Flow, seq_id: i32) -> Option<((Flow, Key), Pending)> {
+        let key = self
+            .pending
+            .iter()
+            .find(|((f, _), call)| *f == flow && call.seq_id == seq_id)
+            .map(|(k, _)| k.clone())?;
+        self.pending.remove_entry(&key)
+    }
+}
+
+fn request_id(headers: &[(String, String)]) -> Option<&str> {
+    headers.iter().find_map(|(k, v)| {
+        let k = k.to_ascii_lowercase().replace('_', "-");
+        k.ends_with(REQUEST_ID_HEADER).then_some(v.as_str())
+    })
+}
@@ -0,0 +1,1268 @@
+#[cfg(feature = "zlib")]
+use flate2::read::ZlibDecoder;
+use serde_json::{json, Value};
+use std::borrow::Cow;
+use std::fmt;
+#[cfg(feature = "zlib")]
+use std::io::Read;
+
+// Binary 协议中的字段类型编码，解码库与命令行的字段解析共用
+pub mod ttype {
+    pub const STOP: u8 = 0;
+    pub const BOOL: u8 = 2;
+    pub const BYTE: u8 = 3;
+    pub const DOUBLE: u8 = 4;
+    pub const I16: u8 = 6;
+    pub const I32: u8 = 8;
+    pub const I64: u8 = 10;
+    pub const STRING: u8 = 11;
+    pub const STRUCT: u8 = 12;
+    pub const MAP: u8 = 13;
+    pub const SET: u8 = 14;
+    pub const LIST: u8 = 15;
+}
+
+// 结构体嵌套的最大深度，防止构造的报文导致栈溢出；sniffer 的打印路径也使用同一上限
+pub const MAX_DEPTH: usize = 64;
+
+// 解码后的 Thrift 值
+#[derive(Debug, Clone, PartialEq)]
+pub enum ThriftValue {
+    Bool(bool),
+    Byte(i8),
+    Double(f64),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    String(Vec<u8>),
+    // 超过 max_string_size 的字符串只保留前缀，第二个字段为声明的长度
+    TruncatedString(Vec<u8>, usize),
+    Struct(Vec<(i16, ThriftValue)>),
+    Map(u8, u8, Vec<(ThriftValue, ThriftValue)>),
+    Set(u8, Vec<ThriftValue>),
+    List(u8, Vec<ThriftValue>),
+}
+
+// 解码失败的原因，offset 均相对于传入的字节切片
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    PayloadTooShort { len: usize },
+    NotTHeader { magic: u16 },
+    UnsupportedProtocol { id: u8 },
+    THeaderTooLarge { header_end: usize, len: usize },
+    NoBinaryPayload { from: usize },
+    TrailingBytes { frame_end: usize, len: usize },
+    UnsupportedTransform { id: u8 },
+    Inflate { message: String },
+    InflatedTooLarge { limit: usize },
+    BadVersion { word: u32 },
+    UnexpectedEof { at: usize, needed: usize, remaining: usize },
+    NegativeLength { len: i32, at: usize },
+    LengthTooLarge { declared: usize, limit: usize, at: usize },
+    UnknownType { code: u8, at: usize },
+    DepthExceeded { at: usize },
+    InvalidVarint { at: usize },
+}
+
+impl DecodeError {
+    // 用于按失败类型分类统计
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DecodeError::PayloadTooShort { .. } => "payload-too-short",
+            DecodeError::NotTHeader { .. } => "not-theader",
+            DecodeError::UnsupportedProtocol { .. } => "unsupported-protocol",
+            DecodeError::THeaderTooLarge { .. } => "theader-too-large",
+            DecodeError::NoBinaryPayload { .. } => "no-binary-payload",
+            DecodeError::TrailingBytes { .. } => "trailing-bytes",
+            DecodeError::UnsupportedTransform { .. } => "unsupported-transform",
+            DecodeError::Inflate { .. } => "inflate",
+            DecodeError::InflatedTooLarge { .. } => "inflated-too-large",
+            DecodeError::BadVersion { .. } => "bad-version",
+            DecodeError::UnexpectedEof { .. } => "unexpected-eof",
+            DecodeError::NegativeLength { .. } => "negative-length",
+            DecodeError::LengthTooLarge { .. } => "length-too-large",
+            DecodeError::UnknownType { .. } => "unknown-type",
+            DecodeError::DepthExceeded { .. } => "depth-exceeded",
+            DecodeError::InvalidVarint { .. } => "invalid-varint",
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::PayloadTooShort { len } => {
+                write!(f, "Payload too short for THeader: {} bytes.", len)
+            }
+            DecodeError::NotTHeader { magic } => write!(
+                f,
+                "Not a THeader frame: magic 0x{:04X} at offset 4, expected 0x{:04X}. Skipping.",
+                magic, THEADER_MAGIC
+            ),
+            DecodeError::UnsupportedProtocol { id } => write!(
+                f,
+                "Unsupported THeader protocol id 0x{:02X}; only Binary and Compact are decoded.",
+                id
+            ),
+            DecodeError::THeaderTooLarge { header_end, len } => write!(
+                f,
+                "Invalid payload or THeader too large: header ends at offset {}, payload is {} bytes.",
+                header_end, len
+            ),
+            DecodeError::NoBinaryPayload { from } => write!(
+                f,
+                "Unable to find valid Thrift Binary payload after offset {}.",
+                from
+            ),
+            DecodeError::TrailingBytes { frame_end, len } => write!(
+                f,
+                "Frame ends at offset {} but payload is {} bytes; trailing bytes rejected.",
+                frame_end, len
+            ),
+            DecodeError::UnsupportedTransform { id } => write!(
+                f,
+                "Unsupported THeader transform 0x{:02X}; payload cannot be decoded.",
+                id
+            ),
+            DecodeError::Inflate { message } => {
+                write!(f, "Failed to inflate zlib payload: {}.", message)
+            }
+            DecodeError::InflatedTooLarge { limit } => write!(
+                f,
+                "Inflated zlib payload exceeds {} bytes; decoding stopped.",
+                limit
+            ),
+            DecodeError::BadVersion { word } => write!(
+                f,
+                "Unexpected Thrift binary version 0x{:08X} at offset 0.",
+                word
+            ),
+            DecodeError::UnexpectedEof {
+                at,
+                needed,
+                remaining,
+            } => write!(
+                f,
+                "Unexpected end of data at offset {}: need {} bytes, {} left.",
+                at, needed, remaining
+            ),
+            DecodeError::NegativeLength { len, at } => {
+                write!(f, "Negative length {} at offset {}.", len, at)
+            }
+            DecodeError::LengthTooLarge {
+                declared,
+                limit,
+                at,
+            } => write!(
+                f,
+                "String of {} bytes at offset {} exceeds limit of {} bytes.",
+                declared, at, limit
+            ),
+            DecodeError::UnknownType { code, at } => write!(
+                f,
+                "Unknown or unhandled type 0x{:02X} at offset {}.",
+                code, at
+            ),
+            DecodeError::DepthExceeded { at } => {
+                write!(f, "Struct nesting too deep at offset {}.", at)
+            }
+            DecodeError::InvalidVarint { at } => write!(
+                f,
+                "Invalid varint at offset {}: longer than {} bytes or overflows 64 bits.",
+                at, MAX_VARINT_LEN
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+type Result<T> = std::result::Result<T, DecodeError>;
+
+// 64 位 varint 最多占 10 个字节
+const MAX_VARINT_LEN: usize = 10;
+
+// 解码 CompactProtocol 使用的 ULEB128 varint，返回值与其后的 offset
+// 超过 10 个字节仍有续位或超出 64 位的视为非法，避免对不可信输入无限读取
+pub fn decode_varint(data: &[u8], offset: usize) -> Result<(u64, usize)> {
+    let mut value = 0u64;
+    for i in 0..MAX_VARINT_LEN {
+        let Some(&byte) = data.get(offset + i) else {
+            return Err(DecodeError::UnexpectedEof {
+                at: offset + i,
+                needed: 1,
+                remaining: 0,
+            });
+        };
+        // 第 10 个字节只剩最高 1 位可用，更大的值会溢出 64 位
+        if i == MAX_VARINT_LEN - 1 && byte > 1 {
+            return Err(DecodeError::InvalidVarint { at: offset });
+        }
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((value, offset + i + 1));
+        }
+    }
+    Err(DecodeError::InvalidVarint { at: offset })
+}
+
+// 解码选项
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DecodeOptions {
+    // 单个 string/binary 字段的最大长度
+    pub max_string_size: Option<usize>,
+}
+
+// 解码后的一条 Thrift 消息
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub message_type: u8,
+    pub name: String,
+    pub seq_id: i32,
+    pub body: Vec<(i16, ThriftValue)>,
+}
+
+impl Message {
+    pub fn is_call(&self) -> bool {
+        self.message_type == 0x01 || self.message_type == 0x04
+    }
+
+    // 多路复用时的服务名
+    pub fn service(&self) -> Option<&str> {
+        split_multiplexed(&self.name).0
+    }
+
+    // 去掉多路复用服务名前缀后的方法名
+    pub fn method(&self) -> &str {
+        split_multiplexed(&self.name).1
+    }
+
+    // Exception 消息按 TApplicationException 解释
+    pub fn exception(&self) -> Option<ApplicationException> {
+        (self.message_type == 0x03).then(|| ApplicationException::from_body(&self.body))
+    }
+
+    // Reply 的结果结构体中 field 0 为返回值，其余字段为 IDL 中声明的异常
+    pub fn declared_exception_field(&self) -> Option<i16> {
+        if self.message_type != 0x02 {
+            return None;
+        }
+        self.body.first().map(|(id, _)| *id).filter(|&id| id != 0)
+    }
+
+    // 一条消息对应一个 JSON 对象，字段树同 ThriftValue::to_json
+    pub fn to_json(&self) -> Value {
+        let mut value = json!({
+            "message_type": message_type_name(self.message_type),
+            "service": self.service(),
+            "method": self.method(),
+            "seq_id": self.seq_id,
+            "fields": ThriftValue::Struct(self.body.clone()).to_json(),
+        });
+        if let Some(exception) = self.exception() {
+            value["exception"] = json!({
+                "message": exception.message,
+                "type": exception.kind_name(),
+                "type_id": exception.kind,
+            });
+        }
+        if let Some(field) = self.declared_exception_field() {
+            value["declared_exception_field"] = json!(field);
+        }
+        value
+    }
+}
+
+// 多路复用协议中方法名编码为 `ServiceName:method`，按第一个 ':' 拆分
+// 没有 ':' 或 ':' 某一侧为空（不是合法的多路复用名）时原样返回
+pub fn split_multiplexed(name: &str) -> (Option<&str>, &str) {
+    match name.split_once(':') {
+        Some((service, method)) if !service.is_empty() && !method.is_empty() => {
+            (Some(service), method)
+        }
+        _ => (None, name),
+    }
+}
+
+// 标准的 TApplicationException：field 1 为 message，field 2 为 type
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApplicationException {
+    pub message: Option<String>,
+    pub kind: Option<i32>,
+}
+
+impl ApplicationException {
+    // 按 TApplicationException 的字段布局解释 Exception 消息体
+    pub fn from_body(body: &[(i16, ThriftValue)]) -> Self {
+        let mut exception = ApplicationException {
+            message: None,
+            kind: None,
+        };
+        for (id, value) in body {
+            match (id, value) {
+                (1, ThriftValue::String(s)) => {
+                    exception.message = Some(String::from_utf8_lossy(s).into_owned())
+                }
+                (2, ThriftValue::I32(kind)) => exception.kind = Some(*kind),
+                _ => (),
+            }
+        }
+        exception
+    }
+
+    pub fn kind_name(&self) -> &'static str {
+        self.kind.map_or("UNKNOWN", exception_type_name)
+    }
+}
+
+impl fmt::Display for ApplicationException {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Exception: {} (type={}",
+            self.message.as_deref().unwrap_or("<no message>"),
+            self.kind_name()
+        )?;
+        if let Some(kind) = self.kind {
+            write!(f, ", {}", kind)?;
+        }
+        write!(f, ")")
+    }
+}
+
+pub fn exception_type_name(kind: i32) -> &'static str {
+    match kind {
+        0 => "UNKNOWN",
+        1 => "UNKNOWN_METHOD",
+        2 => "INVALID_MESSAGE_TYPE",
+        3 => "WRONG_METHOD_NAME",
+        4 => "BAD_SEQUENCE_ID",
+        5 => "MISSING_RESULT",
+        6 => "INTERNAL_ERROR",
+        7 => "PROTOCOL_ERROR",
+        8 => "INVALID_TRANSFORM",
+        9 => "INVALID_PROTOCOL",
+        10 => "UNSUPPORTED_CLIENT_TYPE",
+        _ => "UNRECOGNIZED",
+    }
+}
+
+pub fn message_type_name(message_type: u8) -> &'static str {
+    match message_type {
+        0x01 => "Call",
+        0x02 => "Reply",
+        0x03 => "Exception",
+        0x04 => "Oneway",
+        _ => "Unknown",
+    }
+}
+
+pub fn type_name(ttype: u8) -> &'static str {
+    match ttype {
+        ttype::BOOL => "bool",
+        ttype::BYTE => "byte",
+        ttype::DOUBLE => "double",
+        ttype::I16 => "i16",
+        ttype::I32 => "i32",
+        ttype::I64 => "i64",
+        ttype::STRING => "string",
+        ttype::STRUCT => "struct",
+        ttype::MAP => "map",
+        ttype::SET => "set",
+        ttype::LIST => "list",
+        _ => "unknown",
+    }
+}
+
+// THeader 固定头部：帧长(u32) | magic 0x1000(u16) | flags(u16) | seq id(u32) | 头部长度(u16，以 4 字节为单位)
+// 变长头部紧随其后：protocol id(u8)、transform 个数(u8) 与各 transform id(u8)，之后是若干 info 块
+pub const THEADER_MAGIC: u16 = 0x1000;
+const THEADER_FIXED_LEN: usize = 14;
+
+// 变长头部中的 protocol id
+pub const THEADER_PROTOCOL_BINARY: u8 = 0x00;
+pub const THEADER_PROTOCOL_COMPACT: u8 = 0x02;
+
+// 按字段解析出的 THeader 头部
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct THeader {
+    pub flags: u16,
+    pub seq_id: i32,
+    pub protocol_id: u8,
+    pub transforms: Vec<u8>,
+    // info 块的起始位置与整个头部的结束位置（即消息体起点），均相对于帧起点
+    pub info_start: usize,
+    pub header_end: usize,
+}
+
+fn is_theader(payload: &[u8]) -> bool {
+    payload.len() >= 6 && u16::from_be_bytes([payload[4], payload[5]]) == THEADER_MAGIC
+}
+
+// 解析 THeader 头部；magic 不符时返回 NotTHeader，不再向后寻找版本字节
+pub fn parse_theader(payload: &[u8]) -> Result<THeader> {
+    if payload.len() < THEADER_FIXED_LEN {
+        return Err(DecodeError::PayloadTooShort { len: payload.len() });
+    }
+    let mut r = Reader {
+        data: payload,
+        offset: 4,
+        max_string_size: None,
+    };
+    let magic = r.u16()?;
+    if magic != THEADER_MAGIC {
+        return Err(DecodeError::NotTHeader { magic });
+    }
+    let flags = r.u16()?;
+    let seq_id = r.i32()?;
+    let header_end = THEADER_FIXED_LEN + r.u16()? as usize * 4;
+    if header_end > payload.len() {
+        return Err(DecodeError::THeaderTooLarge {
+            header_end,
+            len: payload.len(),
+        });
+    }
+
+    r.data = &payload[..header_end];
+    let protocol_id = r.u8()?;
+    if !matches!(protocol_id, THEADER_PROTOCOL_BINARY | THEADER_PROTOCOL_COMPACT) {
+        return Err(DecodeError::UnsupportedProtocol { id: protocol_id });
+    }
+    let count = r.u8()? as usize;
+    let transforms = r.take(count)?.to_vec();
+    Ok(THeader {
+        flags,
+        seq_id,
+        protocol_id,
+        transforms,
+        info_start: r.offset,
+        header_end,
+    })
+}
+
+// THeader 传输层自带的 seq id，与消息层的 seq id 相互独立
+// 两者通常相同，但客户端实现有误时可能分别递增或其中一个恒为 0
+pub fn theader_seq_id(payload: &[u8]) -> Option<i32> {
+    parse_theader(payload).ok().map(|header| header.seq_id)
+}
+
+// 读取 THeader 中的 info header（字符串键值与整数键值），按出现顺序返回
+// 0x01 为字符串键值，0x10 为整数键值，0x11 为 ACL token，0x00 为填充
+pub fn theader_headers(payload: &[u8]) -> Result<Vec<(String, String)>> {
+    let header = parse_theader(payload)?;
+    let header_end = header.header_end;
+    let mut r = Reader {
+        data: &payload[..header_end],
+        offset: header.info_start,
+        max_string_size: None,
+    };
+
+    let mut headers = Vec::new();
+    while r.offset < header_end {
+        match r.u8()? {
+            0x01 => {
+                for _ in 0..r.u16()? {
+                    let key = r.u16_string()?;
+                    let value = r.u16_string()?;
+                    headers.push((key, value));
+                }
+            }
+            0x10 => {
+                for _ in 0..r.u16()? {
+                    let key = r.u16()?.to_string();
+                    let value = r.u16_string()?;
+                    headers.push((key, value));
+                }
+            }
+            0x11 => {
+                r.u16_string()?;
+            }
+            // 填充或未知的 info 类型，其后的内容无法解析
+            _ => break,
+        }
+    }
+    Ok(headers)
+}
+
+// THeader transform id
+pub const TRANSFORM_ZLIB: u8 = 0x01;
+// 解压后消息体的上限，抓到的数据不可信，防止压缩炸弹耗尽内存
+pub const MAX_INFLATED_SIZE: usize = 16 << 20;
+
+// 读取 THeader 的 transform 列表，消息体按列表顺序依次还原
+pub fn theader_transforms(payload: &[u8]) -> Result<Vec<u8>> {
+    parse_theader(payload).map(|header| header.transforms)
+}
+
+// 剥离传输层并还原 THeader transform（目前支持 zlib，需开启 zlib feature），返回 BinaryProtocol 报文
+// 没有 transform 时与 strip_theader 相同，不复制数据
+pub fn strip_transport(payload: &[u8]) -> Result<Cow<'_, [u8]>> {
+    if !is_theader(payload) {
+        return strip_theader(payload).map(Cow::Borrowed);
+    }
+    let header = parse_theader(payload)?;
+    if header.transforms.is_empty() {
+        return strip_theader(payload).map(Cow::Borrowed);
+    }
+
+    // 消息体经过变换，从头部结束处开始依次还原
+    let mut body = payload[header.header_end..].to_vec();
+    for id in header.transforms {
+        body = undo_transform(id, &body)?;
+    }
+    Ok(Cow::Owned(body))
+}
+
+// 未开启 zlib feature 时没有可用的变换，body 不会被读取
+#[cfg_attr(not(feature = "zlib"), allow(unused_variables))]
+fn undo_transform(id: u8, body: &[u8]) -> Result<Vec<u8>> {
+    match id {
+        #[cfg(feature = "zlib")]
+        TRANSFORM_ZLIB => inflate(body),
+        _ => Err(DecodeError::UnsupportedTransform { id }),
+    }
+}
+
+#[cfg(feature = "zlib")]
+fn inflate(data: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    // 多读一个字节，用来区分恰好达到上限与超出上限
+    ZlibDecoder::new(data)
+        .take(MAX_INFLATED_SIZE as u64 + 1)
+        .read_to_end(&mut out)
+        .map_err(|e| DecodeError::Inflate {
+            message: e.to_string(),
+        })?;
+    if out.len() > MAX_INFLATED_SIZE {
+        return Err(DecodeError::InflatedTooLarge {
+            limit: MAX_INFLATED_SIZE,
+        });
+    }
+    Ok(out)
+}
+
+// 定位传输帧中的 BinaryProtocol 报文
+// 开头 4 字节的帧长与数据吻合时按帧长切出整帧：framed 直接取长度前缀之后的部分，THeader 再剥离头部；
+// 帧之后还有多余字节视为错误。帧长不可用（如分段不完整）时 THeader 仍按头部长度定位，
+// 其它数据才退回到从头寻找 0x80 0x01 版本字节
+pub fn strip_theader(payload: &[u8]) -> Result<&[u8]> {
+    let frame_end = match payload.get(..4) {
+        Some(prefix) => u32::from_be_bytes(prefix.try_into().unwrap()) as usize + 4,
+        None => return Err(DecodeError::PayloadTooShort { len: payload.len() }),
+    };
+    if frame_end > payload.len() || frame_end < 8 {
+        if is_theader(payload) {
+            return theader_payload(payload);
+        }
+        return find_binary(payload);
+    }
+    if frame_end < payload.len() {
+        return Err(DecodeError::TrailingBytes {
+            frame_end,
+            len: payload.len(),
+        });
+    }
+    // 0x80 0x01 为 Binary 协议，0x82 为 Compact 协议
+    if payload[4..6] == [0x80, 0x01] || payload[4] == 0x82 {
+        return Ok(&payload[4..]);
+    }
+    theader_payload(payload)
+}
+
+fn find_binary(payload: &[u8]) -> Result<&[u8]> {
+    payload
+        .windows(2)
+        .position(|w| w == [0x80, 0x01])
+        .filter(|&start| start + 4 <= payload.len())
+        .map(|start| &payload[start..])
+        .ok_or(DecodeError::NoBinaryPayload { from: 0 })
+}
+
+// 剥离 THeader，返回头部之后的 Binary 或 Compact 报文
+// 消息体的起始字节须与头部声明的 protocol id 一致
+fn theader_payload(payload: &[u8]) -> Result<&[u8]> {
+    let header = parse_theader(payload)?;
+    let body = &payload[header.header_end..];
+    let valid = match header.protocol_id {
+        THEADER_PROTOCOL_COMPACT => body.first() == Some(&0x82),
+        _ => body.starts_with(&[0x80, 0x01]),
+    };
+    if !valid || body.len() < 4 {
+        return Err(DecodeError::NoBinaryPayload {
+            from: header.header_end,
+        });
+    }
+    Ok(body)
+}
+
+// 解码一条完整的 Thrift 消息，按首字节区分 Compact（0x82）与 Binary（0x80 0x01）
+// Compact 的类型编码换算为 Binary 的 ttype，解码结果与协议无关
+pub fn decode_message(data: &[u8]) -> Result<Message> {
+    decode_message_with(data, &DecodeOptions::default())
+}
+
+// 按给定选项解码，超长字符串截断保留前缀而不整段复制
+pub fn decode_message_with(data: &[u8], options: &DecodeOptions) -> Result<Message> {
+    let mut r = Reader {
+        data,
+        offset: 0,
+        max_string_size: options.max_string_size,
+    };
+    if data.first() == Some(&COMPACT_PROTOCOL_ID) {
+        return CompactReader(r).read_message();
+    }
+
+    let message_type_and_version = r.i32()? as u32;
+    if message_type_and_version & 0xffff0000 != 0x80010000 {
+        return Err(DecodeError::BadVersion {
+            word: message_type_and_version,
+        });
+    }
+    let message_type = (message_type_and_version & 0xff) as u8;
+
+    let name_len = r.i32()? as usize;
+    let name = String::from_utf8_lossy(r.take(name_len)?).into_owned();
+    let seq_id = r.i32()?;
+    let body = r.read_struct(0)?;
+
+    Ok(Message {
+        message_type,
+        name,
+        seq_id,
+        body,
+    })
+}
+
+// 从 offset 处按类型 ttype 解码一个值，返回值与其后的 offset
+// map 的键和值、容器元素都经过同一套类型分发，结构体或容器作键时也能正确前进
+pub fn decode_value(data: &[u8], offset: usize, ttype: u8) -> Result<(ThriftValue, usize)> {
+    decode_value_with(data, offset, ttype, &DecodeOptions::default())
+}
+
+// 同 decode_value，超长字符串按 options 截断
+pub fn decode_value_with(
+    data: &[u8],
+    offset: usize,
+    ttype: u8,
+    options: &DecodeOptions,
+) -> Result<(ThriftValue, usize)> {
+    let mut r = Reader {
+        data,
+        offset,
+        max_string_size: options.max_string_size,
+    };
+    let value = r.read_value(ttype, 0)?;
+    Ok((value, r.offset))
+}
+
+// 只遍历消息检查各项限制，不构造解码结果；超长字符串直接报错
+// 按首字节区分 Compact（0x82）与 Binary（0x80 0x01）
+pub fn validate_message(data: &[u8], options: &DecodeOptions) -> Result<()> {
+    let mut r = Reader {
+        data,
+        offset: 0,
+        max_string_size: options.max_string_size,
+    };
+    if data.first() == Some(&COMPACT_PROTOCOL_ID) {
+        return CompactReader(r).skip_message();
+    }
+    r.skip_message()
+}
+
+// 一条 BinaryProtocol 消息占用的字节数，用于切分没有长度前缀的 unframed 字节流
+// 数据不完整时返回 UnexpectedEof
+pub fn message_len(data: &[u8]) -> Result<usize> {
+    let mut r = Reader {
+        data,
+        offset: 0,
+        max_string_size: None,
+    };
+    r.skip_message()?;
+    Ok(r.offset)
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+    max_string_size: Option<usize>,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if n > self.data.len() - self.offset {
+            return Err(DecodeError::UnexpectedEof {
+                at: self.offset,
+                needed: n,
+                remaining: self.data.len() - self.offset,
+            });
+        }
+        let bytes = &self.data[self.offset..self.offset + n];
+        self.offset += n;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    // THeader 中以 u16 长度为前缀的字符串
+    fn u16_string(&mut self) -> Result<String> {
+        let len = self.u16()? as usize;
+        Ok(String::from_utf8_lossy(self.take(len)?).into_owned())
+    }
+
+    fn i16(&mut self) -> Result<i16> {
+        Ok(i16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn i32(&mut self) -> Result<i32> {
+        Ok(i32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn i64(&mut self) -> Result<i64> {
+        Ok(i64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn len(&mut self) -> Result<usize> {
+        let len = self.i32()?;
+        if len < 0 {
+            return Err(DecodeError::NegativeLength {
+                len,
+                at: self.offset - 4,
+            });
+        }
+        Ok(len as usize)
+    }
+
+    fn skip_string(&mut self) -> Result<()> {
+        let len = self.len()?;
+        if let Some(max) = self.max_string_size {
+            if len > max {
+                return Err(DecodeError::LengthTooLarge {
+                    declared: len,
+                    limit: max,
+                    at: self.offset - 4,
+                });
+            }
+        }
+        self.take(len)?;
+        Ok(())
+    }
+
+    fn skip_message(&mut self) -> Result<()> {
+        let message_type_and_version = self.i32()? as u32;
+        if message_type_and_version & 0xffff0000 != 0x80010000 {
+            return Err(DecodeError::BadVersion {
+                word: message_type_and_version,
+            });
+        }
+        // 方法名不是字段，不受字符串长度上限约束
+        let name_len = self.len()?;
+        self.take(name_len)?;
+        self.i32()?;
+        self.skip_value(ttype::STRUCT, 0)
+    }
+
+    fn skip_value(&mut self, ttype: u8, depth: usize) -> Result<()> {
+        if depth > MAX_DEPTH {
+            return Err(DecodeError::DepthExceeded { at: self.offset });
+        }
+        match ttype {
+            ttype::BOOL | ttype::BYTE => self.take(1).map(drop),
+            ttype::DOUBLE | ttype::I64 => self.take(8).map(drop),
+            ttype::I16 => self.take(2).map(drop),
+            ttype::I32 => self.take(4).map(drop),
+            ttype::STRING => self.skip_string(),
+            ttype::STRUCT => loop {
+                let field_type = self.u8()?;
+                if field_type == ttype::STOP {
+                    return Ok(());
+                }
+                self.i16()?;
+                self.skip_value(field_type, depth + 1)?;
+            },
+            ttype::MAP => {
+                let key_type = self.u8()?;
+                let value_type = self.u8()?;
+                for _ in 0..self.len()? {
+                    self.skip_value(key_type, depth + 1)?;
+                    self.skip_value(value_type, depth + 1)?;
+                }
+                Ok(())
+            }
+            ttype::SET | ttype::LIST => {
+                let elem_type = self.u8()?;
+                for _ in 0..self.len()? {
+                    self.skip_value(elem_type, depth + 1)?;
+                }
+                Ok(())
+            }
+            _ => Err(DecodeError::UnknownType {
+                code: ttype,
+                at: self.offset,
+            }),
+        }
+    }
+
+    fn read_struct(&mut self, depth: usize) -> Result<Vec<(i16, ThriftValue)>> {
+        if depth > MAX_DEPTH {
+            return Err(DecodeError::DepthExceeded { at: self.offset });
+        }
+        let mut fields = Vec::new();
+        loop {
+            let field_type = self.u8()?;
+            if field_type == ttype::STOP {
+                return Ok(fields);
+            }
+            let field_id = self.i16()?;
+            fields.push((field_id, self.read_value(field_type, depth)?));
+        }
+    }
+
+    // 解码时超长字符串只复制前缀；validate 走 skip 路径，超长直接报错
+    fn string_value(&self, bytes: &[u8]) -> ThriftValue {
+        match self.max_string_size {
+            Some(max) if bytes.len() > max => {
+                ThriftValue::TruncatedString(bytes[..max].to_vec(), bytes.len())
+            }
+            _ => ThriftValue::String(bytes.to_vec()),
+        }
+    }
+
+    // 容器同样逐层递归，深度在这里检查，不只限于结构体
+    fn read_value(&mut self, ttype: u8, depth: usize) -> Result<ThriftValue> {
+        if depth > MAX_DEPTH {
+            return Err(DecodeError::DepthExceeded { at: self.offset });
+        }
+        let value = match ttype {
+            ttype::BOOL => ThriftValue::Bool(self.u8()? != 0),
+            ttype::BYTE => ThriftValue::Byte(self.u8()? as i8),
+            ttype::DOUBLE => ThriftValue::Double(f64::from_bits(self.i64()? as u64)),
+            ttype::I16 => ThriftValue::I16(self.i16()?),
+            ttype::I32 => ThriftValue::I32(self.i32()?),
+            ttype::I64 => ThriftValue::I64(self.i64()?),
+            ttype::STRING => {
+                let len = self.len()?;
+                let bytes = self.take(len)?;
+                self.string_value(bytes)
+            }
+            ttype::STRUCT => ThriftValue::Struct(self.read_struct(depth + 1)?),
+            ttype::MAP => {
+                let key_type = self.u8()?;
+                let value_type = self.u8()?;
+                let count = self.len()?;
+                let mut entries = Vec::new();
+                for _ in 0..count {
+                    let k = self.read_value(key_type, depth + 1)?;
+                    let v = self.read_value(value_type, depth + 1)?;
+                    entries.push((k, v));
+                }
+                ThriftValue::Map(key_type, value_type, entries)
+            }
+            ttype::SET | ttype::LIST => {
+                let elem_type = self.u8()?;
+                let count = self.len()?;
+                let mut elems = Vec::new();
+                for _ in 0..count {
+                    elems.push(self.read_value(elem_type, depth + 1)?);
+                }
+                if ttype == ttype::SET {
+                    ThriftValue::Set(elem_type, elems)
+                } else {
+                    ThriftValue::List(elem_type, elems)
+                }
+            }
+            _ => {
+                return Err(DecodeError::UnknownType {
+                    code: ttype,
+                    at: self.offset,
+                })
+            }
+        };
+        Ok(value)
+    }
+}
+
+// CompactProtocol 消息以协议 id 0x82 开头，第二个字节高 3 位为消息类型、低 5 位为版本
+pub const COMPACT_PROTOCOL_ID: u8 = 0x82;
+const COMPACT_VERSION: u8 = 1;
+
+// CompactProtocol 的类型编码，与 Binary 协议不同；bool 字段的值直接编码在类型里
+mod ctype {
+    pub const STOP: u8 = 0;
+    pub const BOOL_TRUE: u8 = 1;
+    pub const BOOL_FALSE: u8 = 2;
+    pub const BYTE: u8 = 3;
+    pub const I16: u8 = 4;
+    pub const I32: u8 = 5;
+    pub const I64: u8 = 6;
+    pub const DOUBLE: u8 = 7;
+    pub const BINARY: u8 = 8;
+    pub const LIST: u8 = 9;
+    pub const SET: u8 = 10;
+    pub const MAP: u8 = 11;
+    pub const STRUCT: u8 = 12;
+}
+
+// CompactProtocol 的读取，字节级的边界检查与长度上限沿用 Reader
+struct CompactReader<'a>(Reader<'a>);
+
+impl CompactReader<'_> {
+    fn varint(&mut self) -> Result<u64> {
+        let (value, next) = decode_varint(self.0.data, self.0.offset)?;
+        self.0.offset = next;
+        Ok(value)
+    }
+
+    // string/binary：varint 长度加内容
+    fn skip_binary(&mut self) -> Result<()> {
+        let at = self.0.offset;
+        let len = self.varint()? as usize;
+        if let Some(max) = self.0.max_string_size {
+            if len > max {
+                return Err(DecodeError::LengthTooLarge {
+                    declared: len,
+                    limit: max,
+                    at,
+                });
+            }
+        }
+        self.0.take(len).map(drop)
+    }
+
+    fn zigzag(&mut self) -> Result<i64> {
+        let n = self.varint()?;
+        Ok((n >> 1) as i64 ^ -((n & 1) as i64))
+    }
+
+    // 消息头：协议 id、类型与版本、varint 的 seq id（非 zigzag）、方法名，之后是参数或结果结构体
+    // 返回消息类型、seq id 与方法名
+    fn message_header(&mut self) -> Result<(u8, i32, &[u8])> {
+        let header = self.0.take(2)?;
+        if header[0] != COMPACT_PROTOCOL_ID || header[1] & 0x1f != COMPACT_VERSION {
+            return Err(DecodeError::BadVersion {
+                word: u32::from(u16::from_be_bytes([header[0], header[1]])),
+            });
+        }
+        let message_type = header[1] >> 5;
+        let seq_id = self.varint()? as i32;
+        let name_len = self.varint()? as usize;
+        let name = self.0.take(name_len)?;
+        Ok((message_type, seq_id, name))
+    }
+
+    // list/set 的头部字节高 4 位为元素个数，取 15 时个数改由其后的 varint 给出；低 4 位为元素类型
+    fn collection_header(&mut self) -> Result<(u8, u64)> {
+        let header = self.0.u8()?;
+        let count = match header >> 4 {
+            15 => self.varint()?,
+            n => u64::from(n),
+        };
+        Ok((header & 0x0f, count))
+    }
+
+    fn skip_message(&mut self) -> Result<()> {
+        self.message_header()?;
+        self.skip_value(ctype::STRUCT, 0)
+    }
+
+    fn read_message(&mut self) -> Result<Message> {
+        let (message_type, seq_id, name) = self.message_header()?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        let body = self.read_struct(0)?;
+        Ok(Message {
+            message_type,
+            name,
+            seq_id,
+            body,
+        })
+    }
+
+    // 字段头：高 4 位为 id 增量，为 0 时其后跟 zigzag varint 的完整 id；bool 字段的值即类型
+    fn read_struct(&mut self, depth: usize) -> Result<Vec<(i16, ThriftValue)>> {
+        if depth > MAX_DEPTH {
+            return Err(DecodeError::DepthExceeded { at: self.0.offset });
+        }
+        let mut fields = Vec::new();
+        let mut last_id = 0i16;
+        loop {
+            let header = self.0.u8()?;
+            let field_type = header & 0x0f;
+            if field_type == ctype::STOP {
+                return Ok(fields);
+            }
+            let field_id = match header >> 4 {
+                0 => self.zigzag()? as i16,
+                delta => last_id.wrapping_add(i16::from(delta)),
+            };
+            last_id = field_id;
+            let value = match field_type {
+                ctype::BOOL_TRUE => ThriftValue::Bool(true),
+                ctype::BOOL_FALSE => ThriftValue::Bool(false),
+                _ => self.read_value(field_type, depth)?,
+            };
+            fields.push((field_id, value));
+        }
+    }
+
+    fn read_value(&mut self, ctype: u8, depth: usize) -> Result<ThriftValue> {
+        if depth > MAX_DEPTH {
+            return Err(DecodeError::DepthExceeded { at: self.0.offset });
+        }
+        let value = match ctype {
+            // 容器中的 bool 占一个字节，1 为 true
+            ctype::BOOL_TRUE | ctype::BOOL_FALSE => {
+                ThriftValue::Bool(self.0.u8()? == ctype::BOOL_TRUE)
+            }
+            ctype::BYTE => ThriftValue::Byte(self.0.u8()? as i8),
+            ctype::I16 => ThriftValue::I16(self.zigzag()? as i16),
+            ctype::I32 => ThriftValue::I32(self.zigzag()? as i32),
+            ctype::I64 => ThriftValue::I64(self.zigzag()?),
+            // double 为小端序，与 Binary 相反
+            ctype::DOUBLE => {
+                ThriftValue::Double(f64::from_le_bytes(self.0.take(8)?.try_into().unwrap()))
+            }
+            ctype::BINARY => {
+                let len = self.varint()? as usize;
+                let bytes = self.0.take(len)?;
+                self.0.string_value(bytes)
+            }
+            ctype::STRUCT => ThriftValue::Struct(self.read_struct(depth + 1)?),
+            ctype::LIST | ctype::SET => {
+                let (elem_ctype, count) = self.collection_header()?;
+                let elem_type = self.binary_type(elem_ctype)?;
+                let mut elems = Vec::new();
+                for _ in 0..count {
+                    elems.push(self.read_value(elem_ctype, depth + 1)?);
+                }
+                if ctype == ctype::SET {
+                    ThriftValue::Set(elem_type, elems)
+                } else {
+                    ThriftValue::List(elem_type, elems)
+                }
+            }
+            // 个数为 0 时没有 key/value 类型字节，类型记为 STOP
+            ctype::MAP => {
+                let count = self.varint()?;
+                if count == 0 {
+                    return Ok(ThriftValue::Map(ttype::STOP, ttype::STOP, Vec::new()));
+                }
+                let types = self.0.u8()?;
+                let key_type = self.binary_type(types >> 4)?;
+                let value_type = self.binary_type(types & 0x0f)?;
+                let mut entries = Vec::new();
+                for _ in 0..count {
+                    let k = self.read_value(types >> 4, depth + 1)?;
+                    let v = self.read_value(types & 0x0f, depth + 1)?;
+                    entries.push((k, v));
+                }
+                ThriftValue::Map(key_type, value_type, entries)
+            }
+            _ => {
+                return Err(DecodeError::UnknownType {
+                    code: ctype,
+                    at: self.0.offset,
+                })
+            }
+        };
+        Ok(value)
+    }
+
+    // Compact 类型编码对应的 Binary ttype
+    fn binary_type(&self, ctype: u8) -> Result<u8> {
+        Ok(match ctype {
+            ctype::BOOL_TRUE | ctype::BOOL_FALSE => ttype::BOOL,
+            ctype::BYTE => ttype::BYTE,
+            ctype::I16 => ttype::I16,
+            ctype::I32 => ttype::I32,
+            ctype::I64 => ttype::I64,
+            ctype::DOUBLE => ttype::DOUBLE,
+            ctype::BINARY => ttype::STRING,
+            ctype::LIST => ttype::LIST,
+            ctype::SET => ttype::SET,
+            ctype::MAP => ttype::MAP,
+            ctype::STRUCT => ttype::STRUCT,
+            _ => {
+                return Err(DecodeError::UnknownType {
+                    code: ctype,
+                    at: self.0.offset,
+                })
+            }
+        })
+    }
+
+    fn skip_value(&mut self, ctype: u8, depth: usize) -> Result<()> {
+        if depth > MAX_DEPTH {
+            return Err(DecodeError::DepthExceeded { at: self.0.offset });
+        }
+        match ctype {
+            // 容器中的 bool 占一个字节
+            ctype::BOOL_TRUE | ctype::BOOL_FALSE | ctype::BYTE => self.0.take(1).map(drop),
+            ctype::I16 | ctype::I32 | ctype::I64 => self.varint().map(drop),
+            ctype::DOUBLE => self.0.take(8).map(drop),
+            ctype::BINARY => self.skip_binary(),
+            ctype::LIST | ctype::SET => {
+                let (elem_ctype, count) = self.collection_header()?;
+                for _ in 0..count {
+                    self.skip_value(elem_ctype, depth + 1)?;
+                }
+                Ok(())
+            }
+            // 个数为 0 时没有 key/value 类型字节
+            ctype::MAP => {
+                let count = self.varint()?;
+                if count == 0 {
+                    return Ok(());
+                }
+                let types = self.0.u8()?;
+                for _ in 0..count {
+                    self.skip_value(types >> 4, depth + 1)?;
+                    self.skip_value(types & 0x0f, depth + 1)?;
+                }
+                Ok(())
+            }
+            // 字段头的格式见 read_struct；bool 字段没有值字节
+            ctype::STRUCT => loop {
+                let header = self.0.u8()?;
+                let field_type = header & 0x0f;
+                if field_type == ctype::STOP {
+                    return Ok(());
+                }
+                if header >> 4 == 0 {
+                    self.varint()?;
+                }
+                if !matches!(field_type, ctype::BOOL_TRUE | ctype::BOOL_FALSE) {
+                    self.skip_value(field_type, depth + 1)?;
+                }
+            },
+            _ => Err(DecodeError::UnknownType {
+                code: ctype,
+                at: self.0.offset,
+            }),
+        }
+    }
+}
+
+impl ThriftValue {
+    pub fn type_code(&self) -> u8 {
+        match self {
+            ThriftValue::Bool(_) => ttype::BOOL,
+            ThriftValue::Byte(_) => ttype::BYTE,
+            ThriftValue::Double(_) => ttype::DOUBLE,
+            ThriftValue::I16(_) => ttype::I16,
+            ThriftValue::I32(_) => ttype::I32,
+            ThriftValue::I64(_) => ttype::I64,
+            ThriftValue::String(_) | ThriftValue::TruncatedString(..) => ttype::STRING,
+            ThriftValue::Struct(_) => ttype::STRUCT,
+            ThriftValue::Map(..) => ttype::MAP,
+            ThriftValue::Set(..) => ttype::SET,
+            ThriftValue::List(..) => ttype::LIST,
+        }
+    }
+
+    // 结构体字段展开为 {id, type, value}，保留线上的类型信息
+    pub fn to_json(&self) -> Value {
+        match self {
+            ThriftValue::Bool(v) => json!(v),
+            ThriftValue::Byte(v) => json!(v),
+            ThriftValue::Double(v) => json!(v),
+            ThriftValue::I16(v) => json!(v),
+            ThriftValue::I32(v) => json!(v),
+            ThriftValue::I64(v) => json!(v),
+            ThriftValue::String(v) => json!(String::from_utf8_lossy(v)),
+            ThriftValue::TruncatedString(v, len) => json!({
+                "prefix": String::from_utf8_lossy(v),
+                "len": len,
+                "truncated": true,
+            }),
+            ThriftValue::Struct(fields) => Value::Array(
+                fields
+                    .iter()
+                    .map(|(id, v)| {
+                        json!({
+                            "id": id,
+                            "type": type_name(v.type_code()),
+                            "value": v.to_json(),
+                        })
+                    })
+                    .collect(),
+            ),
+            ThriftValue::Map(_, _, entries) => Value::Array(
+                entries
+                    .iter()
+                    .map(|(k, v)| json!({ "key": k.to_json(), "value": v.to_json() }))
+                    .collect(),
+            ),
+            ThriftValue::Set(_, elems) | ThriftValue::List(_, elems) => {
+                Value::Array(elems.iter().map(ThriftValue::to_json).collect())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} seq={} {}",
+            message_type_name(self.message_type),
+            self.name,
+            self.seq_id,
+            ThriftValue::Struct(self.body.clone())
+        )
+    }
+}
+
+impl fmt::Display for ThriftValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThriftValue::Bool(v) => write!(f, "{}", v),
+            ThriftValue::Byte(v) => write!(f, "{}", v),
+            ThriftValue::Double(v) => write!(f, "{}", v),
+            ThriftValue::I16(v) => write!(f, "{}", v),
+            ThriftValue::I32(v) => write!(f, "{}", v),
+            ThriftValue::I64(v) => write!(f, "{}", v),
+            ThriftValue::String(v) => write!(f, "\"{}\"", String::from_utf8_lossy(v)),
+            ThriftValue::TruncatedString(v, len) => write!(
+                f,
+                "\"{}\"... ({} bytes, truncated)",
+                String::from_utf8_lossy(v),
+                len
+            ),
+            ThriftValue::Struct(fields) => {
+                write!(f, "{{")?;
+                for (i, (id, v)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", id, v)?;
+                }
+                write!(f, "}}")
+            }
+            ThriftValue::Map(_, _, entries) => {
+                write!(f, "{{")?;
+                for (i, (k, v)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{} => {}", k, v)?;
+                }
+                write!(f, "}}")
+            }
+            ThriftValue::Set(_, elems) | ThriftValue::List(_, elems) => {
+                write!(f, "[")?;
+                for (i, v) in elems.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", v)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use anyhow::Result;
+use pnet::packet::ethernet::EtherTypes;
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::tcp::TcpPacket;
+use pnet::packet::Packet;
+use std::path::Path;
+use thrift_sniffer::decode::{self, Message, ThriftValue};
+use thrift_sniffer::pcap_reader;
+
+// 对比两个抓包中同一方法的调用，按调用顺序逐条对齐
+// 不按 seq id 对齐：两次抓包的 seq id 起点不同，无法跨抓包匹配；
+// 而 THeader 与消息体各有一个 seq id，客户端实现有误时两者也不一定一致
+// skip_truncated 为 true 时不解码被 snaplen 截断的帧
+pub fn run(
+    a: &Path,
+    b: &Path,
+    method: Option<&str>,
+    ports: &[u16],
+    skip_truncated: bool,
+) -> Result<()> {
+    let messages_a = load_messages(a, method, ports, skip_truncated)?;
+    let messages_b = load_messages(b, method, ports, skip_truncated)?;
+
+    let (calls_a, replies_a): (Vec<_>, Vec<_>) = messages_a.into_iter().partition(|m| m.is_call());
+    let (calls_b, replies_b): (Vec<_>, Vec<_>) = messages_b.into_iter().partition(|m| m.is_call());
+
+    let mut differences = 0;
+    differences += diff_sequence("call", &calls_a, &calls_b);
+    differences += diff_sequence("reply", &replies_a, &replies_b);
+
+    println!("{} difference(s) found.", differences);
+    Ok(())
+}
+
+fn load_messages(
+    path: &Path,
+    method: Option<&str>,
+    ports: &[u16],
+    skip_truncated: bool,
+) -> Result<Vec<Message>> {
+    let mut messages = Vec::new();
+    pcap_reader::for_each_frame(path, |frame| {
+        if frame.truncated && skip_truncated {
+            return;
+        }
+        let Some(payload) = tcp_payload(frame.data, ports) else {
+            return;
+        };
+        let Ok(data) = decode::strip_theader(&payload) else {
+            return;
+        };
+        match decode::decode_message(data) {
+            Ok(msg) if method.is_none_or(|m| method_matches(msg.method(), m)) => {
+                messages.push(msg)
+            }
+            Ok(_) => (),
+            Err(e) => eprintln!("{}: skipping undecodable message: {}", path.display(), e),
+        }
+    })?;
+    Ok(messages)
+}
+
+fn tcp_payload(frame: &[u8], ports: &[u16]) -> Option<Vec<u8>> {
+    // 与抓包路径一样先剥离 VLAN 标签
+    let (ethertype, packet, _) = crate::strip_ethernet(frame)?;
+    if ethertype != EtherTypes::Ipv4 {
+        return None;
+    }
+    let ipv4 = Ipv4Packet::new(packet)?;
+    if ipv4.get_next_level_protocol() != IpNextHeaderProtocols::Tcp {
+        return None;
+    }
+    let tcp = TcpPacket::new(ipv4.payload())?;
+    if !ports.contains(&tcp.get_source()) && !ports.contains(&tcp.get_destination()) {
+        return None;
+    }
+    Some(tcp.payload().to_vec())
+}
+
+// IDL 中的方法名与命令行里习惯的写法可能不同，如 GetItem 与 get_item
+fn method_matches(name: &str, method: &str) -> bool {
+    let normalize = |s: &str| s.replace('_', "").to_ascii_lowercase();
+    normalize(name) == normalize(method)
+}
+
+fn diff_sequence(kind: &str, a: &[Message], b: &[Message]) -> usize {
+    let mut differences = 0;
+    for (i, (ma, mb)) in a.iter().zip(b).enumerate() {
+        let mut lines = Vec::new();
+        if ma.message_type != mb.message_type {
+            lines.push(format!(
+                "  ~ message type: {} -> {}",
+                decode::message_type_name(ma.message_type),
+                decode::message_type_name(mb.message_type)
+            ));
+        }
+        diff_fields("", &ma.body, &mb.body, &mut lines);
+        if !lines.is_empty() {
+            println!("{} #{} ({}):", kind, i, ma.name);
+            for line in &lines {
+                println!("{}", line);
+            }
+            differences += lines.len();
+        }
+    }
+
+    if a.len() != b.len() {
+        println!(
+            "{} count differs: {} in first capture, {} in second; extra ones are not compared.",
+            kind,
+            a.len(),
+            b.len()
+        );
+        differences += 1;
+    }
+    differences
+}
+
+fn diff_fields(
+    prefix: &str,
+    a: &[(i16, ThriftValue)],
+    b: &[(i16, ThriftValue)],
+    lines: &mut Vec<String>,
+) {
+    for (id, va) in a {
+        let path = format!("{}{}", prefix, id);
+        match b.iter().find(|(other, _)| other == id) {
+            None => lines.push(format!("  - {}: {}", path, va)),
+            Some((_, vb)) => match (va, vb) {
+                (ThriftValue::Struct(fa), ThriftValue::Struct(fb)) => {
+                    diff_fields(&format!("{}.", path), fa, fb, lines)
+                }
+                _ if va != vb => lines.push(format!("  ~ {}: {} -> {}", path, va, vb)),
+                _ => (),
+            },
+        }
+    }
+    for (id, vb) in b {
+        if !a.iter().any(|(other, _)| other == id) {
+            lines.push(format!("  + {}{}: {}", prefix, id, vb));
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use crate::decode::{Message, ThriftValue};
+
+// BinaryProtocol 编码，decode 的逆过程：Binary 消息经 decode_message 解出后重新编码与原字节一致；Compact 消息编码为等价的 Binary
+// （bool 以 0/1 编码；TruncatedString 只能写出保留的前缀，无法还原原消息）
+
+// 编码单个值，不含类型与字段 id；结构体以 STOP 结尾
+pub fn encode_binary(value: &ThriftValue) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_value(&mut out, value);
+    out
+}
+
+// 编码完整的消息：版本与消息类型、方法名、seq id，之后是参数或结果结构体
+pub fn encode_message(msg: &Message) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&(0x8001_0000u32 | msg.message_type as u32).to_be_bytes());
+    write_bytes(&mut out, msg.name.as_bytes());
+    out.extend_from_slice(&msg.seq_id.to_be_bytes());
+    write_struct(&mut out, &msg.body);
+    out
+}
+
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_len(out, bytes.len());
+    out.extend_from_slice(bytes);
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) {
+    out.extend_from_slice(&(len as i32).to_be_bytes());
+}
+
+fn write_struct(out: &mut Vec<u8>, fields: &[(i16, ThriftValue)]) {
+    for (id, value) in fields {
+        out.push(value.type_code());
+        out.extend_from_slice(&id.to_be_bytes());
+        write_value(out, value);
+    }
+    out.push(0);
+}
+
+fn write_value(out: &mut Vec<u8>, value: &ThriftValue) {
+    match value {
+        ThriftValue::Bool(v) => out.push(*v as u8),
+        ThriftValue::Byte(v) => out.push(*v as u8),
+        ThriftValue::Double(v) => out.extend_from_slice(&v.to_bits().to_be_bytes()),
+        ThriftValue::I16(v) => out.extend_from_slice(&v.to_be_bytes()),
+        ThriftValue::I32(v) => out.extend_from_slice(&v.to_be_bytes()),
+        ThriftValue::I64(v) => out.extend_from_slice(&v.to_be_bytes()),
+        ThriftValue::String(v) | ThriftValue::TruncatedString(v, _) => write_bytes(out, v),
+        ThriftValue::Struct(fields) => write_struct(out, fields),
+        ThriftValue::Map(key_type, value_type, entries) => {
+            out.push(*key_type);
+            out.push(*value_type);
+            write_len(out, entries.len());
+            for (k, v) in entries {
+                write_value(out, k);
+                write_value(out, v);
+            }
+        }
+        ThriftValue::Set(elem_type, elems) | ThriftValue::List(elem_type, elems) => {
+            out.push(*elem_type);
+            write_len(out, elems.len());
+            for elem in elems {
+                write_value(out, elem);
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+
+// 单向 TCP 流，由源地址、目的地址与所在 VLAN 确定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Flow {
+    pub src: SocketAddr,
+    pub dst: SocketAddr,
+    pub vlan: VlanTags,
+}
+
+impl fmt::Display for Flow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.src, self.dst)?;
+        if !self.vlan.is_empty() {
+            write!(f, " (VLAN {})", self.vlan)?;
+        }
+        Ok(())
+    }
+}
+
+// 以太网帧中 802.1Q 标签的 VLAN id，从外到内排列；QinQ 最多记录两层，更多的层只剥离不记录
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct VlanTags {
+    ids: [u16; 2],
+    len: usize,
+}
+
+impl VlanTags {
+    pub fn push(&mut self, id: u16) {
+        if self.len < self.ids.len() {
+            self.ids[self.len] = id;
+            self.len += 1;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+// 外层与内层以 '.' 分隔，如 100.200
+impl fmt::Display for VlanTags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, id) in self.ids[..self.len].iter().enumerate() {
+            if i > 0 {
+                write!(f, ".")?;
+            }
+            write!(f, "{}", id)?;
+        }
+        Ok(())
+    }
+}
+
+// --flow 指定的单条连接：`客户端[:端口]-服务端:端口`，两个方向的报文都匹配
+// 省略客户端端口时匹配该客户端发往服务端的任意连接
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlowFilter {
+    client_ip: IpAddr,
+    client_port: Option<u16>,
+    server: SocketAddr,
+}
+
+impl FlowFilter {
+    pub fn matches(&self, flow: &Flow) -> bool {
+        (self.is_client(flow.src) && flow.dst == self.server)
+            || (self.is_client(flow.dst) && flow.src == self.server)
+    }
+
+    fn is_client(&self, addr: SocketAddr) -> bool {
+        addr.ip() == self.client_ip && self.client_port.is_none_or(|port| addr.port() == port)
+    }
+}
+
+impl FromStr for FlowFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (client, server) = s
+            .split_once('-')
+            .ok_or_else(|| format!("expected CLIENT[:PORT]-SERVER:PORT, got {:?}", s))?;
+        let server: SocketAddr = server
+            .parse()
+            .map_err(|e| format!("invalid server address {:?}: {}", server, e))?;
+        let (client_ip, client_port) = match client.parse::<SocketAddr>() {
+            Ok(addr) => (addr.ip(), Some(addr.port())),
+            Err(_) => (
+                client
+                    .parse::<IpAddr>()
+                    .map_err(|e| format!("invalid client address {:?}: {}", client, e))?,
+                None,
+            ),
+        };
+        Ok(Self {
+            client_ip,
+            client_port,
+            server,
+        })
+    }
+}
+
+impl fmt::Display for FlowFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.client_port {
+            Some(port) => write!(f, "{} <-> {}", SocketAddr::new(self.client_ip, port), self.server),
+            None => write!(f, "{}:* <-> {}", self.client_ip, self.server),
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use anyhow::{bail, Context, Result};
+use std::fs::{self, File, Metadata};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use thrift_sniffer::pcap_reader::Frame;
+
+// 读到文件末尾后等待新数据的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+// pcap 全局头中以太网的 link type
+const LINKTYPE_ETHERNET: u32 = 1;
+// 单条记录长度的上限，超过说明文件已损坏
+const MAX_CAPLEN: usize = 1 << 20;
+
+// 像 tail -f 一样持续读取正在写入的 pcap 文件，回调参数为完整的以太网帧
+// 文件被轮转（inode 变化）或截断后从头重新打开；只支持经典 pcap 格式，不支持 pcapng
+pub fn follow(path: &Path, mut f: impl FnMut(Frame)) -> Result<()> {
+    loop {
+        let mut file = wait_for_header(path)?;
+        let little_endian = read_global_header(&mut file, path)?;
+        let id = file_id(&file.metadata()?);
+        let mut pos = file.stream_position()?;
+        loop {
+            match read_record(&mut file, little_endian) {
+                Ok((frame, truncated)) => {
+                    pos = file.stream_position()?;
+                    f(Frame {
+                        data: &frame,
+                        truncated,
+                    });
+                }
+                // 记录还没写完整，回到记录开头等待
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    file.seek(SeekFrom::Start(pos))?;
+                    if replaced(path, id, pos) {
+                        eprintln!("{} was rotated or truncated, reopening", path.display());
+                        break;
+                    }
+                    thread::sleep(POLL_INTERVAL);
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to read {}", path.display()))
+                }
+            }
+        }
+    }
+}
+
+// 文件不存在或全局头尚未写出时等待
+fn wait_for_header(path: &Path) -> Result<File> {
+    loop {
+        match File::open(path) {
+            Ok(file) if file.metadata()?.len() >= 24 => return Ok(file),
+            Ok(_) => (),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to open pcap file {}", path.display()))
+            }
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+// 返回记录头是否为小端
+fn read_global_header(file: &mut File, path: &Path) -> Result<bool> {
+    let mut header = [0u8; 24];
+    file.read_exact(&mut header)?;
+    let little_endian = match header[0..4] {
+        [0xd4, 0xc3, 0xb2, 0xa1] | [0x4d, 0x3c, 0xb2, 0xa1] => true,
+        [0xa1, 0xb2, 0xc3, 0xd4] | [0xa1, 0xb2, 0x3c, 0x4d] => false,
+        _ => bail!("{} is not a pcap file (pcapng cannot be followed)", path.display()),
+    };
+    let link_type = read_u32(&header[20..24], little_endian);
+    if link_type != LINKTYPE_ETHERNET {
+        bail!("Unsupported link type {} in {}", link_type, path.display());
+    }
+    Ok(little_endian)
+}
+
+// 返回帧数据，以及抓包长度是否小于线上原始长度（被 snaplen 截断）
+fn read_record(file: &mut File, little_endian: bool) -> io::Result<(Vec<u8>, bool)> {
+    let mut header = [0u8; 16];
+    file.read_exact(&mut header)?;
+    let caplen = read_u32(&header[8..12], little_endian) as usize;
+    let len = read_u32(&header[12..16], little_endian) as usize;
+    if caplen > MAX_CAPLEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("record length {} exceeds {}", caplen, MAX_CAPLEN),
+        ));
+    }
+    let mut frame = vec![0u8; caplen];
+    file.read_exact(&mut frame)?;
+    Ok((frame, caplen < len))
+}
+
+fn read_u32(bytes: &[u8], little_endian: bool) -> u32 {
+    let bytes: [u8; 4] = bytes.try_into().unwrap();
+    if little_endian {
+        u32::from_le_bytes(bytes)
+    } else {
+        u32::from_be_bytes(bytes)
+    }
+}
+
+// 路径指向了另一个文件，或文件比已读取的位置还短
+// 轮转过程中路径暂时不存在时继续等待
+fn replaced(path: &Path, id: Option<u64>, pos: u64) -> bool {
+    match fs::metadata(path) {
+        Ok(meta) => file_id(&meta) != id || meta.len() < pos,
+        Err(_) => false,
+    }
+}
+
+#[cfg(unix)]
+fn file_id(meta: &Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(meta.ino())
+}
+
+// 非 unix 平台没有 inode，只能通过截断检测轮转
+#[cfg(not(unix))]
+fn file_id(_meta: &Metadata) -> Option<u64> {
+    None
+}
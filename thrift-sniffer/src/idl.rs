@@ -0,0 +1,475 @@
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+
+// .thrift 文件中的类型，typedef 与 include 前缀在解析时保留，由 Idl::resolve 展开
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Type {
+    Bool,
+    Byte,
+    I16,
+    I32,
+    I64,
+    Double,
+    String,
+    Binary,
+    List(Box<Type>),
+    Set(Box<Type>),
+    Map(Box<Type>, Box<Type>),
+    // 结构体、枚举或 typedef 的名字，已去掉 include 前缀
+    Named(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub id: i16,
+    pub name: String,
+    pub ty: Type,
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Method {
+    pub name: String,
+    pub oneway: bool,
+    // void 方法为 None
+    pub returns: Option<Type>,
+    pub args: Vec<Field>,
+}
+
+// 解析后的 IDL，只保留按字段 id 还原名字与类型所需的部分；常量、注解与默认值被跳过
+// 多个文件（如 include 的文件）可以依次 parse 进同一个 Idl
+#[derive(Debug, Default)]
+pub struct Idl {
+    structs: HashMap<String, Vec<Field>>,
+    // 枚举值到名字
+    enums: HashMap<String, Vec<(i32, String)>>,
+    typedefs: HashMap<String, Type>,
+    methods: HashMap<String, Method>,
+}
+
+impl Idl {
+    pub fn parse(&mut self, src: &str) -> Result<()> {
+        let mut p = Parser {
+            tokens: tokenize(src)?,
+            pos: 0,
+        };
+        while let Some(token) = p.next() {
+            let (keyword, line) = (token.text.clone(), token.line);
+            match keyword.as_str() {
+                "namespace" => {
+                    p.next_ident()?;
+                    p.next_ident()?;
+                }
+                "include" | "cpp_include" => {
+                    p.next_kind(Kind::Literal)?;
+                }
+                "typedef" => {
+                    let ty = p.parse_type()?;
+                    let name = p.next_ident()?;
+                    self.typedefs.insert(name, ty);
+                }
+                "const" => {
+                    p.parse_type()?;
+                    p.next_ident()?;
+                    p.expect("=")?;
+                    p.skip_value()?;
+                }
+                "enum" => {
+                    let name = p.next_ident()?;
+                    let values = p.parse_enum_body()?;
+                    self.enums.insert(name, values);
+                }
+                "struct" | "union" | "exception" => {
+                    let name = p.next_ident()?;
+                    p.expect("{")?;
+                    let fields = p.parse_fields("}")?;
+                    self.structs.insert(name, fields);
+                }
+                "service" => {
+                    p.next_ident()?;
+                    if p.eat("extends") {
+                        p.next_ident()?;
+                    }
+                    p.expect("{")?;
+                    while !p.eat("}") {
+                        let method = p.parse_method()?;
+                        self.methods.insert(method.name.clone(), method);
+                    }
+                }
+                _ => bail!("line {}: unexpected `{}`", line, keyword),
+            }
+            p.skip_annotations()?;
+            p.eat_separator();
+        }
+        Ok(())
+    }
+
+    pub fn method(&self, name: &str) -> Option<&Method> {
+        self.methods.get(name)
+    }
+
+    pub fn struct_fields(&self, name: &str) -> Option<&[Field]> {
+        self.structs.get(name).map(Vec::as_slice)
+    }
+
+    pub fn enum_name(&self, name: &str, value: i32) -> Option<&str> {
+        self.enums
+            .get(name)?
+            .iter()
+            .find(|(v, _)| *v == value)
+            .map(|(_, n)| n.as_str())
+    }
+
+    pub fn is_enum(&self, name: &str) -> bool {
+        self.enums.contains_key(name)
+    }
+
+    // 展开 typedef，最多展开 16 层，防止循环定义
+    pub fn resolve<'a>(&'a self, mut ty: &'a Type) -> &'a Type {
+        for _ in 0..16 {
+            match ty {
+                Type::Named(name) => match self.typedefs.get(name) {
+                    Some(target) => ty = target,
+                    None => return ty,
+                },
+                _ => return ty,
+            }
+        }
+        ty
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Ident,
+    Number,
+    Literal,
+    Punct,
+}
+
+#[derive(Debug)]
+struct Token {
+    kind: Kind,
+    text: String,
+    line: usize,
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = src.chars().peekable();
+    let mut line = 1;
+    while let Some(&c) = chars.peek() {
+        match c {
+            '\n' => {
+                line += 1;
+                chars.next();
+            }
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '#' => while chars.next_if(|&c| c != '\n').is_some() {},
+            '/' => {
+                chars.next();
+                match chars.next() {
+                    Some('/') => while chars.next_if(|&c| c != '\n').is_some() {},
+                    Some('*') => {
+                        let mut prev = ' ';
+                        loop {
+                            match chars.next() {
+                                Some('/') if prev == '*' => break,
+                                Some(c) => {
+                                    if c == '\n' {
+                                        line += 1;
+                                    }
+                                    prev = c;
+                                }
+                                None => bail!("line {}: unterminated comment", line),
+                            }
+                        }
+                    }
+                    _ => bail!("line {}: unexpected `/`", line),
+                }
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some(q) if q == c => break,
+                        Some('\\') => text.extend(chars.next()),
+                        Some(ch) => text.push(ch),
+                        None => bail!("line {}: unterminated string", line),
+                    }
+                }
+                tokens.push(Token {
+                    kind: Kind::Literal,
+                    text,
+                    line,
+                });
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '+' => {
+                let mut text = String::new();
+                while let Some(ch) =
+                    chars.next_if(|c| c.is_ascii_alphanumeric() || "+-.".contains(*c))
+                {
+                    text.push(ch);
+                }
+                tokens.push(Token {
+                    kind: Kind::Number,
+                    text,
+                    line,
+                });
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut text = String::new();
+                while let Some(ch) =
+                    chars.next_if(|c| c.is_alphanumeric() || *c == '_' || *c == '.')
+                {
+                    text.push(ch);
+                }
+                tokens.push(Token {
+                    kind: Kind::Ident,
+                    text,
+                    line,
+                });
+            }
+            _ => {
+                chars.next();
+                tokens.push(Token {
+                    kind: Kind::Punct,
+                    text: c.to_string(),
+                    line,
+                });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or(self.tokens.last())
+            .map_or(0, |t| t.line)
+    }
+
+    fn next_kind(&mut self, kind: Kind) -> Result<String> {
+        let line = self.line();
+        match self.next() {
+            Some(t) if t.kind == kind => Ok(t.text.clone()),
+            Some(t) => bail!("line {}: expected {:?}, found `{}`", line, kind, t.text),
+            None => bail!("line {}: expected {:?}, found end of file", line, kind),
+        }
+    }
+
+    fn next_ident(&mut self) -> Result<String> {
+        self.next_kind(Kind::Ident)
+    }
+
+    fn eat(&mut self, text: &str) -> bool {
+        if self
+            .peek()
+            .is_some_and(|t| t.text == text && t.kind != Kind::Literal)
+        {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, text: &str) -> Result<()> {
+        if !self.eat(text) {
+            let found = self.peek().map_or("end of file", |t| t.text.as_str());
+            bail!(
+                "line {}: expected `{}`, found `{}`",
+                self.line(),
+                text,
+                found
+            );
+        }
+        Ok(())
+    }
+
+    fn eat_separator(&mut self) {
+        let _ = self.eat(",") || self.eat(";");
+    }
+
+    // 类型与字段后的 (key = "value", ...) 注解
+    fn skip_annotations(&mut self) -> Result<()> {
+        if self.eat("(") {
+            self.skip_until(")")?;
+        }
+        Ok(())
+    }
+
+    // 跳过到与已读入的左括号配对的 close 为止
+    fn skip_until(&mut self, close: &str) -> Result<()> {
+        let mut depth = 1;
+        while depth > 0 {
+            let line = self.line();
+            let Some(token) = self.next() else {
+                bail!("line {}: expected `{}`, found end of file", line, close);
+            };
+            if token.kind != Kind::Punct {
+                continue;
+            }
+            match token.text.as_str() {
+                "(" | "[" | "{" => depth += 1,
+                ")" | "]" | "}" => depth -= 1,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn skip_value(&mut self) -> Result<()> {
+        if self.eat("[") {
+            self.skip_until("]")
+        } else if self.eat("{") {
+            self.skip_until("}")
+        } else {
+            self.next();
+            Ok(())
+        }
+    }
+
+    fn parse_type(&mut self) -> Result<Type> {
+        let name = self.next_ident()?;
+        let ty = match name.as_str() {
+            "bool" => Type::Bool,
+            "byte" | "i8" => Type::Byte,
+            "i16" => Type::I16,
+            "i32" => Type::I32,
+            "i64" => Type::I64,
+            "double" => Type::Double,
+            "string" => Type::String,
+            "binary" => Type::Binary,
+            "list" | "set" => {
+                self.expect("<")?;
+                let elem = Box::new(self.parse_type()?);
+                self.expect(">")?;
+                if name == "list" {
+                    Type::List(elem)
+                } else {
+                    Type::Set(elem)
+                }
+            }
+            "map" => {
+                self.expect("<")?;
+                let key = Box::new(self.parse_type()?);
+                self.expect(",")?;
+                let value = Box::new(self.parse_type()?);
+                self.expect(">")?;
+                Type::Map(key, value)
+            }
+            // include 的类型写作 file.Type
+            _ => Type::Named(name.rsplit('.').next().unwrap_or(&name).to_string()),
+        };
+        self.skip_annotations()?;
+        Ok(ty)
+    }
+
+    // 字段列表，直到 close；没写 id 的字段按 Thrift 的规则从 -1 开始递减编号
+    fn parse_fields(&mut self, close: &str) -> Result<Vec<Field>> {
+        let mut fields = Vec::new();
+        let mut implicit_id = 0;
+        while !self.eat(close) {
+            let id = if self.peek().is_some_and(|t| t.kind == Kind::Number) {
+                let line = self.line();
+                let text = self.next_kind(Kind::Number)?;
+                self.expect(":")?;
+                text.parse()
+                    .with_context(|| format!("line {}: invalid field id `{}`", line, text))?
+            } else {
+                implicit_id -= 1;
+                implicit_id
+            };
+            let optional = self.eat("optional");
+            if !optional {
+                self.eat("required");
+            }
+            let ty = self.parse_type()?;
+            let name = self.next_ident()?;
+            if self.eat("=") {
+                self.skip_value()?;
+            }
+            self.skip_annotations()?;
+            self.eat_separator();
+            fields.push(Field {
+                id,
+                name,
+                ty,
+                optional,
+            });
+        }
+        Ok(fields)
+    }
+
+    fn parse_enum_body(&mut self) -> Result<Vec<(i32, String)>> {
+        self.expect("{")?;
+        let mut values = Vec::new();
+        let mut next = 0;
+        while !self.eat("}") {
+            let name = self.next_ident()?;
+            if self.eat("=") {
+                let line = self.line();
+                let text = self.next_kind(Kind::Number)?;
+                next = parse_int(&text)
+                    .with_context(|| format!("line {}: invalid enum value `{}`", line, text))?;
+            }
+            self.skip_annotations()?;
+            self.eat_separator();
+            values.push((next, name));
+            next += 1;
+        }
+        Ok(values)
+    }
+
+    fn parse_method(&mut self) -> Result<Method> {
+        let oneway = self.eat("oneway");
+        let returns = if self.eat("void") {
+            None
+        } else {
+            Some(self.parse_type()?)
+        };
+        let name = self.next_ident()?;
+        self.expect("(")?;
+        let args = self.parse_fields(")")?;
+        if self.eat("throws") {
+            self.expect("(")?;
+            self.parse_fields(")")?;
+        }
+        self.skip_annotations()?;
+        self.eat_separator();
+        Ok(Method {
+            name,
+            oneway,
+            returns,
+            args,
+        })
+    }
+}
+
+fn parse_int(text: &str) -> Result<i32, std::num::ParseIntError> {
+    match text.strip_prefix("0x") {
+        Some(hex) => i32::from_str_radix(hex, 16),
+        None => text.parse(),
+    }
+}
@@ -0,0 +1,12 @@
+// Thrift 报文解码，供 sniffer 与其他进程内调试工具共用
+pub mod decode;
+// 把解码结果重新编码为 BinaryProtocol，用于构造测试数据
+pub mod encode;
+// 解析 .thrift 文件，按字段 id 还原字段名与类型
+pub mod idl;
+// 逐帧读取 pcap 文件
+#[cfg(feature = "pcap")]
+pub mod pcap_reader;
+// 按 TCP 序号重组字节流并切分出完整消息
+pub mod reassembly;
+pub mod websocket;
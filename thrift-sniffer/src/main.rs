@@ -1,54 +1,400 @@
-use clap::Parser;
-use pnet::datalink::{self, Channel::Ethernet};
-use pnet::packet::ethernet::{EtherTypes, EthernetPacket};
-use pnet::packet::ip::IpNextHeaderProtocols;
+mod codegen;
+mod compact;
+mod correlate;
+mod diff;
+mod flow;
+mod follow;
+mod ring;
+mod schema;
+mod sqlite;
+mod stats;
+mod timestamp;
+mod tls;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use pnet::datalink;
+use pnet::packet::ethernet::{EtherType, EtherTypes, EthernetPacket};
+use pnet::packet::ip::{IpNextHeaderProtocol, IpNextHeaderProtocols};
 use pnet::packet::ipv4::Ipv4Packet;
-use pnet::packet::tcp::TcpPacket;
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::packet::tcp::{TcpFlags, TcpPacket};
 use pnet::packet::Packet;
 use anyhow::{Context, Result};
+use serde_json::json;
+use thrift_sniffer::decode::{self, ttype};
+use thrift_sniffer::idl::Idl;
+use thrift_sniffer::pcap_reader;
+use thrift_sniffer::reassembly::{Reassembled, StreamReassembler};
+use thrift_sniffer::websocket::{self, WsStream};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{self, Write};
+use codegen::Language;
+use correlate::{CorrelateBy, Correlator};
+use flow::{Flow, FlowFilter, VlanTags};
+use ring::{Ring, Trigger};
+use sqlite::SqliteSink;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
 use std::process;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use schema::SchemaInference;
+use stats::{MessageSummary, OverheadStats, SizeBreakdown};
+use timestamp::Timestamps;
+use tls::{TlsDetector, Verdict};
 
 //命令行参数
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, subcommand_negates_reqs = true)]
 struct Args {
-    #[arg(short, long)]
-    interface: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(short, long, required_unless_present_any = ["follow", "pcap"])]
+    interface: Option<String>,
+
+    /// 从保存的 pcap/pcapng 文件读取报文并解码，代替实时抓包，读完即退出
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["interface", "follow"])]
+    pcap: Option<PathBuf>,
+
+    /// 持续读取另一个进程正在写入的 pcap 文件（类似 tail -f），代替实时抓包
+    #[arg(long, value_name = "FILE", conflicts_with = "interface")]
+    follow: Option<PathBuf>,
+
+    /// 流中出现被 snaplen 截断的报文后，丢弃该流的缓冲数据，不再解码其后续报文
+    #[arg(long)]
+    skip_truncated: bool,
+
+    /// Thrift 服务端口，可重复指定以同时监听多个服务，如 -p 9090 -p 9091
+    #[arg(short, long, default_values_t = [9090])]
+    port: Vec<u16>,
+
+    /// 实时抓包时在内核中应用的 BPF 过滤表达式，如 "tcp port 9090 and host 10.0.0.2"；
+    /// 默认为 `tcp port <PORT>`。解码时仍按 --port 识别 Thrift 流量
+    #[arg(long, value_name = "BPF")]
+    filter: Option<String>,
+
+    /// 以混杂模式打开网卡，接收目的地址不是本机的报文（如交换机镜像端口）；默认关闭，只能看到本机收发的流量。
+    /// 与普通抓包一样需要 root 或 CAP_NET_RAW，切换网卡的混杂模式在部分系统上还需要 CAP_NET_ADMIN
+    #[arg(long, conflicts_with_all = ["pcap", "follow"])]
+    promiscuous: bool,
+
+    /// 按方法汇总平均协议开销，Ctrl-C 退出时打印
+    #[arg(long)]
+    stats: bool,
+
+    /// 不逐条打印报文，只按方法统计 Call/Reply/Exception 条数与消息大小分布，Ctrl-C 退出时打印
+    #[arg(long, conflicts_with_all = ["errors_only", "format", "emit_code", "correlate_by"])]
+    summary: bool,
+
+    /// 根据观察到的报文推断 .thrift 结构体定义，Ctrl-C 退出时打印
+    #[arg(long)]
+    infer_schema: bool,
+
+    /// 只输出解码失败的报文（附失败原因与原始字节）
+    #[arg(long)]
+    errors_only: bool,
+
+    /// Thrift 报文封装在 WebSocket 帧中（如经网关转发）
+    #[arg(long)]
+    websocket: bool,
+
+    /// 将解码后的消息写入 SQLite 数据库
+    #[arg(long, value_name = "FILE")]
+    sqlite: Option<PathBuf>,
+
+    /// 单个 string/binary 字段的最大长度，超出部分截断显示，不按声明长度整段复制
+    #[arg(long, value_name = "BYTES")]
+    max_string_size: Option<usize>,
+
+    /// 每条报文的十六进制 dump 最多打印的字节数，超出部分只显示剩余字节数，0 表示不限制；
+    /// 只影响 dump，解析仍使用完整报文
+    #[arg(long, value_name = "BYTES", default_value_t = 256)]
+    max_dump: usize,
+
+    /// 把落在合理时间范围内的 i64 字段额外显示为 UTC 时间
+    #[arg(long)]
+    decode_timestamps: bool,
+
+    /// 只把这些字段 id 当作时间戳，如 --timestamp-fields 3,5
+    #[arg(long, value_name = "ID", value_delimiter = ',', requires = "decode_timestamps")]
+    timestamp_fields: Vec<u16>,
 
-    #[arg(short, long, default_value_t = 9090)]
-    port: u16,
+    /// 在内存中保留最近 N 条消息，触发时写出到 --ring-dump 文件
+    #[arg(long, value_name = "N")]
+    ring: Option<usize>,
+
+    /// 环形缓冲区的触发条件
+    #[arg(long, value_enum, default_value_t = Trigger::Exception, requires = "ring")]
+    trigger: Trigger,
+
+    /// 触发后继续写出的消息条数
+    #[arg(long, value_name = "N", default_value_t = 0, requires = "ring")]
+    ring_after: usize,
+
+    /// 环形缓冲区的 dump 文件，每次触发追加写入
+    #[arg(long, value_name = "FILE", default_value = "ring-dump.txt", requires = "ring")]
+    ring_dump: PathBuf,
+
+    /// 将 Reply 与对应的 Call 配对并打印耗时
+    #[arg(long, value_enum, value_name = "KEY")]
+    correlate_by: Option<CorrelateBy>,
+
+    /// 为每个 Call 输出等价的客户端调用代码，字段名与类型取自 --idl
+    #[arg(long, value_enum, value_name = "LANG", requires = "idl")]
+    emit_code: Option<Language>,
+
+    /// 服务的 .thrift 文件，可重复指定以加入 include 的文件
+    #[arg(long, value_name = "FILE")]
+    idl: Vec<PathBuf>,
+
+    /// 只解码一条连接（双向），如 10.0.0.1:53122-10.0.0.2:9090，省略客户端端口则匹配任意端口
+    #[arg(long, value_name = "CLIENT[:PORT]-SERVER:PORT")]
+    flow: Option<FlowFilter>,
+
+    /// 输出格式；json 时每条消息输出一行 JSON 对象，便于交给 jq 等工具处理
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Format::Text,
+        conflicts_with_all = ["errors_only", "emit_code", "correlate_by"]
+    )]
+    format: Format,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+}
+
+// 抓包过程中各报文处理共享的配置与统计
+struct Session {
+    ports: Vec<u16>,
+    errors_only: bool,
+    format: Format,
+    print_options: PrintOptions,
+    stats: Option<Arc<Mutex<OverheadStats>>>,
+    summary: Option<Mutex<MessageSummary>>,
+    schema: Option<Arc<Mutex<SchemaInference>>>,
+    // 按方向区分的 WebSocket 流
+    websocket: Option<Mutex<HashMap<Flow, WsStream>>>,
+    sqlite: Option<Mutex<SqliteSink>>,
+    ring: Option<Mutex<Ring>>,
+    correlator: Option<Mutex<Correlator>>,
+    emit_code: Option<(Language, Idl)>,
+    flow: Option<FlowFilter>,
+    tls: Mutex<TlsDetector>,
+    reassembler: Mutex<StreamReassembler<Flow>>,
+    skip_truncated: bool,
+    // 出现过被 snaplen 截断的报文的流，每条流只告警一次
+    truncated_flows: Mutex<HashSet<Flow>>,
+}
+
+// 连接空闲超过这个时间后，TLS 判定与重组状态被回收
+const STREAM_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+impl Session {
+    // 是否有需要在退出时处理的缓冲数据或汇总输出
+    fn needs_finish(&self) -> bool {
+        self.stats.is_some()
+            || self.summary.is_some()
+            || self.schema.is_some()
+            || self.sqlite.is_some()
+            || self.ring.is_some()
+    }
+
+    // 提示与告警；--format json 时 stdout 只输出 JSONL 记录，其余内容改走 stderr
+    fn notice(&self, args: fmt::Arguments) {
+        match self.format {
+            Format::Text => println!("{}", args),
+            Format::Json => eprintln!("{}", args),
+        }
+    }
+
+    // 退出时写出缓冲数据并打印汇总类输出
+    fn finish(&self) {
+        if let Some(sqlite) = &self.sqlite {
+            if let Err(e) = sqlite.lock().unwrap().flush() {
+                eprintln!("Failed to write SQLite: {}", e);
+            }
+        }
+        if let Some(ring) = &self.ring {
+            if let Err(e) = ring.lock().unwrap().finish() {
+                eprintln!("Failed to dump ring buffer: {}", e);
+            }
+        }
+        // 汇总输出同样不能混进 JSONL
+        let mut out: Box<dyn Write> = match self.format {
+            Format::Text => Box::new(io::stdout()),
+            Format::Json => Box::new(io::stderr()),
+        };
+        if let Some(stats) = &self.stats {
+            if let Err(e) = stats.lock().unwrap().print(&mut out) {
+                eprintln!("Failed to print stats: {}", e);
+            }
+        }
+        if let Some(summary) = &self.summary {
+            summary.lock().unwrap().print();
+        }
+        if let Some(schema) = &self.schema {
+            if let Err(e) = schema.lock().unwrap().print(&mut out) {
+                eprintln!("Failed to print schema: {}", e);
+            }
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// 按调用顺序对比两个抓包文件中同一方法的请求与回包
+    Diff {
+        a: PathBuf,
+        b: PathBuf,
+
+        #[arg(short, long)]
+        method: Option<String>,
+
+        /// 跳过被 snaplen 截断的帧，不做解码
+        #[arg(long)]
+        skip_truncated: bool,
+    },
+    /// 列出可用于 --interface 的网卡及其地址
+    List,
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
 
+    if let Some(Command::Diff {
+        a,
+        b,
+        method,
+        skip_truncated,
+    }) = &args.command
+    {
+        return diff::run(a, b, method.as_deref(), &args.port, *skip_truncated);
+    }
+    if let Some(Command::List) = &args.command {
+        list_interfaces();
+        return Ok(());
+    }
+    let session = Arc::new(Session {
+        ports: args.port.clone(),
+        errors_only: args.errors_only,
+        format: args.format,
+        print_options: PrintOptions {
+            max_string_size: args.max_string_size,
+            max_dump: args.max_dump,
+            timestamps: args
+                .decode_timestamps
+                .then(|| Timestamps::new(args.timestamp_fields.clone())),
+        },
+        stats: args.stats.then(Default::default),
+        summary: args.summary.then(Default::default),
+        schema: args.infer_schema.then(Default::default),
+        websocket: args.websocket.then(Default::default),
+        sqlite: args
+            .sqlite
+            .as_deref()
+            .map(SqliteSink::open)
+            .transpose()?
+            .map(Mutex::new),
+        ring: args.ring.map(|capacity| {
+            Mutex::new(Ring::new(capacity, args.ring_after, args.trigger, args.ring_dump.clone()))
+        }),
+        correlator: args.correlate_by.map(|by| Mutex::new(Correlator::new(by))),
+        emit_code: args
+            .emit_code
+            .map(|language| load_idl(&args.idl).map(|idl| (language, idl)))
+            .transpose()?,
+        flow: args.flow,
+        tls: Mutex::new(TlsDetector::new(STREAM_IDLE_TIMEOUT)),
+        reassembler: Mutex::new(StreamReassembler::new(STREAM_IDLE_TIMEOUT)),
+        skip_truncated: args.skip_truncated,
+        truncated_flows: Default::default(),
+    });
+    if session.needs_finish() {
+        let session = session.clone();
+        ctrlc::set_handler(move || {
+            session.finish();
+            process::exit(0);
+        })?;
+    }
+
+    if let Some(path) = &args.pcap {
+        eprintln!("Reading {} for Thrift traffic on {}", path.display(), port_list(&args.port));
+        print_flow_filter(&args);
+        pcap_reader::for_each_frame(path, |frame| process_frame(frame, &session))?;
+        session.finish();
+        return Ok(());
+    }
+
+    if let Some(path) = &args.follow {
+        eprintln!("Following {} for Thrift traffic on {}", path.display(), port_list(&args.port));
+        print_flow_filter(&args);
+        return follow::follow(path, |frame| process_frame(frame, &session));
+    }
+
+    let interface_name = args.interface.as_deref().unwrap_or_default();
+
     // 指定的网卡
-    let interface = datalink::interfaces()
+    datalink::interfaces()
         .into_iter()
-        .find(|iface| iface.name == args.interface)
-        .with_context(|| format!("Interface {} not found", args.interface))?;
-
-    // 创建 data link 通道，拿到接收器 rx
-    let (_, mut rx) = match datalink::channel(&interface, Default::default()) {
-        Ok(Ethernet(tx, rx)) => (tx, rx),
-        Ok(_) => anyhow::bail!("Unsupported channel type"),
-        Err(e) => anyhow::bail!("Error creating channel: {}", e),
+        .find(|iface| iface.name == interface_name)
+        .with_context(|| {
+            format!(
+                "Interface {} not found (run `thrift-sniffer list` to see available interfaces)",
+                interface_name
+            )
+        })?;
+
+    // 打开抓包句柄
+    let open = pcap::Capture::from_device(interface_name)
+        .and_then(|cap| cap.promisc(args.promiscuous).immediate_mode(true).open());
+    let mut cap = match open {
+        Ok(cap) => cap,
+        Err(e) => {
+            if let Some(hint) = channel_error_hint(&e) {
+                eprintln!("Error creating channel on {}: {}", interface_name, e);
+                eprintln!("{}", hint);
+                process::exit(EXIT_NOPERM);
+            }
+            anyhow::bail!("Error creating channel: {}", e)
+        }
     };
-        
 
-    println!("Listening on {} for Thrift traffic on port {}", args.interface, args.port);
+    // 过滤在内核中完成，不匹配的报文不会复制到用户态
+    // 没有 VLAN offload 的网卡上带 802.1Q 标签的帧不匹配 tcp，需要用 vlan 原语跳过标签后再匹配一次
+    let filter = args.filter.clone().unwrap_or_else(|| {
+        let ports: Vec<_> = args.port.iter().map(|port| format!("port {}", port)).collect();
+        let ports = ports.join(" or ");
+        format!("(tcp and ({0})) or (vlan and tcp and ({0}))", ports)
+    });
+    cap.filter(&filter, true)
+        .with_context(|| format!("Invalid filter expression `{}`", filter))?;
+
+    eprintln!(
+        "Listening on {}{} for Thrift traffic on {} (filter: {})",
+        interface_name,
+        if args.promiscuous { " (promiscuous)" } else { "" },
+        port_list(&args.port),
+        filter
+    );
+    print_flow_filter(&args);
 
-    
     // 持续接收并处理每个以太网帧
     loop {
-        match rx.next() {
-            Ok(packet) => {
-                let ethernet = EthernetPacket::new(packet).unwrap();
-                match ethernet.get_ethertype() {
-                    EtherTypes::Ipv4 => process_ipv4_packet(&ethernet, args.port),
-                    _ => (),
-                }
-            }
+        match cap.next_packet() {
+            Ok(packet) => process_frame(
+                pcap_reader::Frame {
+                    data: packet.data,
+                    truncated: packet.header.caplen < packet.header.len,
+                },
+                &session,
+            ),
+            Err(pcap::Error::TimeoutExpired) => continue,
             Err(e) => {
                 eprintln!("Error receiving packet: {}", e);
                 process::exit(1);
@@ -57,73 +403,464 @@ fn main() -> Result<()> {
     }
 }
 
+// 每行一个网卡：名称、描述与地址
+fn list_interfaces() {
+    for iface in datalink::interfaces() {
+        let ips: Vec<_> = iface.ips.iter().map(|ip| ip.to_string()).collect();
+        let mut line = iface.name.clone();
+        if !iface.description.is_empty() {
+            line += &format!(" ({})", iface.description);
+        }
+        if !ips.is_empty() {
+            line += &format!(": {}", ips.join(", "));
+        }
+        println!("{}", line);
+    }
+}
+
+fn load_idl(paths: &[PathBuf]) -> Result<Idl> {
+    let mut idl = Idl::default();
+    for path in paths {
+        let src = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        idl.parse(&src)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+    }
+    Ok(idl)
+}
+
+// 启动提示中的端口列表，如 `port 9090` 或 `ports 9090, 9091`
+fn port_list(ports: &[u16]) -> String {
+    let list: Vec<_> = ports.iter().map(u16::to_string).collect();
+    match list.len() {
+        1 => format!("port {}", list[0]),
+        _ => format!("ports {}", list.join(", ")),
+    }
+}
+
+fn print_flow_filter(args: &Args) {
+    if let Some(filter) = &args.flow {
+        eprintln!("Tracking only flow {}", filter);
+    }
+}
+
+// 剥离以太网头与 802.1Q 标签（QinQ 时为多层），返回内层 EtherType、三层报文与 VLAN id
+// 过短的帧返回 None；抓包与 diff 共用
+fn strip_ethernet(frame: &[u8]) -> Option<(EtherType, &[u8], VlanTags)> {
+    let ethernet = EthernetPacket::new(frame)?;
+    // 标签：TCI(2，低 12 位为 VLAN id) + 内层 EtherType(2)
+    let mut ethertype = ethernet.get_ethertype();
+    // payload() 的生命周期跟随 ethernet，按长度从原始帧中切出以便返回
+    let mut payload = &frame[frame.len() - ethernet.payload().len()..];
+    let mut vlan = VlanTags::default();
+    while matches!(ethertype, EtherTypes::Vlan | EtherTypes::PBridge | EtherTypes::QinQ) {
+        let Some(&[tci_hi, tci_lo, type_hi, type_lo]) = payload.get(..4) else {
+            return None;
+        };
+        vlan.push(u16::from_be_bytes([tci_hi, tci_lo]) & 0x0fff);
+        ethertype = EtherType::new(u16::from_be_bytes([type_hi, type_lo]));
+        payload = &payload[4..];
+    }
+    Some((ethertype, payload, vlan))
+}
+
+// 按以太网类型分发一帧
+fn process_frame(frame: pcap_reader::Frame, session: &Session) {
+    // 过短的帧无法构造，跳过而不中断抓包
+    let Some((ethertype, payload, vlan)) = strip_ethernet(frame.data) else {
+        return;
+    };
+    match ethertype {
+        EtherTypes::Ipv4 => process_ipv4_packet(payload, vlan, frame.truncated, session),
+        EtherTypes::Ipv6 => process_ipv6_packet(payload, vlan, frame.truncated, session),
+        _ => (),
+    }
+}
+
+// 抓包权限不足时的退出码（sysexits.h 中的 EX_NOPERM）
+const EXIT_NOPERM: i32 = 77;
+
+// 创建抓包通道失败时，针对权限不足等常见原因给出可操作的提示
+fn channel_error_hint(e: &pcap::Error) -> Option<&'static str> {
+    if cfg!(windows) {
+        // Npcap 未安装或未启用 WinPcap 兼容模式时无法打开网卡
+        return Some(
+            "Capturing on Windows requires Npcap installed in WinPcap API-compatible mode, and the sniffer run as Administrator.",
+        );
+    }
+    // libpcap 只在错误信息中说明权限不足
+    match e {
+        pcap::Error::PcapError(msg) if msg.contains("permission") || msg.contains("not permitted") => Some(
+            "Capturing requires raw socket access: run as root, or grant it with `sudo setcap cap_net_raw,cap_net_admin=eip <path to thrift-sniffer>`.",
+        ),
+        _ => None,
+    }
+}
+
 // 处理 IPv4 数据包
-// 解析 TCP 数据包，检查源或目的端口是否匹配
-fn process_ipv4_packet(ethernet: &EthernetPacket, port: u16) {
-    let ipv4 = Ipv4Packet::new(ethernet.payload()).unwrap();
+fn process_ipv4_packet(packet: &[u8], vlan: VlanTags, truncated: bool, session: &Session) {
+    let Some(ipv4) = Ipv4Packet::new(packet) else {
+        return;
+    };
     if ipv4.get_next_level_protocol() == IpNextHeaderProtocols::Tcp {
-        let tcp = TcpPacket::new(ipv4.payload()).unwrap();
-        if tcp.get_source() == port || tcp.get_destination() == port {
-            process_thrift_payload(tcp.payload());
+        process_tcp_segment(
+            IpAddr::V4(ipv4.get_source()),
+            IpAddr::V4(ipv4.get_destination()),
+            ipv4.payload(),
+            vlan,
+            truncated,
+            session,
+        );
+    }
+}
+
+// 处理 IPv6 数据包
+// 沿 next header 链跳过逐跳、路由与目的选项扩展头找到 TCP；分片等其他扩展头直接跳过该包
+fn process_ipv6_packet(packet: &[u8], vlan: VlanTags, truncated: bool, session: &Session) {
+    let Some(ipv6) = Ipv6Packet::new(packet) else {
+        return;
+    };
+    let mut next_header = ipv6.get_next_header();
+    let mut payload = ipv6.payload();
+    loop {
+        match next_header {
+            IpNextHeaderProtocols::Tcp => break,
+            IpNextHeaderProtocols::Hopopt
+            | IpNextHeaderProtocols::Ipv6Route
+            | IpNextHeaderProtocols::Ipv6Opts => {
+                // 扩展头：next header(1) + 以 8 字节为单位、不含首个 8 字节的长度(1)
+                let Some(&[next, len]) = payload.get(..2) else {
+                    return;
+                };
+                let Some(rest) = payload.get((len as usize + 1) * 8..) else {
+                    return;
+                };
+                next_header = IpNextHeaderProtocol::new(next);
+                payload = rest;
+            }
+            _ => return,
+        }
+    }
+    process_tcp_segment(
+        IpAddr::V6(ipv6.get_source()),
+        IpAddr::V6(ipv6.get_destination()),
+        payload,
+        vlan,
+        truncated,
+        session,
+    );
+}
+
+// 解析 TCP 数据包，检查源或目的端口是否匹配
+fn process_tcp_segment(
+    src: IpAddr,
+    dst: IpAddr,
+    segment: &[u8],
+    vlan: VlanTags,
+    truncated: bool,
+    session: &Session,
+) {
+    let Some(tcp) = TcpPacket::new(segment) else {
+        return;
+    };
+    if session.ports.contains(&tcp.get_source()) || session.ports.contains(&tcp.get_destination()) {
+        let flow = Flow {
+            src: SocketAddr::new(src, tcp.get_source()),
+            dst: SocketAddr::new(dst, tcp.get_destination()),
+            vlan,
+        };
+        if session.flow.is_some_and(|filter| !filter.matches(&flow)) {
+            return;
+        }
+        // 截断的分段缺少尾部字节，之后的消息可能错位；收到该流第一个截断报文时就告警
+        let mut truncated_flows = session.truncated_flows.lock().unwrap();
+        if truncated && truncated_flows.insert(flow) {
+            session.notice(format_args!(
+                "Warning: {} has packets truncated by snaplen; {}",
+                flow,
+                if session.skip_truncated {
+                    "skipping the rest of this flow."
+                } else {
+                    "decoded output for it may be corrupt."
+                }
+            ));
+        }
+        if session.skip_truncated && truncated_flows.contains(&flow) {
+            session.reassembler.lock().unwrap().close(&flow);
+            if let Some(streams) = &session.websocket {
+                streams.lock().unwrap().remove(&flow);
+            }
+            return;
+        }
+        drop(truncated_flows);
+        // 加密连接的密文没法按 Thrift 解码，只提示一次
+        let closing = tcp.get_flags() & (TcpFlags::FIN | TcpFlags::RST) != 0;
+        let mut tls = session.tls.lock().unwrap();
+        let verdict = tls.check(tcp.payload(), &flow);
+        if closing {
+            tls.close(&flow);
+        }
+        drop(tls);
+        match verdict {
+            Verdict::Plaintext => {}
+            Verdict::Encrypted => return,
+            Verdict::NewTls { sni } => {
+                match sni {
+                    Some(sni) => session.notice(format_args!(
+                        "Encrypted (TLS) flow {}, cannot decode (SNI: {}).",
+                        flow, sni
+                    )),
+                    None => session.notice(format_args!(
+                        "Encrypted (TLS) flow {}, cannot decode.",
+                        flow
+                    )),
+                }
+                return;
+            }
+        }
+        match &session.websocket {
+            Some(streams) => process_websocket_payload(tcp.payload(), flow, streams, session),
+            None => {
+                // 跨多个分段的消息拼完整后再解码
+                let mut reassembler = session.reassembler.lock().unwrap();
+                reassembler.push(
+                    flow,
+                    tcp.get_sequence(),
+                    tcp.payload(),
+                    |event| match event {
+                        Reassembled::Message(msg) => process_thrift_payload(msg, &flow, session),
+                        Reassembled::Gap { missing, dropped } => session.notice(format_args!(
+                            "Warning: {} lost {} bytes; discarding {} buffered bytes to resynchronize.",
+                            flow, missing, dropped
+                        )),
+                    },
+                );
+                if closing {
+                    reassembler.close(&flow);
+                }
+            }
         }
     }
 }
 
+// 从 WebSocket 帧中取出完整消息后再按 Thrift 解析
+fn process_websocket_payload(
+    payload: &[u8],
+    flow: Flow,
+    streams: &Mutex<HashMap<Flow, WsStream>>,
+    session: &Session,
+) {
+    if payload.is_empty() || websocket::is_http_handshake(payload) {
+        return;
+    }
+    let messages = streams.lock().unwrap().entry(flow).or_default().push(payload);
+    match messages {
+        Ok(messages) => {
+            for message in messages {
+                process_thrift_payload(&message, &flow, session);
+            }
+        }
+        Err(e) => session.notice(format_args!("{}", e)),
+    }
+}
+
 //Thrift 报文预处理
-fn process_thrift_payload(payload: &[u8]) {
+fn process_thrift_payload(payload: &[u8], flow: &Flow, session: &Session) {
     if payload.len() < 16 {
         return;
     }
 
-    println!("Full Payload (hex):");
-    dump_bytes(payload);
+    if !session.errors_only && session.summary.is_none() && session.format == Format::Text {
+        println!("Full Payload (hex):");
+        dump_bytes(payload, session.print_options.max_dump);
+    }
+
+    // 剥离传输层，zlib 压缩的消息体先解压
+    let binary = match decode::strip_transport(payload) {
+        Ok(binary) => binary,
+        Err(e) => {
+            if let Some(summary) = &session.summary {
+                let method = format!("<undecodable: {}>", e.kind());
+                summary.lock().unwrap().record(&method, None, payload.len());
+                return;
+            }
+            match session.format {
+                Format::Text => report_decode_error(payload, &e, session),
+                Format::Json => print_json(flow, payload, Err(&e)),
+            }
+            return;
+        }
+    };
+
+    // 帧长度即整条报文的长度，与解码失败时记录的口径相同：framed 报文的长度前缀已由 strip_transport 核对，
+    // 含 4 字节前缀；unframed 报文没有前缀，不能从开头 4 字节读取。THeader 部分即去掉 Binary 报文后的剩余字节
+    let size = SizeBreakdown {
+        frame: payload.len(),
+        theader: payload.len().saturating_sub(binary.len()),
+        payload: binary.len(),
+    };
+    let decoded =
+        decode::decode_message_with(&binary, &session.print_options.decode_options());
+    if let Err(e) = &decoded {
+        if session.errors_only {
+            report_decode_error(payload, e, session);
+        }
+    }
+    if let Some(stats) = &session.stats {
+        let method = decoded
+            .as_ref()
+            .map(|msg| msg.method().to_string())
+            .unwrap_or_else(|e| format!("<undecodable: {}>", e.kind()));
+        stats.lock().unwrap().record(&method, size);
+    }
+    if let Some(summary) = &session.summary {
+        let method = decoded
+            .as_ref()
+            .map(|msg| msg.method().to_string())
+            .unwrap_or_else(|e| format!("<undecodable: {}>", e.kind()));
+        let message_type = decoded.as_ref().ok().map(|msg| msg.message_type);
+        summary.lock().unwrap().record(&method, message_type, size.frame);
+    }
+    if let (Some(schema), Ok(msg)) = (&session.schema, &decoded) {
+        schema.lock().unwrap().observe(msg);
+    }
+    if let (Some(sqlite), Ok(msg)) = (&session.sqlite, &decoded) {
+        if let Err(e) = sqlite.lock().unwrap().insert(flow, msg) {
+            eprintln!("Failed to write SQLite: {}", e);
+        }
+    }
+    if let (Some(ring), Ok(msg)) = (&session.ring, &decoded) {
+        if let Err(e) = ring.lock().unwrap().record(flow, msg) {
+            eprintln!("Failed to dump ring buffer: {}", e);
+        }
+    }
 
-    // THeader 协议识别
-    let protocol_id = payload[4];
-    if protocol_id != 0x10 {
-        println!("Not a THeader protocol. Skipping.");
+    if session.errors_only || session.summary.is_some() {
+        return;
+    }
+    if session.format == Format::Json {
+        print_json(flow, payload, decoded.as_ref());
         return;
     }
 
-    // 读取 header length
-    let header_len_words = payload[12] as usize;
-    let header_len = header_len_words * 4;
-    let base_header_len = 4 + 8;
-    let header_total_len = base_header_len + header_len;
+    if !flow.vlan.is_empty() {
+        println!("VLAN: {}", flow.vlan);
+    }
+    println!(
+        "Frame: {} bytes (THeader {} bytes, payload {} bytes, overhead {:.1}%)",
+        size.frame,
+        size.theader,
+        size.payload,
+        size.overhead_percent()
+    );
+    if let Some(theader_seq_id) = decode::theader_seq_id(payload) {
+        println!("THeader Seq ID: {}", theader_seq_id);
+        if let Ok(msg) = &decoded {
+            if msg.seq_id != theader_seq_id {
+                println!(
+                    "Warning: THeader seq id {} differs from message seq id {}.",
+                    theader_seq_id, msg.seq_id
+                );
+            }
+        }
+    }
+    if let Ok(transforms) = decode::theader_transforms(payload) {
+        if !transforms.is_empty() {
+            println!("THeader Transforms: {:?} (payload inflated)", transforms);
+        }
+    }
+    if let Ok(headers) = decode::theader_headers(payload) {
+        if !headers.is_empty() {
+            println!("THeader Headers:");
+            for (key, value) in &headers {
+                println!("  {}: {}", key, value);
+            }
+        }
+    }
+    println!("\nStripped THeader. Parsing payload:");
+    dump_bytes(&binary, session.print_options.max_dump);
 
-    if payload.len() <= header_total_len {
-        println!("Invalid payload or THeader too large.");
-        return;
+    // 按协议首字节区分 Compact 与 Binary
+    if binary.first() == Some(&decode::COMPACT_PROTOCOL_ID) {
+        compact::print_message(decoded.as_ref(), &session.print_options);
+    } else {
+        parse_thrift_binary(&binary, &session.print_options);
+    }
+
+    if let (Some((language, idl)), Ok(msg)) = (&session.emit_code, &decoded) {
+        if msg.is_call() {
+            println!("{}", codegen::emit(*language, idl, msg));
+        }
+    }
+    if let (Some(correlator), Ok(msg)) = (&session.correlator, &decoded) {
+        let headers = decode::theader_headers(payload).unwrap_or_default();
+        correlator.lock().unwrap().observe(flow, msg, &headers);
     }
+}
 
-    // 从 header 末尾处寻找 0x80（BinaryProtocol 版本字节）
-    let mut trans_offset = header_total_len;
-    while trans_offset < payload.len() && payload[trans_offset] != 0x80 {
-        trans_offset += 1;
+// --format json：每条消息一行，解码失败时输出失败原因；THeader 帧附带 info header
+fn print_json(
+    flow: &Flow,
+    payload: &[u8],
+    decoded: Result<&decode::Message, &decode::DecodeError>,
+) {
+    let mut value = match decoded {
+        Ok(msg) => msg.to_json(),
+        Err(e) => json!({ "error": e.kind(), "message": e.to_string() }),
+    };
+    value["flow"] = json!(flow.to_string());
+    if let Ok(headers) = decode::theader_headers(payload) {
+        let headers: serde_json::Map<_, _> =
+            headers.into_iter().map(|(k, v)| (k, json!(v))).collect();
+        value["headers"] = json!(headers);
     }
+    println!("{}", value);
+}
 
-    if trans_offset + 4 > payload.len() {
-        println!("Unable to find valid Thrift Binary payload.");
-        return;
+// 解码失败：--errors-only 时附带原始字节，便于定位问题报文
+fn report_decode_error(payload: &[u8], err: &decode::DecodeError, session: &Session) {
+    if session.errors_only {
+        println!("Decode error ({}): {}", err.kind(), err);
+        println!("Full Payload (hex):");
+        dump_bytes(payload, session.print_options.max_dump);
+        println!();
+    } else {
+        println!("{}", err);
     }
+}
 
-    println!("\nStripped THeader. Parsing BinaryProtocol payload:");
-    dump_bytes(&payload[trans_offset..]);
+// 字段打印相关的选项
+struct PrintOptions {
+    max_string_size: Option<usize>,
+    max_dump: usize,
+    timestamps: Option<Timestamps>,
+}
 
-     // Thrift BinaryProtocol 解析
-    parse_thrift_binary(&payload[trans_offset..]);
+impl PrintOptions {
+    fn decode_options(&self) -> decode::DecodeOptions {
+        decode::DecodeOptions {
+            max_string_size: self.max_string_size,
+        }
+    }
+
+    fn i64_suffix(&self, field_id: u16, value: i64) -> String {
+        self.timestamps
+            .as_ref()
+            .and_then(|t| t.annotate(field_id, value))
+            .map(|time| format!(" ({})", time))
+            .unwrap_or_default()
+    }
 }
 
-fn parse_thrift_binary(data: &[u8]) {
+fn parse_thrift_binary(data: &[u8], opts: &PrintOptions) {
     let mut offset = 0;
 
-    if data.len() < 4 {
+    // 读取 message type + version
+    let Some(bytes) = be_bytes(data, 0) else {
         println!("Data too short to contain message header.");
         return;
-    }
-
-    // 读取 message type + version
-    let message_type_and_version = u32::from_be_bytes(data[0..4].try_into().unwrap());
+    };
+    let message_type_and_version = u32::from_be_bytes(bytes);
     offset += 4;
 
     let version = message_type_and_version & 0xffff0000;
@@ -134,70 +871,99 @@ fn parse_thrift_binary(data: &[u8]) {
 
     let message_type = message_type_and_version & 0x000000ff;
 
-    let message_type_str = match message_type {
-        0x01 => "Call",
-        0x02 => "Reply",
-        0x03 => "Exception",
-        0x04 => "Oneway",
-        _ => "Unknown",
-    };
+    let message_type_str = decode::message_type_name(message_type as u8);
 
     println!("Message Type: {} (0x{:02X})", message_type_str, message_type);
+    // Thrift 只定义了 1~4，其余取值通常意味着解码起点错位
+    if !(0x01..=0x04).contains(&message_type) || message_type_and_version & 0x0000ff00 != 0 {
+        println!(
+            "Warning: raw message type word 0x{:08X} is out of range; the decoder likely started at the wrong offset (framing misalignment).",
+            message_type_and_version
+        );
+    }
 
     // 读取方法名长度 + 方法名
-    let name_len = u32::from_be_bytes(data[offset..offset+4].try_into().unwrap()) as usize;
+    let Some(bytes) = be_bytes(data, offset) else {
+        println!("Payload too short to read method name length.");
+        return;
+    };
+    let name_len = u32::from_be_bytes(bytes) as usize;
     offset += 4;
 
-    if data.len() < offset + name_len {
+    if data.len() - offset < name_len {
         println!("Payload too short to read method name.");
         return;
     }
 
     let method_name = String::from_utf8_lossy(&data[offset..offset+name_len]);
-    println!("Method Name: {}", method_name);
+    let (service, method) = decode::split_multiplexed(&method_name);
+    if let Some(service) = service {
+        println!("Service Name: {}", service);
+    }
+    println!("Method Name: {}", method);
     offset += name_len;
 
     //读取 Sequence ID
-    let seq_id = u32::from_be_bytes(data[offset..offset+4].try_into().unwrap());
+    let Some(bytes) = be_bytes(data, offset) else {
+        println!("Payload too short to read sequence ID.");
+        return;
+    };
+    let seq_id = u32::from_be_bytes(bytes);
     offset += 4;
     println!("Sequence ID: {}", seq_id);
 
+    // Exception 消息体是标准的 TApplicationException；Reply 中非 0 的字段为 IDL 声明的异常
+    if let (0x02 | 0x03, Ok(msg)) = (message_type, decode::decode_message_with(data, &opts.decode_options())) {
+        if let Some(exception) = msg.exception() {
+            println!("{}", exception);
+        }
+        if let Some(field) = msg.declared_exception_field() {
+            println!("Reply carries a declared exception in field {}.", field);
+        }
+    }
+
     //解析字段列表
     println!("\n--- Begin Fields ---");
-    while offset + 1 <= data.len() {
+    let mut seen = HashMap::new();
+    while offset < data.len() {
+        let field_start = offset;
         let field_type = data[offset];
         offset += 1;
 
-        if field_type == 0x00 {
+        if field_type == ttype::STOP {
             println!("Field STOP (0x00)");
             break;
         }
 
-        if offset + 2 > data.len() {
+        let Some(bytes) = be_bytes(data, offset) else {
             println!("Unexpected end while reading field ID.");
             break;
-        }
-
-        let field_id = u16::from_be_bytes(data[offset..offset+2].try_into().unwrap());
+        };
+        let field_id = u16::from_be_bytes(bytes);
         offset += 2;
+        warn_duplicate_field(&mut seen, field_id, field_start);
+        print_field_offset(field_start);
 
-        print!("field {} type:", field_id);
+        // 容器类型自行打印完整的一行
+        if !matches!(field_type, ttype::MAP | ttype::SET | ttype::LIST) {
+            print!("field {} type:", field_id);
+        }
         match field_type {
-            0x0A => { // i64
-                if offset + 8 > data.len() {
+            ttype::I64 => {
+                let Some(bytes) = be_bytes(data, offset) else {
                     println!("Not enough data for i64.");
                     break;
-                }
-                let value = i64::from_be_bytes(data[offset..offset+8].try_into().unwrap());
+                };
+                let value = i64::from_be_bytes(bytes);
                 offset += 8;
-                println!("i64 = {}", value);
+                println!("i64 = {}{}", value, opts.i64_suffix(field_id, value));
             }
-            0x0B => { // string
-                if offset + 4 > data.len() {
+            ttype::STRING => {
+                let Some(bytes) = be_bytes(data, offset) else {
                     println!("Not enough data for string length.");
                     break;
-                }
-                let len = u32::from_be_bytes(data[offset..offset+4].try_into().unwrap()) as usize;
+                };
+                let len = u32::from_be_bytes(bytes) as usize;
                 offset += 4;
 
                 if offset + len > data.len() {
@@ -205,11 +971,11 @@ fn parse_thrift_binary(data: &[u8]) {
                     break;
                 }
 
-                let s = String::from_utf8_lossy(&data[offset..offset+len]);
+                let s = display_string(&data[offset..offset+len], opts.max_string_size);
                 offset += len;
                 println!("string = \"{}\"", s);
             }
-            0x02 => { // bool
+            ttype::BOOL => {
                 if offset + 1 > data.len() {
                     println!("Not enough data for bool.");
                     break;
@@ -218,23 +984,54 @@ fn parse_thrift_binary(data: &[u8]) {
                 offset += 1;
                 println!("bool = {}", value);
             }
-            0x01 => { // double
-                if offset + 8 > data.len() {
+            ttype::DOUBLE => {
+                let Some(bytes) = be_bytes(data, offset) else {
                     println!("Not enough data for double.");
                     break;
-                }
-                let value = f64::from_be_bytes(data[offset..offset+8].try_into().unwrap());
+                };
+                let value = f64::from_be_bytes(bytes);
                 offset += 8;
                 println!("double = {}", value);
             }
-            0x0C => {
+            ttype::BYTE => {
+                if offset + 1 > data.len() {
+                    println!("Not enough data for byte.");
+                    break;
+                }
+                let value = data[offset] as i8;
+                offset += 1;
+                println!("byte = {}", value);
+            }
+            ttype::I16 => {
+                let Some(bytes) = be_bytes(data, offset) else {
+                    println!("Not enough data for i16.");
+                    break;
+                };
+                let value = i16::from_be_bytes(bytes);
+                offset += 2;
+                println!("i16 = {}", value);
+            }
+            ttype::I32 => {
+                let Some(bytes) = be_bytes(data, offset) else {
+                    println!("Not enough data for i32.");
+                    break;
+                };
+                let value = i32::from_be_bytes(bytes);
+                offset += 4;
+                println!("i32 = {}", value);
+            }
+            ttype::STRUCT => {
                 println!("Start of struct:");
-                offset = parse_struct(data, offset);
+                offset = parse_struct(data, offset, opts, 1);
             }        
-            0x0F => {
-                println!("Field Type 0x0F: Struct handling not implemented.");
-                offset += 6;
-            }
+            ttype::MAP => match parse_map(data, offset, field_id, opts) {
+                Some(next) => offset = next,
+                None => break,
+            },
+            ttype::SET | ttype::LIST => match parse_elements(data, offset, field_id, field_type, opts) {
+                Some(next) => offset = next,
+                None => break,
+            },
             _ => {
                 println!("Unknown or unhandled type: 0x{:02X}", field_type);
                 break;
@@ -244,103 +1041,293 @@ fn parse_thrift_binary(data: &[u8]) {
     println!("--- End Fields ---\n");
 }
 
-fn dump_bytes(data: &[u8]) {
-    for (i, byte) in data.iter().enumerate() {
-        print!("{:02X} ", byte);
-        if (i + 1) % 16 == 0 {
-            println!();
+// 从 offset 处读取 N 个字节，越界时返回 None 而不是 panic
+fn be_bytes<const N: usize>(data: &[u8], offset: usize) -> Option<[u8; N]> {
+    data.get(offset..offset.checked_add(N)?)?.try_into().ok()
+}
+
+// 与 hexdump -C 相同的布局：左侧为 offset，中间每行 16 字节（8 字节一组），右侧为 ASCII
+// 超过 max_dump 字节时只打印前 max_dump 字节，max_dump 为 0 时不截断
+fn dump_bytes(data: &[u8], max_dump: usize) {
+    let shown = if max_dump == 0 { data.len() } else { data.len().min(max_dump) };
+    for (line, chunk) in data[..shown].chunks(16).enumerate() {
+        let mut hex = String::new();
+        for (i, byte) in chunk.iter().enumerate() {
+            if i == 8 {
+                hex.push(' ');
+            }
+            hex += &format!("{:02X} ", byte);
         }
+        let ascii: String = chunk
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect();
+        println!("{:08X}  {:<49} |{}|", line * 16, hex, ascii);
     }
-    if data.len() % 16 != 0 {
-        println!();
+    if shown < data.len() {
+        println!("... ({} more bytes)", data.len() - shown);
     }
 }
 
-fn parse_struct(data: &[u8], mut offset: usize) -> usize {
+// 读取 key 类型、value 类型与 i32 个数后逐个解码键值对，键值可以是结构体或容器
+// 声明的个数超出剩余数据时打印已解出的部分并返回 None，调用方停止解析当前结构体
+fn parse_map(data: &[u8], mut offset: usize, field_id: u16, opts: &PrintOptions) -> Option<usize> {
+    let Some([key_type, value_type, count @ ..]) = be_bytes::<6>(data, offset) else {
+        println!("field {} (map): Not enough data for map header.", field_id);
+        return None;
+    };
+    let count = i32::from_be_bytes(count);
+    offset += 6;
+
+    let options = opts.decode_options();
+    let mut entries = Vec::new();
+    let mut error = None;
+    for _ in 0..count.max(0) {
+        let entry = decode::decode_value_with(data, offset, key_type, &options).and_then(
+            |(k, next)| {
+                decode::decode_value_with(data, next, value_type, &options)
+                    .map(|(v, next)| (k, v, next))
+            },
+        );
+        match entry {
+            Ok((k, v, next)) => {
+                entries.push(format!("{} => {}", k, v));
+                offset = next;
+            }
+            Err(e) => {
+                error = Some(e);
+                break;
+            }
+        }
+    }
+
+    let body = if entries.is_empty() {
+        "{}".to_string()
+    } else {
+        format!("{{ {} }}", entries.join(", "))
+    };
+    println!(
+        "field {} (map<{},{}>): {}",
+        field_id,
+        decode::type_name(key_type),
+        decode::type_name(value_type),
+        body
+    );
+    match error {
+        None => Some(offset),
+        Some(e) => {
+            println!(
+                "Warning: map declares {} entries but only {} could be decoded ({}); stopping.",
+                count,
+                entries.len(),
+                e
+            );
+            None
+        }
+    }
+}
+
+// list 与 set 的编码相同：读取元素类型与 i32 个数后逐个解码元素，container 为 ttype::LIST 或 ttype::SET
+// 声明的个数超出剩余数据时打印已解出的部分并返回 None，调用方停止解析当前结构体
+fn parse_elements(
+    data: &[u8],
+    mut offset: usize,
+    field_id: u16,
+    container: u8,
+    opts: &PrintOptions,
+) -> Option<usize> {
+    let kind = decode::type_name(container);
+    let Some([elem_type, count @ ..]) = be_bytes::<5>(data, offset) else {
+        println!("field {} ({}): Not enough data for {} header.", field_id, kind, kind);
+        return None;
+    };
+    let count = i32::from_be_bytes(count);
+    offset += 5;
+
+    let options = opts.decode_options();
+    let mut elems = Vec::new();
+    let mut error = None;
+    for _ in 0..count.max(0) {
+        match decode::decode_value_with(data, offset, elem_type, &options) {
+            Ok((v, next)) => {
+                elems.push(v.to_string());
+                offset = next;
+            }
+            Err(e) => {
+                error = Some(e);
+                break;
+            }
+        }
+    }
+
+    let body = match container {
+        ttype::SET if elems.is_empty() => "{}".to_string(),
+        ttype::SET => format!("{{ {} }}", elems.join(", ")),
+        _ => format!("[{}]", elems.join(", ")),
+    };
+    println!(
+        "field {} ({}<{}>): {}",
+        field_id,
+        kind,
+        decode::type_name(elem_type),
+        body
+    );
+    match error {
+        None => Some(offset),
+        Some(e) => {
+            println!(
+                "Warning: {} declares {} elements but only {} could be decoded ({}); stopping.",
+                kind,
+                count,
+                elems.len(),
+                e
+            );
+            None
+        }
+    }
+}
+
+// 超过 --max-string-size 时只转换前缀，不为整段字段分配内存
+fn display_string(bytes: &[u8], max_string_size: Option<usize>) -> String {
+    match max_string_size {
+        Some(max) if bytes.len() > max => format!(
+            "{}... ({} bytes, truncated)",
+            String::from_utf8_lossy(&bytes[..max]),
+            bytes.len()
+        ),
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+// 同一结构体内字段 id 重复通常意味着编码器有 bug 或数据损坏，只告警不中断解码
+fn warn_duplicate_field(seen: &mut HashMap<u16, usize>, field_id: u16, offset: usize) {
+    match seen.get(&field_id) {
+        Some(first) => println!(
+            "Warning: duplicate field id {} at offset {} (first seen at offset {}).",
+            field_id, offset, first
+        ),
+        None => {
+            seen.insert(field_id, offset);
+        }
+    }
+}
+
+// 字段所在行以其起始 offset 开头（相对于剥离传输层后打印的报文），便于对照十六进制输出
+fn print_field_offset(offset: usize) {
+    print!("@0x{:02X} ", offset);
+}
+
+fn parse_struct(data: &[u8], mut offset: usize, opts: &PrintOptions, depth: usize) -> usize {
+    if depth > decode::MAX_DEPTH {
+        println!("struct nesting too deep, aborting");
+        return offset;
+    }
+    let mut seen = HashMap::new();
     loop {
         if offset + 1 > data.len() {
             break;
         }
+        let field_start = offset;
         let field_type = data[offset];
         offset += 1;
 
-        if field_type == 0x00 {
+        if field_type == ttype::STOP {
             println!("End of struct (STOP).");
             break;
         }
 
 
 
-        if offset + 2 > data.len() {
+        let Some(bytes) = be_bytes(data, offset) else {
             println!("Unexpected end of data while reading field ID.");
             break;
-        }
-
-        let field_id = u16::from_be_bytes(data[offset..offset+2].try_into().unwrap());
+        };
+        let field_id = u16::from_be_bytes(bytes);
         offset += 2;
+        warn_duplicate_field(&mut seen, field_id, field_start);
+        print_field_offset(field_start);
 
         match field_type {
-            0x0A => {
-                let val = i64::from_be_bytes(data[offset..offset+8].try_into().unwrap());
+            ttype::I64 => {
+                let Some(bytes) = be_bytes(data, offset) else {
+                    println!("Not enough data for i64.");
+                    break;
+                };
+                let val = i64::from_be_bytes(bytes);
                 offset += 8;
-                println!("field {} (i64): {}", field_id, val);
+                println!("field {} (i64): {}{}", field_id, val, opts.i64_suffix(field_id, val));
             }
-            0x0B => {
-                let len = u32::from_be_bytes(data[offset..offset+4].try_into().unwrap()) as usize;
-                offset += 4;
-                let s = String::from_utf8_lossy(&data[offset..offset+len]);
-                offset += len;
-                println!("field {} (string): {}", field_id, s);
+            ttype::BOOL => {
+                if offset + 1 > data.len() {
+                    println!("Not enough data for bool.");
+                    break;
+                }
+                let val = data[offset] != 0;
+                offset += 1;
+                println!("field {} (bool): {}", field_id, val);
             }
-            0x0D => { // list
-                let elem_type = data[offset]; // 获取元素类型
+            ttype::BYTE => {
+                if offset + 1 > data.len() {
+                    println!("Not enough data for byte.");
+                    break;
+                }
+                let val = data[offset] as i8;
+                offset += 1;
+                println!("field {} (byte): {}", field_id, val);
+            }
+            ttype::I16 => {
+                let Some(bytes) = be_bytes(data, offset) else {
+                    println!("Not enough data for i16.");
+                    break;
+                };
+                let val = i16::from_be_bytes(bytes);
                 offset += 2;
-                let list_len = u32::from_be_bytes(data[offset..offset+4].try_into().unwrap()) as usize;
+                println!("field {} (i16): {}", field_id, val);
+            }
+            ttype::I32 => {
+                let Some(bytes) = be_bytes(data, offset) else {
+                    println!("Not enough data for i32.");
+                    break;
+                };
+                let val = i32::from_be_bytes(bytes);
                 offset += 4;
-            
-                println!("field {} (list):", field_id);
-            
-                for i in 0..list_len {
-                    if offset + 4 > data.len() {
-                        println!("Not enough data to read element length for index {}", i);
-                        break;
-                    }
-                    
-                    let len = u32::from_be_bytes(data[offset..offset+4].try_into().unwrap()) as usize;
-                    offset += 4;
-            
-                    if offset + len > data.len() {
-                        println!("Not enough data to read element data for index {}", i);
-                        break;
-                    }
-            
-                    // 解析列表元素类型
-                    match elem_type {
-                        0x0A => { // 假设是 string 类型
-                            let s = String::from_utf8_lossy(&data[offset..offset+len]);
-                            offset += len;
-                            println!("  [{}] string: {}", i, s);
-                        }
-                        0x0B => { // 假设是 i64 类型
-                            if offset + 8 > data.len() {
-                                println!("Not enough data to read i64 for index {}", i);
-                                break;
-                            }
-                            let val = i64::from_be_bytes(data[offset..offset+8].try_into().unwrap());
-                            offset += 8;
-                            println!("  [{}] i64: {}", i, val);
-                        }
-                        _ => {
-                            println!("  [{}] Unknown element type: 0x{:02X}", i, elem_type);
-                            break;
-                        }
-                    }
-                }
+                println!("field {} (i32): {}", field_id, val);
+            }
+            ttype::DOUBLE => {
+                let Some(bytes) = be_bytes(data, offset) else {
+                    println!("Not enough data for double.");
+                    break;
+                };
+                let val = f64::from_be_bytes(bytes);
+                offset += 8;
+                println!("field {} (double): {}", field_id, val);
+            }
+            ttype::STRING => {
+                let Some(bytes) = be_bytes(data, offset) else {
+                    println!("Not enough data for string length.");
+                    break;
+                };
+                let len = u32::from_be_bytes(bytes) as usize;
+                offset += 4;
+                let Some(bytes) = data.get(offset..).and_then(|rest| rest.get(..len)) else {
+                    println!("String truncated.");
+                    break;
+                };
+                let s = display_string(bytes, opts.max_string_size);
+                offset += len;
+                println!("field {} (string): {}", field_id, s);
             }
-            
-            0x0C => {
+            ttype::MAP => match parse_map(data, offset, field_id, opts) {
+                Some(next) => offset = next,
+                None => break,
+            },
+            ttype::SET | ttype::LIST => match parse_elements(data, offset, field_id, field_type, opts) {
+                Some(next) => offset = next,
+                None => break,
+            },
+            ttype::STRUCT => {
                 println!("field {} Start of struct:", field_id);
-                offset = parse_struct(data, offset);
+                offset = parse_struct(data, offset, opts, depth + 1);
             }
             _ => {
                 println!("Unknown field type: 0x{:02X}", field_type);
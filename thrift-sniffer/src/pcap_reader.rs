@@ -0,0 +1,74 @@
+use anyhow::{bail, Context, Result};
+use pcap::{Capture, Linktype};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+// 从 pcap 读出的一帧
+pub struct Frame<'a> {
+    pub data: &'a [u8],
+    // 抓包长度小于线上原始长度，说明被 snaplen 截断
+    pub truncated: bool,
+}
+
+// 逐帧读取 pcap 文件，回调参数为完整的以太网帧
+pub fn for_each_frame(path: &Path, mut f: impl FnMut(Frame)) -> Result<()> {
+    let mut cap = Capture::from_file(path)
+        .with_context(|| format!("Failed to open pcap file {}", path.display()))?;
+    if cap.get_datalink() != Linktype::ETHERNET {
+        bail!(
+            "Unsupported link type {:?} in {}",
+            cap.get_datalink(),
+            path.display()
+        );
+    }
+
+    let snaplen = read_snaplen(path);
+    if let Some(snaplen) = snaplen {
+        eprintln!("Reading {} (snaplen {})", path.display(), snaplen);
+    }
+
+    let mut total = 0;
+    let mut truncated = 0;
+    loop {
+        match cap.next_packet() {
+            Ok(packet) => {
+                total += 1;
+                let frame = Frame {
+                    data: packet.data,
+                    truncated: packet.header.caplen < packet.header.len,
+                };
+                if frame.truncated {
+                    truncated += 1;
+                }
+                f(frame);
+            }
+            Err(pcap::Error::NoMorePackets) => break,
+            Err(e) => return Err(e).context("Failed to read packet"),
+        }
+    }
+
+    if truncated > 0 {
+        eprintln!(
+            "Warning: {} of {} packets in {} were truncated by snaplen {}; decoded output for them may be corrupt.",
+            truncated,
+            total,
+            path.display(),
+            snaplen.map_or_else(|| "?".to_string(), |s| s.to_string())
+        );
+    }
+    Ok(())
+}
+
+// 读取 pcap 全局头里的 snaplen；pcapng 等其他格式返回 None
+fn read_snaplen(path: &Path) -> Option<u32> {
+    let mut header = [0u8; 24];
+    File::open(path).ok()?.read_exact(&mut header).ok()?;
+
+    let snaplen: [u8; 4] = header[16..20].try_into().ok()?;
+    match &header[0..4] {
+        [0xd4, 0xc3, 0xb2, 0xa1] | [0x4d, 0x3c, 0xb2, 0xa1] => Some(u32::from_le_bytes(snaplen)),
+        [0xa1, 0xb2, 0xc3, 0xd4] | [0xa1, 0xb2, 0x3c, 0x4d] => Some(u32::from_be_bytes(snaplen)),
+        _ => None,
+    }
+}
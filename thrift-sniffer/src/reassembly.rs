@@ -0,0 +1,164 @@
+use crate::decode::{self, DecodeError};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+// 4 字节前缀不超过该值才视为 framed 帧长，否则按 unframed 数据处理
+pub const MAX_FRAME_SIZE: usize = 16 << 20;
+// 每个方向最多缓存的乱序分段数，超过后放弃等待缺口，从最早的乱序分段继续
+pub const MAX_OUT_OF_ORDER: usize = 64;
+
+// 重组过程中交给调用方的结果
+#[derive(Debug, PartialEq, Eq)]
+pub enum Reassembled<'a> {
+    // 一条完整消息：framed 与 THeader 含 4 字节长度前缀，unframed Binary 为消息本身；
+    // 既不像帧长也不是 Binary 版本字节的数据（如 unframed Compact、从连接中途开始抓包）原样交出
+    Message(&'a [u8]),
+    // 缺失的分段一直没有补上，跳过 missing 字节，缺口之前未凑成消息的 dropped 字节被丢弃
+    Gap { missing: u64, dropped: usize },
+}
+
+// 单个方向的重组状态
+struct Stream {
+    // 下一个期望的 TCP 序号及其在该方向字节流中的位置
+    // 乱序分段按位置而不是序号排列，序号回绕后先后关系不变
+    next_seq: u32,
+    next_pos: u64,
+    // 已按序拼接、尚未凑成完整消息的字节
+    buf: Vec<u8>,
+    out_of_order: BTreeMap<u64, Vec<u8>>,
+    // 最近一次有新数据并入 buf 的时间；重传与乱序分段不刷新，卡在缺口上的流照常过期回收
+    last_progress: Instant,
+}
+
+// 按 TCP 序号把分段拼成连续字节流，再按 framed 长度前缀（THeader 同样以此开头）切出完整消息
+// 以 key（通常为单向的 Flow）区分两个方向；空闲超过 idle_timeout 的流被回收
+pub struct StreamReassembler<K> {
+    streams: HashMap<K, Stream>,
+    idle_timeout: Duration,
+    last_eviction: Instant,
+}
+
+impl<K: Eq + Hash> StreamReassembler<K> {
+    pub fn new(idle_timeout: Duration) -> Self {
+        Self {
+            streams: HashMap::new(),
+            idle_timeout,
+            last_eviction: Instant::now(),
+        }
+    }
+
+    // 加入一个分段，每凑出一条完整消息或跳过一个缺口就调用一次 on_event
+    pub fn push(
+        &mut self,
+        key: K,
+        seq: u32,
+        bytes: &[u8],
+        mut on_event: impl FnMut(Reassembled<'_>),
+    ) {
+        let now = Instant::now();
+        self.evict_idle(now);
+        if bytes.is_empty() {
+            return;
+        }
+
+        let stream = self.streams.entry(key).or_insert_with(|| Stream {
+            next_seq: seq,
+            next_pos: 0,
+            buf: Vec::new(),
+            out_of_order: BTreeMap::new(),
+            last_progress: now,
+        });
+        if let Some(gap) = stream.accept(seq, bytes, now) {
+            on_event(gap);
+        }
+        while let Some(len) = stream.next_message() {
+            on_event(Reassembled::Message(&stream.buf[..len]));
+            stream.buf.drain(..len);
+        }
+    }
+
+    // 连接关闭（FIN/RST）后丢弃该方向的状态
+    pub fn close(&mut self, key: &K) {
+        self.streams.remove(key);
+    }
+
+    fn evict_idle(&mut self, now: Instant) {
+        if now.duration_since(self.last_eviction) < self.idle_timeout / 2 {
+            return;
+        }
+        self.last_eviction = now;
+        let idle_timeout = self.idle_timeout;
+        self.streams
+            .retain(|_, stream| now.duration_since(stream.last_progress) < idle_timeout);
+    }
+}
+
+impl Stream {
+    fn accept(&mut self, seq: u32, bytes: &[u8], now: Instant) -> Option<Reassembled<'static>> {
+        // 按有符号差值换算位置，序号回绕时同样成立
+        let pos = self.next_pos as i64 + i64::from(seq.wrapping_sub(self.next_seq) as i32);
+        if pos > self.next_pos as i64 {
+            self.out_of_order.insert(pos as u64, bytes.to_vec());
+            if self.out_of_order.len() <= MAX_OUT_OF_ORDER {
+                return None;
+            }
+            // 缺口处的分段多半已经丢失，缓存的半条消息不可能再凑齐
+            let first = *self.out_of_order.keys().next().unwrap();
+            let gap = Reassembled::Gap {
+                missing: first - self.next_pos,
+                dropped: self.buf.len(),
+            };
+            self.buf.clear();
+            self.next_seq = self.next_seq.wrapping_add((first - self.next_pos) as u32);
+            self.next_pos = first;
+            self.drain_out_of_order(now);
+            return Some(gap);
+        }
+        // 重传或与已收数据重叠，只取新的部分
+        let overlap = (self.next_pos as i64 - pos) as usize;
+        if overlap < bytes.len() {
+            self.append(&bytes[overlap..], now);
+        }
+        self.drain_out_of_order(now);
+        None
+    }
+
+    fn drain_out_of_order(&mut self, now: Instant) {
+        while let Some(entry) = self.out_of_order.first_entry() {
+            if *entry.key() > self.next_pos {
+                break;
+            }
+            let overlap = (self.next_pos - *entry.key()) as usize;
+            let bytes = entry.remove();
+            if overlap < bytes.len() {
+                self.append(&bytes[overlap..], now);
+            }
+        }
+    }
+
+    fn append(&mut self, bytes: &[u8], now: Instant) {
+        self.buf.extend_from_slice(bytes);
+        self.next_seq = self.next_seq.wrapping_add(bytes.len() as u32);
+        self.next_pos += bytes.len() as u64;
+        self.last_progress = now;
+    }
+
+    // buf 开头一条完整消息的长度，数据不足时返回 None
+    fn next_message(&self) -> Option<usize> {
+        let prefix: [u8; 4] = self.buf.get(..4)?.try_into().ok()?;
+        let frame_len = u32::from_be_bytes(prefix) as usize + 4;
+        if frame_len <= MAX_FRAME_SIZE + 4 {
+            return (self.buf.len() >= frame_len).then_some(frame_len);
+        }
+        if !self.buf.starts_with(&[0x80, 0x01]) {
+            return Some(self.buf.len());
+        }
+        // unframed Binary：没有长度前缀，只能按消息结构走一遍确定边界
+        match decode::message_len(&self.buf) {
+            Ok(len) => Some(len),
+            Err(DecodeError::UnexpectedEof { .. }) if self.buf.len() <= MAX_FRAME_SIZE => None,
+            Err(_) => Some(self.buf.len()),
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::flow::Flow;
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thrift_sniffer::decode::Message;
+
+// 何时把环形缓冲区写出到文件
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Trigger {
+    /// 看到 Exception 消息时
+    Exception,
+    /// 收到 Ctrl-C 退出时
+    Signal,
+}
+
+struct Entry {
+    timestamp: SystemTime,
+    flow: Flow,
+    msg: Message,
+}
+
+// 在内存中保留最近 capacity 条消息，触发时连同之后的 after 条一起追加写入 dump 文件
+// 用于捕捉偶发故障发生前的上下文，而不必记录全部流量
+pub struct Ring {
+    capacity: usize,
+    after: usize,
+    trigger: Trigger,
+    path: PathBuf,
+    entries: VecDeque<Entry>,
+    // 正在写出触发后的消息：剩余条数与打开的文件
+    pending: Option<(usize, File)>,
+}
+
+impl Ring {
+    pub fn new(capacity: usize, after: usize, trigger: Trigger, path: PathBuf) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            after,
+            trigger,
+            path,
+            entries: VecDeque::new(),
+            pending: None,
+        }
+    }
+
+    pub fn record(&mut self, flow: &Flow, msg: &Message) -> Result<()> {
+        let entry = Entry {
+            timestamp: SystemTime::now(),
+            flow: *flow,
+            msg: msg.clone(),
+        };
+
+        if let Some((remaining, file)) = &mut self.pending {
+            write_entry(file, &entry)?;
+            *remaining -= 1;
+            if *remaining == 0 {
+                self.pending = None;
+            }
+            return Ok(());
+        }
+
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+
+        if self.trigger == Trigger::Exception && msg.message_type == 0x03 {
+            let file = self.dump(&format!("exception in {}", msg.name))?;
+            if self.after > 0 {
+                self.pending = Some((self.after, file));
+            }
+        }
+        Ok(())
+    }
+
+    // 退出时调用：signal 触发模式下写出当前缓冲区
+    pub fn finish(&mut self) -> Result<()> {
+        if self.trigger == Trigger::Signal {
+            self.dump("signal")?;
+        }
+        Ok(())
+    }
+
+    fn dump(&mut self, reason: &str) -> Result<File> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open ring dump {}", self.path.display()))?;
+        writeln!(
+            file,
+            "=== Trigger: {} ({} buffered message(s)) ===",
+            reason,
+            self.entries.len()
+        )?;
+        for entry in self.entries.drain(..) {
+            write_entry(&mut file, &entry)?;
+        }
+        eprintln!("Ring buffer dumped to {} ({})", self.path.display(), reason);
+        Ok(file)
+    }
+}
+
+fn write_entry(file: &mut File, entry: &Entry) -> Result<()> {
+    let since_epoch = entry
+        .timestamp
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    writeln!(
+        file,
+        "{}.{:03} {} {}",
+        since_epoch.as_secs(),
+        since_epoch.subsec_millis(),
+        entry.flow,
+        entry.msg
+    )?;
+    Ok(())
+}
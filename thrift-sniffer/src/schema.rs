@@ -0,0 +1,133 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Write};
+use thrift_sniffer::decode::{self, Message, ThriftValue};
+
+#[derive(Debug, Default)]
+struct Field {
+    // 出现过该字段的结构体实例数
+    seen: usize,
+    types: BTreeSet<String>,
+}
+
+#[derive(Debug, Default)]
+struct Shape {
+    // 观察到的结构体实例数
+    observed: usize,
+    fields: BTreeMap<i16, Field>,
+}
+
+// 根据抓到的报文推断 .thrift 结构体定义
+// 类型取自线上的类型码；某字段在所有实例中都出现则视为 required，否则 optional
+#[derive(Debug, Default)]
+pub struct SchemaInference {
+    shapes: BTreeMap<String, Shape>,
+}
+
+impl SchemaInference {
+    pub fn observe(&mut self, msg: &Message) {
+        let kind = if msg.is_call() { "Args" } else { "Result" };
+        let name = format!("{}{}", upper_camel(msg.method()), kind);
+        self.observe_struct(&name, &msg.body);
+    }
+
+    pub fn print(&self, out: &mut dyn Write) -> io::Result<()> {
+        for (name, shape) in &self.shapes {
+            writeln!(out, "// inferred from {} message(s)", shape.observed)?;
+            writeln!(out, "struct {} {{", name)?;
+            for (id, field) in &shape.fields {
+                let required = if field.seen == shape.observed {
+                    "required"
+                } else {
+                    "optional"
+                };
+                let mut types = field.types.iter();
+                let ty = types.next().map(String::as_str).unwrap_or("binary");
+                let others: Vec<_> = types.map(String::as_str).collect();
+                if others.is_empty() {
+                    writeln!(out, "    {}: {} {} field_{},", id, required, ty, id)?;
+                } else {
+                    writeln!(
+                        out,
+                        "    {}: {} {} field_{}, // also seen as {}",
+                        id,
+                        required,
+                        ty,
+                        id,
+                        others.join(", ")
+                    )?;
+                }
+            }
+            writeln!(out, "}}\n")?;
+        }
+        Ok(())
+    }
+
+    fn observe_struct(&mut self, name: &str, fields: &[(i16, ThriftValue)]) {
+        let typed: Vec<(i16, String)> = fields
+            .iter()
+            .map(|(id, v)| (*id, self.type_of(&format!("{}F{}", name, id), v)))
+            .collect();
+
+        let shape = self.shapes.entry(name.to_string()).or_default();
+        shape.observed += 1;
+        for (id, ty) in typed {
+            let field = shape.fields.entry(id).or_default();
+            field.seen += 1;
+            field.types.insert(ty);
+        }
+    }
+
+    // 嵌套结构体以 `父结构体名 + F + 字段 id` 命名
+    fn type_of(&mut self, name: &str, value: &ThriftValue) -> String {
+        match value {
+            ThriftValue::Bool(_) => "bool".to_string(),
+            ThriftValue::Byte(_) => "byte".to_string(),
+            ThriftValue::Double(_) => "double".to_string(),
+            ThriftValue::I16(_) => "i16".to_string(),
+            ThriftValue::I32(_) => "i32".to_string(),
+            ThriftValue::I64(_) => "i64".to_string(),
+            ThriftValue::String(_) | ThriftValue::TruncatedString(..) => "string".to_string(),
+            ThriftValue::Struct(fields) => {
+                self.observe_struct(name, fields);
+                name.to_string()
+            }
+            ThriftValue::List(elem_type, elems) => {
+                format!("list<{}>", self.elem_type(name, *elem_type, elems.iter()))
+            }
+            ThriftValue::Set(elem_type, elems) => {
+                format!("set<{}>", self.elem_type(name, *elem_type, elems.iter()))
+            }
+            ThriftValue::Map(key_type, value_type, entries) => {
+                let k = self.elem_type(&format!("{}Key", name), *key_type, entries.iter().map(|(k, _)| k));
+                let v = self.elem_type(&format!("{}Value", name), *value_type, entries.iter().map(|(_, v)| v));
+                format!("map<{},{}>", k, v)
+            }
+        }
+    }
+
+    fn elem_type<'a>(
+        &mut self,
+        name: &str,
+        ttype: u8,
+        elems: impl Iterator<Item = &'a ThriftValue>,
+    ) -> String {
+        let mut ty = None;
+        for elem in elems {
+            ty = Some(self.type_of(name, elem));
+        }
+        ty.unwrap_or_else(|| decode::type_name(ttype).to_string())
+    }
+}
+
+// get_item -> GetItem；已是驼峰的名字保持不变
+pub(crate) fn upper_camel(name: &str) -> String {
+    name.split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
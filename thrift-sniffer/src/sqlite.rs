@@ -0,0 +1,100 @@
+use crate::flow::Flow;
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use thrift_sniffer::decode::{self, Message, ThriftValue};
+
+// 攒够一批或超过间隔后在一个事务里写入
+const BATCH_SIZE: usize = 256;
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+struct Row {
+    timestamp: i64,
+    flow: String,
+    service: Option<String>,
+    method: String,
+    message_type: &'static str,
+    seq_id: i32,
+    fields: String,
+}
+
+// 将解码后的消息写入 SQLite，便于用 SQL 查询抓到的流量
+pub struct SqliteSink {
+    conn: Connection,
+    pending: Vec<Row>,
+    last_flush: Instant,
+}
+
+impl SqliteSink {
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open SQLite database {}", path.display()))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS messages (
+                id INTEGER PRIMARY KEY,
+                timestamp INTEGER NOT NULL,
+                flow TEXT NOT NULL,
+                service TEXT,
+                method TEXT NOT NULL,
+                message_type TEXT NOT NULL,
+                seq_id INTEGER NOT NULL,
+                fields TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS messages_method ON messages (method);
+            CREATE INDEX IF NOT EXISTS messages_timestamp ON messages (timestamp);",
+        )?;
+        Ok(Self {
+            conn,
+            pending: Vec::new(),
+            last_flush: Instant::now(),
+        })
+    }
+
+    pub fn insert(&mut self, flow: &Flow, msg: &Message) -> Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as i64;
+        self.pending.push(Row {
+            timestamp,
+            flow: flow.to_string(),
+            service: msg.service().map(str::to_string),
+            method: msg.method().to_string(),
+            message_type: decode::message_type_name(msg.message_type),
+            seq_id: msg.seq_id,
+            fields: ThriftValue::Struct(msg.body.clone()).to_json().to_string(),
+        });
+        if self.pending.len() >= BATCH_SIZE || self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.last_flush = Instant::now();
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO messages (timestamp, flow, service, method, message_type, seq_id, fields)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for row in self.pending.drain(..) {
+                stmt.execute(params![
+                    row.timestamp,
+                    row.flow,
+                    row.service,
+                    row.method,
+                    row.message_type,
+                    row.seq_id,
+                    row.fields
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+// 单条消息的字节构成
+#[derive(Debug, Clone, Copy)]
+pub struct SizeBreakdown {
+    // 整帧长度，framed 时含 4 字节长度前缀
+    pub frame: usize,
+    // THeader 部分（含长度前缀）
+    pub theader: usize,
+    // Thrift 消息本身
+    pub payload: usize,
+}
+
+impl SizeBreakdown {
+    pub fn overhead_percent(&self) -> f64 {
+        if self.frame == 0 {
+            return 0.0;
+        }
+        self.theader as f64 * 100.0 / self.frame as f64
+    }
+}
+
+#[derive(Debug, Default)]
+struct MethodTotals {
+    count: usize,
+    frame: usize,
+    theader: usize,
+    payload: usize,
+}
+
+// 按方法聚合协议开销
+#[derive(Debug, Default)]
+pub struct OverheadStats {
+    by_method: BTreeMap<String, MethodTotals>,
+}
+
+impl OverheadStats {
+    pub fn record(&mut self, method: &str, size: SizeBreakdown) {
+        let totals = self.by_method.entry(method.to_string()).or_default();
+        totals.count += 1;
+        totals.frame += size.frame;
+        totals.theader += size.theader;
+        totals.payload += size.payload;
+    }
+
+    pub fn print(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(
+            out,
+            "{:<24} {:>8} {:>12} {:>12} {:>12} {:>10}",
+            "method", "count", "avg frame", "avg theader", "avg payload", "overhead"
+        )?;
+        for (method, t) in &self.by_method {
+            let avg = |total: usize| total as f64 / t.count as f64;
+            let overhead = if t.frame == 0 {
+                0.0
+            } else {
+                t.theader as f64 * 100.0 / t.frame as f64
+            };
+            writeln!(
+                out,
+                "{:<24} {:>8} {:>12.1} {:>12.1} {:>12.1} {:>9.1}%",
+                method,
+                t.count,
+                avg(t.frame),
+                avg(t.theader),
+                avg(t.payload),
+                overhead
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct MethodSummary {
+    calls: usize,
+    replies: usize,
+    exceptions: usize,
+    sizes: SizeHistogram,
+}
+
+// --summary：按方法统计 Call/Reply/Exception 条数与消息大小分布
+#[derive(Debug, Default)]
+pub struct MessageSummary {
+    by_method: BTreeMap<String, MethodSummary>,
+}
+
+impl MessageSummary {
+    // message_type 为 None 表示消息无法解码，只计入大小
+    pub fn record(&mut self, method: &str, message_type: Option<u8>, size: usize) {
+        let summary = self.by_method.entry(method.to_string()).or_default();
+        match message_type {
+            // oneway 也算作调用
+            Some(0x01 | 0x04) => summary.calls += 1,
+            Some(0x02) => summary.replies += 1,
+            Some(0x03) => summary.exceptions += 1,
+            _ => {}
+        }
+        summary.sizes.record(size);
+    }
+
+    pub fn print(&self) {
+        println!(
+            "{:<24} {:>8} {:>8} {:>10} {:>10} {:>8} {:>8} {:>8}",
+            "method", "calls", "replies", "exceptions", "avg size", "p50", "p90", "p99"
+        );
+        for (method, s) in &self.by_method {
+            println!(
+                "{:<24} {:>8} {:>8} {:>10} {:>10.1} {:>8} {:>8} {:>8}",
+                method,
+                s.calls,
+                s.replies,
+                s.exceptions,
+                s.sizes.mean(),
+                s.sizes.percentile(50),
+                s.sizes.percentile(90),
+                s.sizes.percentile(99)
+            );
+        }
+    }
+}
+
+// 每个 2 的幂区间再等分的桶数
+const SUB_BUCKETS: usize = 8;
+const SUB_BITS: u32 = SUB_BUCKETS.trailing_zeros();
+const BUCKETS: usize = (usize::BITS - SUB_BITS + 1) as usize * SUB_BUCKETS;
+
+// 消息大小的对数分桶直方图，占用内存与消息条数无关
+// 小于 SUB_BUCKETS 的值各占一个桶，之后每个 [2^k, 2^(k+1)) 区间等分为 SUB_BUCKETS 个桶，
+// 分位数取所在桶的上界，最多比真实值大 1/SUB_BUCKETS
+#[derive(Debug)]
+struct SizeHistogram {
+    counts: Vec<u64>,
+    total: u64,
+    sum: u64,
+    max: usize,
+}
+
+impl Default for SizeHistogram {
+    fn default() -> Self {
+        Self {
+            counts: vec![0; BUCKETS],
+            total: 0,
+            sum: 0,
+            max: 0,
+        }
+    }
+}
+
+impl SizeHistogram {
+    fn record(&mut self, size: usize) {
+        self.counts[bucket(size)] += 1;
+        self.total += 1;
+        self.sum = self.sum.saturating_add(size as u64);
+        self.max = self.max.max(size);
+    }
+
+    fn mean(&self) -> f64 {
+        self.sum as f64 / self.total.max(1) as f64
+    }
+
+    // nearest-rank 分位数
+    fn percentile(&self, p: u64) -> usize {
+        if self.total == 0 {
+            return 0;
+        }
+        let rank = (self.total * p).div_ceil(100).max(1);
+        let mut seen = 0;
+        for (i, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return bucket_upper(i).min(self.max);
+            }
+        }
+        self.max
+    }
+}
+
+fn bucket(size: usize) -> usize {
+    if size < SUB_BUCKETS {
+        return size;
+    }
+    let exp = usize::BITS - 1 - size.leading_zeros();
+    let shift = exp - SUB_BITS;
+    let mantissa = (size >> shift) - SUB_BUCKETS;
+    (shift as usize + 1) * SUB_BUCKETS + mantissa
+}
+
+// 桶内的最大值
+fn bucket_upper(index: usize) -> usize {
+    if index < SUB_BUCKETS {
+        return index;
+    }
+    let shift = (index / SUB_BUCKETS - 1) as u32;
+    let lower = (SUB_BUCKETS + index % SUB_BUCKETS) << shift;
+    lower + ((1 << shift) - 1)
+}
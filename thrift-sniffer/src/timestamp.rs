@@ -0,0 +1,67 @@
+use std::collections::HashSet;
+
+// 2000-01-01 至 2100-01-01，超出该范围的 i64 不视为时间戳
+const MIN_SECS: i64 = 946_684_800;
+const MAX_SECS: i64 = 4_102_444_800;
+
+// 把看起来像时间戳的 i64 额外显示为 UTC 时间
+// 未指定字段时按数值范围猜测毫秒或秒，指定后只处理这些字段 id，避免把普通整数误判为时间
+#[derive(Debug, Clone, Default)]
+pub struct Timestamps {
+    fields: Option<HashSet<u16>>,
+}
+
+impl Timestamps {
+    pub fn new(fields: Vec<u16>) -> Self {
+        Self {
+            fields: (!fields.is_empty()).then(|| fields.into_iter().collect()),
+        }
+    }
+
+    pub fn annotate(&self, field_id: u16, value: i64) -> Option<String> {
+        if self.fields.as_ref().is_some_and(|fields| !fields.contains(&field_id)) {
+            return None;
+        }
+        if (MIN_SECS * 1000..MAX_SECS * 1000).contains(&value) {
+            Some(format_utc(value.div_euclid(1000), value.rem_euclid(1000)))
+        } else if (MIN_SECS..MAX_SECS).contains(&value) {
+            Some(format_utc(value, 0))
+        } else {
+            None
+        }
+    }
+}
+
+// RFC 3339 格式，毫秒为 0 时省略小数部分
+fn format_utc(secs: i64, millis: i64) -> String {
+    let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+    let rem = secs.rem_euclid(86_400);
+    let time = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    );
+    if millis == 0 {
+        format!("{}Z", time)
+    } else {
+        format!("{}.{:03}Z", time, millis)
+    }
+}
+
+// 1970-01-01 起的天数转换为公历年月日
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
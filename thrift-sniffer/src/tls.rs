@@ -0,0 +1,161 @@
+use crate::flow::Flow;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+// TLS 记录头：content type(1) + version(2) + length(2)
+// content type 为 0x14~0x17，version 主版本恒为 0x03
+pub fn is_tls_record(payload: &[u8]) -> bool {
+    payload.len() >= 5
+        && (0x14..=0x17).contains(&payload[0])
+        && payload[1] == 0x03
+        && payload[2] <= 0x04
+}
+
+// 从 ClientHello 的 server_name 扩展中取出 SNI，不是 ClientHello 或报文不完整时返回 None
+pub fn client_hello_sni(payload: &[u8]) -> Option<String> {
+    let mut r = Cursor { data: payload, offset: 0 };
+    // 记录头，只处理 handshake
+    if r.u8()? != 0x16 {
+        return None;
+    }
+    r.skip(4)?;
+    // handshake 头，只处理 ClientHello
+    if r.u8()? != 0x01 {
+        return None;
+    }
+    r.skip(3)?;
+    // client version + random
+    r.skip(2 + 32)?;
+    let session_id_len = r.u8()? as usize;
+    r.skip(session_id_len)?;
+    let cipher_suites_len = r.u16()? as usize;
+    r.skip(cipher_suites_len)?;
+    let compression_len = r.u8()? as usize;
+    r.skip(compression_len)?;
+
+    let extensions_end = r.u16()? as usize + r.offset;
+    while r.offset + 4 <= extensions_end {
+        let ext_type = r.u16()?;
+        let ext_len = r.u16()? as usize;
+        if ext_type != 0x0000 {
+            r.skip(ext_len)?;
+            continue;
+        }
+        // server_name_list：列表长度(2)，之后每项 name type(1) + 长度(2) + 名字
+        r.skip(2)?;
+        if r.u8()? != 0x00 {
+            return None;
+        }
+        let name_len = r.u16()? as usize;
+        return Some(String::from_utf8_lossy(r.take(name_len)?).into_owned());
+    }
+    None
+}
+
+struct Cursor<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.offset..self.offset.checked_add(n)?)?;
+        self.offset += n;
+        Some(bytes)
+    }
+
+    fn skip(&mut self, n: usize) -> Option<()> {
+        self.take(n).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+    }
+}
+
+// 按连接（两个方向视为同一条）记录是否为 TLS，只根据连接上第一个带数据的分段判定，每条连接只提示一次
+// 之后的报文直接沿用判定结果：TLS 连接中途的分段不从记录边界开始，明文中恰好以 16 03 0x 开头的数据也不会被误判
+// 连接在 FIN/RST 时由 close 移除，空闲超过 idle_timeout 的连接被回收
+pub struct TlsDetector {
+    connections: HashMap<(SocketAddr, SocketAddr), Connection>,
+    idle_timeout: Duration,
+    last_eviction: Instant,
+}
+
+struct Connection {
+    tls: bool,
+    last_seen: Instant,
+}
+
+pub enum Verdict {
+    Plaintext,
+    Encrypted,
+    // 连接上第一次判定为 TLS，由调用方提示，sni 取自 ClientHello
+    NewTls { sni: Option<String> },
+}
+
+fn connection_key(flow: &Flow) -> (SocketAddr, SocketAddr) {
+    if flow.src <= flow.dst {
+        (flow.src, flow.dst)
+    } else {
+        (flow.dst, flow.src)
+    }
+}
+
+impl TlsDetector {
+    pub fn new(idle_timeout: Duration) -> Self {
+        Self {
+            connections: HashMap::new(),
+            idle_timeout,
+            last_eviction: Instant::now(),
+        }
+    }
+
+    // 判定报文是否属于加密连接，非 Plaintext 时调用方应跳过解码
+    pub fn check(&mut self, payload: &[u8], flow: &Flow) -> Verdict {
+        let now = Instant::now();
+        self.evict_idle(now);
+        // SYN、纯 ACK 等不带数据的分段不参与判定
+        if payload.is_empty() {
+            return Verdict::Plaintext;
+        }
+        let key = connection_key(flow);
+        if let Some(connection) = self.connections.get_mut(&key) {
+            connection.last_seen = now;
+            return if connection.tls {
+                Verdict::Encrypted
+            } else {
+                Verdict::Plaintext
+            };
+        }
+        let tls = is_tls_record(payload);
+        self.connections.insert(key, Connection { tls, last_seen: now });
+        if tls {
+            Verdict::NewTls {
+                sni: client_hello_sni(payload),
+            }
+        } else {
+            Verdict::Plaintext
+        }
+    }
+
+    // 连接关闭（FIN/RST）后丢弃判定结果，同一地址上的新连接重新判定
+    pub fn close(&mut self, flow: &Flow) {
+        self.connections.remove(&connection_key(flow));
+    }
+
+    fn evict_idle(&mut self, now: Instant) {
+        if now.duration_since(self.last_eviction) < self.idle_timeout / 2 {
+            return;
+        }
+        self.last_eviction = now;
+        let idle_timeout = self.idle_timeout;
+        self.connections
+            .retain(|_, connection| now.duration_since(connection.last_seen) < idle_timeout);
+    }
+}
@@ -0,0 +1,126 @@
+use anyhow::{bail, Result};
+
+// 单个 WebSocket 消息的最大长度，超出视为不是 WebSocket 流
+const MAX_MESSAGE_LEN: u64 = 64 * 1024 * 1024;
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+
+#[derive(Debug)]
+struct Frame {
+    fin: bool,
+    opcode: u8,
+    payload: Vec<u8>,
+}
+
+// 解析一个 WebSocket 帧，数据不完整时返回 None
+fn parse_frame(data: &[u8]) -> Result<Option<(Frame, usize)>> {
+    if data.len() < 2 {
+        return Ok(None);
+    }
+    if data[0] & 0x70 != 0 {
+        bail!("WebSocket RSV bits set (0x{:02X} at offset 0).", data[0]);
+    }
+    let fin = data[0] & 0x80 != 0;
+    let opcode = data[0] & 0x0F;
+    let masked = data[1] & 0x80 != 0;
+
+    let mut offset = 2;
+    let len = match data[1] & 0x7F {
+        126 => {
+            if data.len() < offset + 2 {
+                return Ok(None);
+            }
+            let len = u16::from_be_bytes([data[2], data[3]]) as u64;
+            offset += 2;
+            len
+        }
+        127 => {
+            if data.len() < offset + 8 {
+                return Ok(None);
+            }
+            let len = u64::from_be_bytes(data[2..10].try_into()?);
+            offset += 8;
+            len
+        }
+        len => len as u64,
+    };
+    if len > MAX_MESSAGE_LEN {
+        bail!("WebSocket frame length {} exceeds limit {}.", len, MAX_MESSAGE_LEN);
+    }
+
+    let mask = if masked {
+        if data.len() < offset + 4 {
+            return Ok(None);
+        }
+        let mask = [data[offset], data[offset + 1], data[offset + 2], data[offset + 3]];
+        offset += 4;
+        Some(mask)
+    } else {
+        None
+    };
+
+    let len = len as usize;
+    if data.len() < offset + len {
+        return Ok(None);
+    }
+    let mut payload = data[offset..offset + len].to_vec();
+    // 客户端发出的帧带掩码，需要逐字节异或还原
+    if let Some(mask) = mask {
+        for (i, b) in payload.iter_mut().enumerate() {
+            *b ^= mask[i % 4];
+        }
+    }
+    Ok(Some((Frame { fin, opcode, payload }, offset + len)))
+}
+
+// 单个方向上的 WebSocket 字节流：处理跨 TCP 段的帧与分片消息
+#[derive(Debug, Default)]
+pub struct WsStream {
+    buf: Vec<u8>,
+    // 尚未收到 FIN 的分片消息
+    partial: Option<Vec<u8>>,
+}
+
+impl WsStream {
+    // 追加字节，返回已完整的数据消息（控制帧被忽略）
+    pub fn push(&mut self, bytes: &[u8]) -> Result<Vec<Vec<u8>>> {
+        self.buf.extend_from_slice(bytes);
+        let mut messages = Vec::new();
+        loop {
+            let (frame, consumed) = match parse_frame(&self.buf) {
+                Ok(Some(parsed)) => parsed,
+                Ok(None) => break,
+                Err(e) => {
+                    self.buf.clear();
+                    self.partial = None;
+                    return Err(e);
+                }
+            };
+            self.buf.drain(..consumed);
+
+            match frame.opcode {
+                OPCODE_TEXT | OPCODE_BINARY if frame.fin => messages.push(frame.payload),
+                OPCODE_TEXT | OPCODE_BINARY => self.partial = Some(frame.payload),
+                OPCODE_CONTINUATION => {
+                    let Some(partial) = self.partial.as_mut() else {
+                        bail!("WebSocket continuation frame without a preceding data frame.");
+                    };
+                    partial.extend_from_slice(&frame.payload);
+                    if frame.fin {
+                        messages.extend(self.partial.take());
+                    }
+                }
+                // close / ping / pong
+                _ => (),
+            }
+        }
+        Ok(messages)
+    }
+}
+
+// HTTP Upgrade 握手阶段的报文，不是 WebSocket 帧
+pub fn is_http_handshake(payload: &[u8]) -> bool {
+    payload.starts_with(b"GET ") || payload.starts_with(b"HTTP/1.1 ")
+}
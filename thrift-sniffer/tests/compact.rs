@@ -0,0 +1,90 @@
+use thrift_sniffer::decode::{self, ttype, DecodeError, ThriftValue};
+
+// Compact 编码的 call f，seq id 5，覆盖 bool 字段、zigzag 整数、小端 double、容器与长格式字段 id
+// 字段头高 4 位为相对上一字段 id 的增量
+const MESSAGE: &[u8] = &[
+    0x82, 0x21, // 协议 id，call 与版本 1
+    0x05, // seq id
+    0x01, b'f', // 方法名
+    0x11, // field 1: bool true
+    0x15, 0x05, // field 2: i32 -3
+    0x17, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xf8, 0x3f, // field 3: double 1.5
+    0x19, 0x21, 0x01, 0x02, // field 4: list<bool> [true, false]
+    0x1b, 0x01, 0x86, 0x01, b'a', 0xd8, 0x04, // field 5: map<string, i64> {"a": 300}
+    0x04, 0xc8, 0x01, 0x01, // field 100（长格式 id）: i16 -1
+    0x1b, 0x00, // field 101: 空 map
+    0x00,
+];
+
+#[test]
+fn compact_message_is_decoded() {
+    let msg = decode::decode_message(MESSAGE).unwrap();
+    assert_eq!(msg.message_type, 1);
+    assert_eq!(msg.name, "f");
+    assert_eq!(msg.seq_id, 5);
+    assert_eq!(
+        msg.body,
+        vec![
+            (1, ThriftValue::Bool(true)),
+            (2, ThriftValue::I32(-3)),
+            (3, ThriftValue::Double(1.5)),
+            (
+                4,
+                ThriftValue::List(
+                    ttype::BOOL,
+                    vec![ThriftValue::Bool(true), ThriftValue::Bool(false)]
+                )
+            ),
+            (
+                5,
+                ThriftValue::Map(
+                    ttype::STRING,
+                    ttype::I64,
+                    vec![(ThriftValue::String(b"a".to_vec()), ThriftValue::I64(300))]
+                )
+            ),
+            (100, ThriftValue::I16(-1)),
+            (101, ThriftValue::Map(ttype::STOP, ttype::STOP, Vec::new())),
+        ]
+    );
+}
+
+#[test]
+fn compact_version_is_checked() {
+    let mut data = MESSAGE.to_vec();
+    data[1] = 0x22;
+    assert_eq!(
+        decode::decode_message(&data),
+        Err(DecodeError::BadVersion { word: 0x8222 })
+    );
+}
+
+#[test]
+fn truncated_compact_message() {
+    let err = decode::decode_message(&MESSAGE[..MESSAGE.len() - 1]).unwrap_err();
+    assert_eq!(err.kind(), "unexpected-eof");
+}
+
+#[test]
+fn varint_boundaries() {
+    assert_eq!(decode::decode_varint(&[0xac, 0x02], 0), Ok((300, 2)));
+    let mut max = vec![0xff; 9];
+    max.push(0x01);
+    assert_eq!(decode::decode_varint(&max, 0), Ok((u64::MAX, 10)));
+}
+
+// 20 个 0xFF：第 10 个字节已超出 64 位，不再继续读取
+#[test]
+fn overlong_varint_is_rejected() {
+    assert_eq!(
+        decode::decode_varint(&[0xff; 20], 0),
+        Err(DecodeError::InvalidVarint { at: 0 })
+    );
+    // 只有 10 个字节，但第 10 个字节的值超出 64 位
+    let mut overflow = vec![0x80; 9];
+    overflow.push(0x02);
+    assert_eq!(
+        decode::decode_varint(&overflow, 0),
+        Err(DecodeError::InvalidVarint { at: 0 })
+    );
+}
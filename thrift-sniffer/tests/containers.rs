@@ -0,0 +1,80 @@
+use thrift_sniffer::decode::{self, ttype, ThriftValue};
+
+// struct { 1: list<i64> ids, 2: list<string> tags }，之后还有一个字段，确认列表后的 offset 正确
+const LISTS: &[u8] = &[
+    0x0f, 0x00, 0x01, 0x0a, 0x00, 0x00, 0x00, 0x02, // field 1: list<i64>，2 个元素
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, // 1
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe, // -2
+    0x0f, 0x00, 0x02, 0x0b, 0x00, 0x00, 0x00, 0x02, // field 2: list<string>，2 个元素
+    0x00, 0x00, 0x00, 0x01, b'a', // "a"
+    0x00, 0x00, 0x00, 0x02, b'b', b'c', // "bc"
+    0x08, 0x00, 0x03, 0x00, 0x00, 0x00, 0x07, // field 3: i32 7
+    0x00,
+];
+
+#[test]
+fn list_elements_decode_by_element_type() {
+    let (value, end) = decode::decode_value(LISTS, 0, ttype::STRUCT).unwrap();
+    assert_eq!(end, LISTS.len());
+    assert_eq!(
+        value,
+        ThriftValue::Struct(vec![
+            (
+                1,
+                ThriftValue::List(ttype::I64, vec![ThriftValue::I64(1), ThriftValue::I64(-2)])
+            ),
+            (
+                2,
+                ThriftValue::List(
+                    ttype::STRING,
+                    vec![
+                        ThriftValue::String(b"a".to_vec()),
+                        ThriftValue::String(b"bc".to_vec())
+                    ]
+                )
+            ),
+            (3, ThriftValue::I32(7)),
+        ])
+    );
+}
+
+// struct { 1: map<Item, i64> }，Item { 1: i64 id, 2: string title }；键是结构体，按 STOP 结束而不是固定长度
+const STRUCT_KEYED_MAP: &[u8] = &[
+    0x0d, 0x00, 0x01, 0x0c, 0x0a, 0x00, 0x00, 0x00,
+    0x02, // field 1: map<struct, i64>，2 对键值
+    0x0a, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, // 键 Item.id = 1
+    0x0b, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01, b'x', 0x00, // 键 Item.title = "x"
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0a, // 值 10
+    0x0a, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, // 键 Item.id = 2
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x14, // 值 20
+    0x08, 0x00, 0x02, 0x00, 0x00, 0x00, 0x07, // field 2: i32 7
+    0x00,
+];
+
+#[test]
+fn map_with_struct_keys_advances_past_each_key() {
+    let (value, end) = decode::decode_value(STRUCT_KEYED_MAP, 0, ttype::STRUCT).unwrap();
+    assert_eq!(end, STRUCT_KEYED_MAP.len());
+    let item = |id, title: Option<&[u8]>| {
+        let mut fields = vec![(1, ThriftValue::I64(id))];
+        fields.extend(title.map(|t| (2, ThriftValue::String(t.to_vec()))));
+        ThriftValue::Struct(fields)
+    };
+    assert_eq!(
+        value,
+        ThriftValue::Struct(vec![
+            (
+                1,
+                ThriftValue::Map(
+                    ttype::STRUCT,
+                    ttype::I64,
+                    vec![
+                        (item(1, Some(b"x")), ThriftValue::I64(10)),
+                        (item(2, None), ThriftValue::I64(20)),
+                    ]
+                )
+            ),
+            (2, ThriftValue::I32(7)),
+        ])
+    );
+}
@@ -0,0 +1,83 @@
+use thrift_sniffer::idl::{Field, Idl, Type};
+
+const SERVICE: &str = r#"
+namespace rs volo.example
+include "base.thrift"
+
+typedef i64 ItemId (go.type = "int64")
+const list<string> DEFAULT_TAGS = ["a", "b"];
+
+/* 多行
+   注释 */
+enum Status {
+    UNKNOWN,
+    ACTIVE = 0x10,
+    DELETED,
+}
+
+struct Item {
+    1: required ItemId id,
+    2: optional map<string, list<base.Tag>> tags = {},
+    # 没写 id 的字段
+    binary raw;
+}
+
+service ItemService extends base.BaseService {
+    Item GetItem(1: i64 id, 2: Status status) throws (1: base.Error err),
+    oneway void Touch(1: ItemId id);
+}
+"#;
+
+#[test]
+fn parses_structs_enums_and_services() {
+    let mut idl = Idl::default();
+    idl.parse(SERVICE).unwrap();
+
+    assert_eq!(
+        idl.struct_fields("Item").unwrap(),
+        &[
+            Field {
+                id: 1,
+                name: "id".into(),
+                ty: Type::Named("ItemId".into()),
+                optional: false,
+            },
+            Field {
+                id: 2,
+                name: "tags".into(),
+                ty: Type::Map(
+                    Box::new(Type::String),
+                    Box::new(Type::List(Box::new(Type::Named("Tag".into())))),
+                ),
+                optional: true,
+            },
+            Field {
+                id: -1,
+                name: "raw".into(),
+                ty: Type::Binary,
+                optional: false,
+            },
+        ]
+    );
+    assert_eq!(idl.resolve(&Type::Named("ItemId".into())), &Type::I64);
+    assert!(idl.is_enum("Status"));
+    assert_eq!(idl.enum_name("Status", 0), Some("UNKNOWN"));
+    assert_eq!(idl.enum_name("Status", 0x11), Some("DELETED"));
+
+    let get_item = idl.method("GetItem").unwrap();
+    assert!(!get_item.oneway);
+    assert_eq!(get_item.returns, Some(Type::Named("Item".into())));
+    assert_eq!(get_item.args.len(), 2);
+    let touch = idl.method("Touch").unwrap();
+    assert!(touch.oneway);
+    assert_eq!(touch.returns, None);
+    assert_eq!(touch.args[0].name, "id");
+}
+
+#[test]
+fn syntax_errors_report_the_line() {
+    let err = Idl::default()
+        .parse("struct A {\n    1: i64 id,\n    2: list<i32 ids,\n}")
+        .unwrap_err();
+    assert!(err.to_string().starts_with("line 3:"), "{}", err);
+}
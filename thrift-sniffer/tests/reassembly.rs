@@ -0,0 +1,111 @@
+use std::time::Duration;
+use thrift_sniffer::reassembly::{Reassembled, StreamReassembler, MAX_OUT_OF_ORDER};
+
+// 单元测试里只有一个方向，key 用不到
+const KEY: u8 = 0;
+
+// unframed Binary 的 GetItem(id = 1024) 请求
+const UNFRAMED: &[u8] = &[
+    0x80, 0x01, 0x00, 0x01, // Binary call
+    0x00, 0x00, 0x00, 0x07, b'G', b'e', b't', b'I', b't', b'e', b'm', // 方法名
+    0x00, 0x00, 0x00, 0x01, // seq id
+    0x0c, 0x00, 0x01, // field 1: GetItemRequest
+    0x0a, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x00, // field 1: i64 1024
+    0x00, 0x00,
+];
+
+// 加上 4 字节长度前缀
+fn framed(body: &[u8]) -> Vec<u8> {
+    let mut frame = (body.len() as u32).to_be_bytes().to_vec();
+    frame.extend_from_slice(body);
+    frame
+}
+
+// 依次推入 (seq, bytes)，收集交出的消息与缺口
+fn reassemble(segments: &[(u32, &[u8])]) -> (Vec<Vec<u8>>, Vec<(u64, usize)>) {
+    let mut reassembler = StreamReassembler::new(Duration::from_secs(60));
+    let mut messages = Vec::new();
+    let mut gaps = Vec::new();
+    for &(seq, bytes) in segments {
+        reassembler.push(KEY, seq, bytes, |event| match event {
+            Reassembled::Message(msg) => messages.push(msg.to_vec()),
+            Reassembled::Gap { missing, dropped } => gaps.push((missing, dropped)),
+        });
+    }
+    (messages, gaps)
+}
+
+#[test]
+fn in_order_segments_are_joined() {
+    let frame = framed(UNFRAMED);
+    let (head, tail) = frame.split_at(10);
+    let (messages, gaps) = reassemble(&[(1000, head), (1010, tail)]);
+    assert_eq!(messages, vec![frame.clone()]);
+    assert!(gaps.is_empty());
+}
+
+#[test]
+fn out_of_order_and_retransmitted_segments() {
+    let frame = framed(UNFRAMED);
+    let (messages, _) = reassemble(&[
+        (1000, &frame[..10]),
+        (1020, &frame[20..]),
+        // 与已收数据部分重叠的重传
+        (1005, &frame[5..20]),
+        (1000, &frame[..10]),
+    ]);
+    assert_eq!(messages, vec![frame]);
+}
+
+#[test]
+fn sequence_number_wraparound() {
+    let frame = framed(UNFRAMED);
+    let start = u32::MAX - 9;
+    // 后一段的序号已回绕到 0 附近，数值上比前一段小，仍应排在后面
+    let (messages, _) = reassemble(&[
+        (start, &frame[..5]),
+        (start.wrapping_add(20), &frame[20..]),
+        (start.wrapping_add(10), &frame[10..20]),
+        (start.wrapping_add(5), &frame[5..10]),
+    ]);
+    assert_eq!(messages, vec![frame]);
+}
+
+#[test]
+fn gap_is_skipped_once_out_of_order_buffer_is_full() {
+    let frame = framed(UNFRAMED);
+    let lost = 10;
+    let mut segments = vec![(0, &frame[..lost])];
+    // 第一条消息的后半段丢失，之后的消息全部排在缺口之后
+    let mut seq = frame.len() as u32;
+    for _ in 0..=MAX_OUT_OF_ORDER {
+        segments.push((seq, &frame[..]));
+        seq += frame.len() as u32;
+    }
+    let (messages, gaps) = reassemble(&segments);
+    assert_eq!(gaps, vec![((frame.len() - lost) as u64, lost)]);
+    assert_eq!(messages.len(), MAX_OUT_OF_ORDER + 1);
+    assert!(messages.iter().all(|msg| *msg == frame));
+}
+
+#[test]
+fn unframed_binary_is_split_by_message_structure() {
+    let mut stream = UNFRAMED.to_vec();
+    stream.extend_from_slice(UNFRAMED);
+    let (messages, _) = reassemble(&[
+        (0, &stream[..20]),
+        (20, &stream[20..50]),
+        (50, &stream[50..]),
+    ]);
+    assert_eq!(messages, vec![UNFRAMED.to_vec(), UNFRAMED.to_vec()]);
+}
+
+#[test]
+fn unrecognized_data_is_passed_through() {
+    // unframed Compact 没有长度前缀，原样交给调用方
+    let compact = [
+        0x82, 0x21, 0x01, 0x07, b'G', b'e', b't', b'I', b't', b'e', b'm', 0x00,
+    ];
+    let (messages, _) = reassemble(&[(0, &compact)]);
+    assert_eq!(messages, vec![compact.to_vec()]);
+}
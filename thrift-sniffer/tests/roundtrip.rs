@@ -0,0 +1,141 @@
+use thrift_sniffer::decode::{self, ttype, ThriftValue};
+use thrift_sniffer::encode;
+
+// GetItem(id = 1024) 的 Call
+const GET_ITEM_CALL: &[u8] = &[
+    0x80, 0x01, 0x00, 0x01, // Binary call
+    0x00, 0x00, 0x00, 0x07, b'G', b'e', b't', b'I', b't', b'e', b'm', // 方法名
+    0x00, 0x00, 0x00, 0x01, // seq id
+    0x0c, 0x00, 0x01, // field 1: GetItemRequest
+    0x0a, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x00, // field 1: i64 1024
+    0x00, 0x00,
+];
+
+// GetItem 的 Reply：field 0 为 Item { 1: i64 id, 2: string title, 3: map<string, string> extra }
+const GET_ITEM_REPLY: &[u8] = &[
+    0x80, 0x01, 0x00, 0x02, // Binary reply
+    0x00, 0x00, 0x00, 0x07, b'G', b'e', b't', b'I', b't', b'e', b'm', // 方法名
+    0x00, 0x00, 0x00, 0x01, // seq id
+    0x0c, 0x00, 0x00, // field 0: GetItemResponse
+    0x0c, 0x00, 0x01, // field 1: Item
+    0x0a, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x00, // id
+    0x0b, 0x00, 0x02, 0x00, 0x00, 0x00, 0x02, b'h', b'i', // title
+    0x0d, 0x00, 0x03, 0x0b, 0x0b, 0x00, 0x00, 0x00, 0x01, // extra: 1 对键值
+    0x00, 0x00, 0x00, 0x01, b'k', 0x00, 0x00, 0x00, 0x01, b'v', // "k" => "v"
+    0x00, 0x00, 0x00,
+];
+
+// UNKNOWN_METHOD 的 Exception
+const UNKNOWN_METHOD: &[u8] = &[
+    0x80, 0x01, 0x00, 0x03, // Binary exception
+    0x00, 0x00, 0x00, 0x03, b'F', b'o', b'o', // 方法名
+    0x00, 0x00, 0x00, 0x07, // seq id
+    0x0b, 0x00, 0x01, 0x00, 0x00, 0x00, 0x03, b'F', b'o', b'o', // message
+    0x08, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01, // type
+    0x00,
+];
+
+#[test]
+fn captured_messages_round_trip() {
+    for data in [GET_ITEM_CALL, GET_ITEM_REPLY, UNKNOWN_METHOD] {
+        let msg = decode::decode_message(data).unwrap();
+        assert_eq!(encode::encode_message(&msg), data, "{}", msg);
+    }
+}
+
+// 固定种子的 xorshift，生成可复现的随机值
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+}
+
+const SCALAR_TYPES: [u8; 7] = [
+    ttype::BOOL,
+    ttype::BYTE,
+    ttype::DOUBLE,
+    ttype::I16,
+    ttype::I32,
+    ttype::I64,
+    ttype::STRING,
+];
+
+fn random_value(rng: &mut Rng, value_type: u8, depth: usize) -> ThriftValue {
+    match value_type {
+        ttype::BOOL => ThriftValue::Bool(rng.below(2) == 1),
+        ttype::BYTE => ThriftValue::Byte(rng.next_u64() as i8),
+        // 只取有限值，NaN 与自身不相等
+        ttype::DOUBLE => ThriftValue::Double(rng.next_u64() as i32 as f64 / 7.0),
+        ttype::I16 => ThriftValue::I16(rng.next_u64() as i16),
+        ttype::I32 => ThriftValue::I32(rng.next_u64() as i32),
+        ttype::I64 => ThriftValue::I64(rng.next_u64() as i64),
+        ttype::STRING => {
+            let len = rng.below(8) as usize;
+            ThriftValue::String((0..len).map(|_| rng.next_u64() as u8).collect())
+        }
+        ttype::STRUCT => {
+            let fields = (0..rng.below(4))
+                .map(|_| {
+                    let id = rng.below(32) as i16;
+                    let field_type = random_type(rng, depth);
+                    (id, random_value(rng, field_type, depth + 1))
+                })
+                .collect();
+            ThriftValue::Struct(fields)
+        }
+        ttype::MAP => {
+            let key_type = random_type(rng, depth);
+            let value_type = random_type(rng, depth);
+            let entries = (0..rng.below(4))
+                .map(|_| {
+                    (
+                        random_value(rng, key_type, depth + 1),
+                        random_value(rng, value_type, depth + 1),
+                    )
+                })
+                .collect();
+            ThriftValue::Map(key_type, value_type, entries)
+        }
+        _ => {
+            let elem_type = random_type(rng, depth);
+            let elems = (0..rng.below(4))
+                .map(|_| random_value(rng, elem_type, depth + 1))
+                .collect();
+            if value_type == ttype::SET {
+                ThriftValue::Set(elem_type, elems)
+            } else {
+                ThriftValue::List(elem_type, elems)
+            }
+        }
+    }
+}
+
+// 嵌套到第 3 层后只生成标量
+fn random_type(rng: &mut Rng, depth: usize) -> u8 {
+    if depth >= 3 || rng.below(2) == 0 {
+        return SCALAR_TYPES[rng.below(SCALAR_TYPES.len() as u64) as usize];
+    }
+    [ttype::STRUCT, ttype::MAP, ttype::SET, ttype::LIST][rng.below(4) as usize]
+}
+
+#[test]
+fn random_structs_round_trip() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..500 {
+        let value = random_value(&mut rng, ttype::STRUCT, 0);
+        let bytes = encode::encode_binary(&value);
+        let (decoded, end) = decode::decode_value(&bytes, 0, ttype::STRUCT).unwrap();
+        assert_eq!(end, bytes.len());
+        assert_eq!(decoded, value);
+        assert_eq!(encode::encode_binary(&decoded), bytes);
+    }
+}
@@ -0,0 +1,65 @@
+use thrift_sniffer::decode::{self, ttype, Message, ThriftValue};
+use thrift_sniffer::encode;
+
+// 每种标量类型各一个字段，类型码取自 ttype，错用类型码时后续字段全部错位
+const SCALARS: &[u8] = &[
+    0x02, 0x00, 0x01, 0x01, // field 1: bool true
+    0x03, 0x00, 0x02, 0xfe, // field 2: byte -2
+    0x04, 0x00, 0x03, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // field 3: double 1.5
+    0x06, 0x00, 0x04, 0x01, 0x00, // field 4: i16 256
+    0x08, 0x00, 0x05, 0xff, 0xff, 0xff, 0xfd, // field 5: i32 -3
+    0x0a, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, // field 6: i64 1 << 32
+    0x0b, 0x00, 0x07, 0x00, 0x00, 0x00, 0x02, b'o', b'k', // field 7: string "ok"
+    0x00,
+];
+
+#[test]
+fn every_scalar_type_decodes() {
+    let (value, end) = decode::decode_value(SCALARS, 0, ttype::STRUCT).unwrap();
+    assert_eq!(end, SCALARS.len());
+    assert_eq!(
+        value,
+        ThriftValue::Struct(vec![
+            (1, ThriftValue::Bool(true)),
+            (2, ThriftValue::Byte(-2)),
+            (3, ThriftValue::Double(1.5)),
+            (4, ThriftValue::I16(256)),
+            (5, ThriftValue::I32(-3)),
+            (6, ThriftValue::I64(1 << 32)),
+            (7, ThriftValue::String(b"ok".to_vec())),
+        ])
+    );
+    assert_eq!(
+        value.to_string(),
+        "{1: true, 2: -2, 3: 1.5, 4: 256, 5: -3, 6: 4294967296, 7: \"ok\"}"
+    );
+}
+
+// 每种标量类型的边界值各作为一个字段，encode_message 编码后经 decode_message 解回原消息
+#[test]
+fn every_scalar_type_round_trips_through_decode_message() {
+    let values = [
+        ThriftValue::Bool(false),
+        ThriftValue::Bool(true),
+        ThriftValue::Byte(i8::MIN),
+        ThriftValue::Byte(i8::MAX),
+        ThriftValue::Double(-1.5),
+        ThriftValue::Double(f64::MAX),
+        ThriftValue::I16(i16::MIN),
+        ThriftValue::I16(i16::MAX),
+        ThriftValue::I32(i32::MIN),
+        ThriftValue::I32(i32::MAX),
+        ThriftValue::I64(i64::MIN),
+        ThriftValue::I64(i64::MAX),
+        ThriftValue::String(Vec::new()),
+        ThriftValue::String("ok ✓".as_bytes().to_vec()),
+    ];
+    let msg = Message {
+        message_type: 1,
+        name: "f".to_string(),
+        seq_id: 1,
+        body: (1..).zip(values).collect(),
+    };
+    let decoded = decode::decode_message(&encode::encode_message(&msg)).unwrap();
+    assert_eq!(decoded, msg);
+}
@@ -0,0 +1,142 @@
+use thrift_sniffer::decode::{
+    self, DecodeError, THEADER_PROTOCOL_BINARY, THEADER_PROTOCOL_COMPACT,
+};
+
+// GetItem(id = 1024) 请求帧：TTHeader，seq id 1，一个字符串 info header x-trace-id = abc，
+// 头部 24 字节（含 2 字节填充），消息体为 Binary 协议
+const BINARY_FRAME: &[u8] = &[
+    0x00, 0x00, 0x00, 0x45, // 帧长 69
+    0x10, 0x00, // magic
+    0x00, 0x00, // flags
+    0x00, 0x00, 0x00, 0x01, // seq id
+    0x00, 0x06, // 头部长度 6 * 4
+    0x00, 0x00, // protocol id Binary，无 transform
+    0x01, 0x00, 0x01, // 字符串 info 块，1 对键值
+    0x00, 0x0a, b'x', b'-', b't', b'r', b'a', b'c', b'e', b'-', b'i', b'd', // key
+    0x00, 0x03, b'a', b'b', b'c', // value
+    0x00, 0x00, // 填充
+    0x80, 0x01, 0x00, 0x01, // Binary call
+    0x00, 0x00, 0x00, 0x07, b'G', b'e', b't', b'I', b't', b'e', b'm', // 方法名
+    0x00, 0x00, 0x00, 0x01, // seq id
+    0x0c, 0x00, 0x01, // field 1: GetItemRequest
+    0x0a, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x00, // field 1: i64 1024
+    0x00, 0x00,
+];
+
+// 同一请求以 Compact 协议编码：flags 非 0，seq id 42，一个整数 info header 8 = "42"，头部 12 字节（含 1 字节填充）
+const COMPACT_FRAME: &[u8] = &[
+    0x00, 0x00, 0x00, 0x27, // 帧长 39
+    0x10, 0x00, // magic
+    0x00, 0x01, // flags
+    0x00, 0x00, 0x00, 0x2a, // seq id
+    0x00, 0x03, // 头部长度 3 * 4
+    0x02, 0x00, // protocol id Compact，无 transform
+    0x10, 0x00, 0x01, // 整数 info 块，1 对键值
+    0x00, 0x08, 0x00, 0x02, b'4', b'2', // key 8，value "42"
+    0x00, // 填充
+    0x82, 0x21, 0x2a, // Compact call，seq id 42
+    0x07, b'G', b'e', b't', b'I', b't', b'e', b'm', // 方法名
+    0x1c, 0x16, 0x80, 0x10, 0x00, 0x00, // field 1 { field 1: i64 1024 }
+];
+
+#[test]
+fn parse_binary_frame() {
+    let header = decode::parse_theader(BINARY_FRAME).unwrap();
+    assert_eq!(header.flags, 0);
+    assert_eq!(header.seq_id, 1);
+    assert_eq!(header.protocol_id, THEADER_PROTOCOL_BINARY);
+    assert!(header.transforms.is_empty());
+    assert_eq!(header.header_end, 38);
+    assert_eq!(
+        decode::theader_headers(BINARY_FRAME).unwrap(),
+        vec![("x-trace-id".to_string(), "abc".to_string())]
+    );
+
+    let body = decode::strip_transport(BINARY_FRAME).unwrap();
+    assert_eq!(&body[..], &BINARY_FRAME[38..]);
+    let msg = decode::decode_message(&body).unwrap();
+    assert_eq!(msg.name, "GetItem");
+    assert_eq!(msg.seq_id, 1);
+}
+
+#[test]
+fn parse_compact_frame_with_flags() {
+    let header = decode::parse_theader(COMPACT_FRAME).unwrap();
+    assert_eq!(header.flags, 1);
+    assert_eq!(header.seq_id, 42);
+    assert_eq!(header.protocol_id, THEADER_PROTOCOL_COMPACT);
+    assert_eq!(header.header_end, 26);
+    assert_eq!(
+        decode::theader_headers(COMPACT_FRAME).unwrap(),
+        vec![("8".to_string(), "42".to_string())]
+    );
+    assert_eq!(
+        decode::strip_theader(COMPACT_FRAME).unwrap(),
+        &COMPACT_FRAME[26..]
+    );
+    let msg = decode::decode_message(&COMPACT_FRAME[26..]).unwrap();
+    assert_eq!(msg.name, "GetItem");
+    assert_eq!(msg.seq_id, 42);
+}
+
+#[test]
+fn magic_mismatch_is_reported() {
+    // Apache THeader 的 magic 0x0FFF
+    let mut frame = BINARY_FRAME.to_vec();
+    frame[4..6].copy_from_slice(&[0x0f, 0xff]);
+    assert_eq!(
+        decode::strip_theader(&frame),
+        Err(DecodeError::NotTHeader { magic: 0x0fff })
+    );
+}
+
+#[test]
+fn body_must_match_protocol_id() {
+    let mut frame = BINARY_FRAME.to_vec();
+    frame[14] = THEADER_PROTOCOL_COMPACT;
+    assert_eq!(
+        decode::strip_theader(&frame),
+        Err(DecodeError::NoBinaryPayload { from: 38 })
+    );
+}
+
+// 把 BINARY_FRAME 的消息体换成 zlib 压缩后的 body，头部只有 protocol id 与一个 zlib transform
+#[cfg(feature = "zlib")]
+fn zlib_frame(body: &[u8]) -> Vec<u8> {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(body).unwrap();
+    let compressed = encoder.finish().unwrap();
+    // protocol id Binary，1 个 transform：zlib，1 字节填充
+    let header = [0x00, 0x01, decode::TRANSFORM_ZLIB, 0x00];
+    let mut frame = ((10 + header.len() + compressed.len()) as u32)
+        .to_be_bytes()
+        .to_vec();
+    frame.extend_from_slice(&BINARY_FRAME[4..12]); // magic、flags、seq id
+    frame.extend_from_slice(&[0x00, 0x01]); // 头部长度 1 * 4
+    frame.extend_from_slice(&header);
+    frame.extend_from_slice(&compressed);
+    frame
+}
+
+#[cfg(feature = "zlib")]
+#[test]
+fn zlib_transform_is_inflated() {
+    let frame = zlib_frame(&BINARY_FRAME[38..]);
+    let body = decode::strip_transport(&frame).unwrap();
+    assert_eq!(&body[..], &BINARY_FRAME[38..]);
+}
+
+#[cfg(feature = "zlib")]
+#[test]
+fn inflated_size_is_capped() {
+    // 全零的数据压缩比极高，解压到上限即停止
+    let frame = zlib_frame(&vec![0; decode::MAX_INFLATED_SIZE + 1]);
+    assert_eq!(
+        decode::strip_transport(&frame),
+        Err(DecodeError::InflatedTooLarge {
+            limit: decode::MAX_INFLATED_SIZE,
+        })
+    );
+}
@@ -0,0 +1,164 @@
+use thrift_sniffer::decode::{self, ttype, DecodeError, DecodeOptions, ThriftValue, MAX_DEPTH};
+
+// Compact 编码的 call GetItem，第一个参数中 field 1 为 5 字节的字符串
+const COMPACT: &[u8] = &[
+    0x82, 0x21, // 协议 id，call 与版本 1
+    0x01, // seq id
+    0x07, b'G', b'e', b't', b'I', b't', b'e', b'm', // 方法名
+    0x1c, // field 1: struct
+    0x18, 0x05, b'h', b'e', b'l', b'l', b'o', // field 1: binary
+    0x00, 0x00,
+];
+
+// 同一请求的 Binary 编码
+const BINARY: &[u8] = &[
+    0x80, 0x01, 0x00, 0x01, // Binary call
+    0x00, 0x00, 0x00, 0x07, b'G', b'e', b't', b'I', b't', b'e', b'm', // 方法名
+    0x00, 0x00, 0x00, 0x01, // seq id
+    0x0c, 0x00, 0x01, // field 1: struct
+    0x0b, 0x00, 0x01, 0x00, 0x00, 0x00, 0x05, b'h', b'e', b'l', b'l', b'o', // field 1: string
+    0x00, 0x00,
+];
+
+fn limit(max: usize) -> DecodeOptions {
+    DecodeOptions {
+        max_string_size: Some(max),
+    }
+}
+
+#[test]
+fn both_protocols_pass_within_limit() {
+    assert_eq!(decode::validate_message(COMPACT, &limit(8)), Ok(()));
+    assert_eq!(decode::validate_message(BINARY, &limit(8)), Ok(()));
+}
+
+#[test]
+fn oversized_string_is_reported_for_both_protocols() {
+    assert_eq!(
+        decode::validate_message(COMPACT, &limit(4)),
+        Err(DecodeError::LengthTooLarge {
+            declared: 5,
+            limit: 4,
+            at: 13,
+        })
+    );
+    assert_eq!(
+        decode::validate_message(BINARY, &limit(4)),
+        Err(DecodeError::LengthTooLarge {
+            declared: 5,
+            limit: 4,
+            at: 25,
+        })
+    );
+}
+
+// 解码时超长字符串截断为前缀并保留声明的长度，而不是报错
+#[test]
+fn oversized_string_is_truncated_when_decoding() {
+    for data in [COMPACT, BINARY] {
+        let msg = decode::decode_message_with(data, &limit(2)).unwrap();
+        assert_eq!(
+            msg.body,
+            vec![(
+                1,
+                ThriftValue::Struct(vec![(1, ThriftValue::TruncatedString(b"he".to_vec(), 5))])
+            )]
+        );
+    }
+}
+
+#[test]
+fn truncated_message_is_not_a_size_error() {
+    let err = decode::validate_message(&COMPACT[..15], &limit(8)).unwrap_err();
+    assert_eq!(err.kind(), "unexpected-eof");
+}
+
+// 参数中层层嵌套 depth 层结构体、list<list<...>> 或 map<i32, map<...>> 的 call，compact 为 true 时使用 Compact 编码
+fn nested(kind: u8, depth: usize, compact: bool) -> Vec<u8> {
+    let mut data = if compact {
+        vec![0x82, 0x21, 0x01, 0x01, b'f']
+    } else {
+        vec![
+            0x80, 0x01, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, b'f', 0x00, 0x00, 0x00, 0x01,
+        ]
+    };
+    match (kind, compact) {
+        // 每层都是 field 1: struct，最后逐层以 STOP 结束
+        (ttype::STRUCT, false) => {
+            for _ in 0..depth {
+                data.extend([0x0c, 0x00, 0x01]);
+            }
+            data.extend(std::iter::repeat_n(0x00, depth));
+        }
+        (ttype::STRUCT, true) => {
+            data.extend(std::iter::repeat_n(0x1c, depth));
+            data.extend(std::iter::repeat_n(0x00, depth));
+        }
+        // field 1 为 list，每层只有一个元素，即下一层的 list，最内层是空的 list<i32>
+        (ttype::LIST, false) => {
+            data.extend([0x0f, 0x00, 0x01]);
+            for _ in 1..depth {
+                data.extend([0x0f, 0x00, 0x00, 0x00, 0x01]);
+            }
+            data.extend([0x08, 0x00, 0x00, 0x00, 0x00]);
+        }
+        (ttype::LIST, true) => {
+            data.push(0x19);
+            data.extend(std::iter::repeat_n(0x19, depth - 1));
+            data.push(0x05);
+        }
+        // field 1 为 map，每层只有一对键值：键 i32 0，值为下一层的 map，最内层是空 map
+        (_, false) => {
+            data.extend([0x0d, 0x00, 0x01]);
+            for _ in 1..depth {
+                data.extend([0x08, 0x0d, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00]);
+            }
+            data.extend([0x08, 0x08, 0x00, 0x00, 0x00, 0x00]);
+        }
+        (_, true) => {
+            data.push(0x1b);
+            for _ in 1..depth {
+                data.extend([0x01, 0x5b, 0x00]);
+            }
+            data.push(0x00);
+        }
+    }
+    // 参数结构体的 STOP
+    data.push(0x00);
+    data
+}
+
+#[test]
+fn deeply_nested_values_are_rejected_without_overflowing() {
+    for kind in [ttype::STRUCT, ttype::LIST, ttype::MAP] {
+        for compact in [false, true] {
+            let data = nested(kind, MAX_DEPTH, compact);
+            assert!(
+                decode::decode_message(&data).is_ok(),
+                "{} {}",
+                kind,
+                compact
+            );
+            assert_eq!(decode::validate_message(&data, &limit(8)), Ok(()));
+
+            // 远超上限的嵌套，没有深度检查时会栈溢出
+            for depth in [200, 5000] {
+                let data = nested(kind, depth, compact);
+                assert!(
+                    matches!(
+                        decode::decode_message(&data),
+                        Err(DecodeError::DepthExceeded { .. })
+                    ),
+                    "{} {} {}",
+                    kind,
+                    compact,
+                    depth
+                );
+                assert!(matches!(
+                    decode::validate_message(&data, &limit(8)),
+                    Err(DecodeError::DepthExceeded { .. })
+                ));
+            }
+        }
+    }
+}
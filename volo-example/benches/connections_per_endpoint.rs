@@ -0,0 +1,47 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use futures::future::join_all;
+use volo_example::client::ConnectionSet;
+use volo_example::S;
+use volo_gen::volo::example::{GetItemRequest, ItemServiceClientBuilder, ItemServiceServer};
+
+const CONCURRENCY: usize = 64;
+
+fn connections_per_endpoint(c: &mut Criterion) {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let addr: SocketAddr = "127.0.0.1:19090".parse().unwrap();
+    rt.spawn(async move {
+        ItemServiceServer::new(S::default())
+            .run(volo::net::Address::from(addr))
+            .await
+            .unwrap();
+    });
+    rt.block_on(tokio::time::sleep(Duration::from_millis(100)));
+
+    let mut group = c.benchmark_group("connections_per_endpoint");
+    group.throughput(Throughput::Elements(CONCURRENCY as u64));
+    for n in [1, 2, 4, 8] {
+        let clients = ConnectionSet::new(n, || {
+            ItemServiceClientBuilder::new("bench")
+                .address(addr)
+                .multiplex(true)
+                .build()
+        });
+        group.bench_with_input(BenchmarkId::from_parameter(n), &clients, |b, clients| {
+            b.to_async(&rt).iter(|| async {
+                join_all((0..CONCURRENCY).map(|i| {
+                    clients
+                        .pick()
+                        .get_item(GetItemRequest { id: i as i64 })
+                }))
+                .await
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, connections_per_endpoint);
+criterion_main!(benches);
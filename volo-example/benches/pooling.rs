@@ -0,0 +1,119 @@
+use std::io;
+use std::time::{Duration, Instant};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use futures::future::join_all;
+use tokio::net::TcpListener;
+use volo::net::conn::{Conn, ConnInfo, ConnStream};
+use volo::net::incoming::{Incoming, MakeIncoming};
+use volo::net::Address;
+use volo_example::S;
+use volo_gen::volo::example::{
+    GetItemRequest, ItemServiceClient, ItemServiceClientBuilder, ItemServiceServer,
+};
+
+// 统计 p99 前先跑几批预热，不计入结果
+const WARM_UP_BATCHES: usize = 10;
+const SAMPLE_BATCHES: usize = 100;
+
+// 已绑定在随机端口上的监听，交给 Server::run，避免与其它进程的固定端口冲突
+#[derive(Debug)]
+struct Listener(TcpListener);
+
+impl MakeIncoming for Listener {
+    type Incoming = Listener;
+
+    async fn make_incoming(self) -> io::Result<Listener> {
+        Ok(self)
+    }
+}
+
+impl Incoming for Listener {
+    async fn accept(&mut self) -> io::Result<Option<Conn>> {
+        let (stream, peer) = self.0.accept().await?;
+        Ok(Some(Conn {
+            stream: ConnStream::Tcp(stream),
+            info: ConnInfo {
+                peer_addr: Some(Address::from(peer)),
+            },
+        }))
+    }
+}
+
+// 并发发起一批调用，返回每个调用的耗时；调用失败直接 panic，避免把失败的耗时算进结果
+async fn batch(client: &ItemServiceClient, concurrency: usize) -> Vec<Duration> {
+    join_all((0..concurrency).map(|i| {
+        let client = client.clone();
+        async move {
+            let t = Instant::now();
+            client
+                .get_item(GetItemRequest { id: i as i64 })
+                .await
+                .unwrap();
+            t.elapsed()
+        }
+    }))
+    .await
+}
+
+async fn p99_latency(client: &ItemServiceClient, concurrency: usize) -> Duration {
+    for _ in 0..WARM_UP_BATCHES {
+        batch(client, concurrency).await;
+    }
+    let mut latencies = Vec::with_capacity(SAMPLE_BATCHES * concurrency);
+    for _ in 0..SAMPLE_BATCHES {
+        latencies.extend(batch(client, concurrency).await);
+    }
+    latencies.sort();
+    latencies[latencies.len() * 99 / 100]
+}
+
+// 对比开启与关闭连接池时的吞吐，并在每组结束后打印 p99 延迟
+// 关闭连接池即不保留空闲连接，每次调用都新建连接
+fn pooling(c: &mut Criterion) {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let listener = rt.block_on(TcpListener::bind("127.0.0.1:0")).unwrap();
+    let addr = listener.local_addr().unwrap();
+    rt.spawn(async move {
+        ItemServiceServer::new(S::default())
+            .run(Listener(listener))
+            .await
+            .unwrap();
+    });
+
+    for pooled in [true, false] {
+        let name = if pooled { "pooled" } else { "unpooled" };
+        let max_idle = if pooled { 256 } else { 0 };
+        let client = ItemServiceClientBuilder::new("bench")
+            .address(addr)
+            .pool_config(volo_thrift::transport::pool::Config::default().max_idle_per_key(max_idle))
+            .build();
+
+        let mut group = c.benchmark_group(name);
+        for concurrency in [1, 8, 64, 256] {
+            group.throughput(Throughput::Elements(concurrency as u64));
+            group.bench_with_input(
+                BenchmarkId::from_parameter(concurrency),
+                &concurrency,
+                |b, &concurrency| {
+                    b.to_async(&rt).iter_custom(|iters| {
+                        let client = client.clone();
+                        async move {
+                            let start = Instant::now();
+                            for _ in 0..iters {
+                                batch(&client, concurrency).await;
+                            }
+                            start.elapsed()
+                        }
+                    });
+                },
+            );
+            let p99 = rt.block_on(p99_latency(&client, concurrency));
+            eprintln!("{}/{}: p99 latency {:?}", name, concurrency, p99);
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, pooling);
+criterion_main!(benches);
@@ -16,6 +16,34 @@ struct GetItemResponse {
     1: required Item item,
 }
 
+struct GetItemsRequest {
+    1: required list<i64> ids,
+}
+
+// 部分 id 失败时只返回成功的条目，失败原因通过回包 header x-warnings 返回
+struct GetItemsResponse {
+    1: required list<Item> items,
+}
+
+// oneway 调用：客户端写出请求后立即返回，服务端不回包
+struct ItemEvent {
+    1: required i64 id,
+    2: required string event,
+}
+
+enum ServingStatus {
+    UNKNOWN = 0,
+    SERVING = 1,
+    NOT_SERVING = 2,
+}
+
+struct HealthCheckResponse {
+    1: required ServingStatus status,
+}
+
 service ItemService {
     GetItemResponse GetItem (1: GetItemRequest req),
+    GetItemsResponse GetItems (1: GetItemsRequest req),
+    HealthCheckResponse Check (),
+    oneway void FireAndForget (1: ItemEvent event),
 }
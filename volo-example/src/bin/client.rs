@@ -1,11 +1,25 @@
 use lazy_static::lazy_static;
+use metainfo::{MetaInfo, METAINFO};
+use std::cell::RefCell;
 use std::net::SocketAddr;
+use std::time::Duration;
+use volo_example::client::{
+    is_timeout, server_processing_time, ClientIdLayer, DebugWireMakeCodec, LogLayer,
+    ReconnectLayer, RequestTimeoutLayer,
+};
+use volo_thrift::codec::default::DefaultMakeCodec;
 
 lazy_static! {
     static ref CLIENT: volo_gen::volo::example::ItemServiceClient = {
         let addr: SocketAddr = "127.0.0.1:9090".parse().unwrap();
         volo_gen::volo::example::ItemServiceClientBuilder::new("volo-example")
             .address(addr)
+            .layer_outer(LogLayer)
+            .layer_outer(ReconnectLayer::default())
+            // 放在最外层，截止时间覆盖建连、重连重试与收发
+            .layer_outer_front(RequestTimeoutLayer::new(Duration::from_secs(3)))
+            .layer_inner(ClientIdLayer::new(env!("CARGO_PKG_VERSION")))
+            .make_codec(DebugWireMakeCodec::new(DefaultMakeCodec::default()))
             .build()
     };
 }
@@ -14,9 +28,18 @@ lazy_static! {
 async fn main() {
     tracing_subscriber::fmt::init();
     let req = volo_gen::volo::example::GetItemRequest { id: 1024 };
-    let resp = CLIENT.get_item(req).await;
-    match resp {
-        Ok(info) => tracing::info!("{:?}", info),
-        Err(e) => tracing::error!("{:?}", e),
-    }
+    // 在 METAINFO 作用域内调用，便于读取回包 header
+    METAINFO
+        .scope(RefCell::new(MetaInfo::default()), async {
+            let resp = CLIENT.get_item(req).await;
+            match resp {
+                Ok(info) => tracing::info!("{:?}", info),
+                Err(e) if is_timeout(&e) => tracing::error!("get_item timed out: {}", e),
+                Err(e) => tracing::error!("{:?}", e),
+            }
+            if let Some(cost) = server_processing_time() {
+                tracing::info!("server processing time: {:?}", cost);
+            }
+        })
+        .await;
 }
@@ -1,14 +1,42 @@
 use std::net::SocketAddr;
+use volo_example::server::{
+    limited_make_codec, PanicIsolationLayer, ProcessingTimeLayer, ServerEvent, ServerEvents,
+    StreamingMakeCodec,
+};
 use volo_example::S;
 
+// metrics HTTP 端点的监听地址，默认 0.0.0.0:9091
+#[cfg(feature = "metrics")]
+const METRICS_ADDR_ENV: &str = "VOLO_METRICS_ADDR";
+
 #[volo::main]
 async fn main() {
-    let addr: SocketAddr = "0.0.0.0:9090".parse().unwrap();
-    let addr = volo::net::Address::from(addr);
+    tracing_subscriber::fmt::init();
+    let socket_addr: SocketAddr = "0.0.0.0:9090".parse().unwrap();
+    let addr = volo::net::Address::from(socket_addr);
+
+    let events = ServerEvents::default().on_event(|event| tracing::info!("server event: {:?}", event));
+
+    let server = volo_gen::volo::example::ItemServiceServer::new(S::default())
+        .make_codec(events.make_codec(StreamingMakeCodec::new(limited_make_codec())))
+        .register_shutdown_hook(events.shutdown_hook())
+        .layer(ProcessingTimeLayer)
+        .layer(PanicIsolationLayer::default());
 
-    volo_gen::volo::example::ItemServiceServer::new(S)
-        .run(addr)
+    #[cfg(feature = "metrics")]
+    let server = {
+        let metrics = volo_example::server::Metrics::default();
+        let metrics_addr: SocketAddr = std::env::var(METRICS_ADDR_ENV)
+            .unwrap_or_else(|_| "0.0.0.0:9091".to_string())
+            .parse()
+            .expect("invalid metrics bind address");
+        tokio::spawn(metrics.clone().serve(metrics_addr));
+        server.layer_front(metrics.layer())
+    };
+
+    server
+        .run(events.make_incoming(socket_addr, addr))
         .await
         .unwrap();
+    events.emit(ServerEvent::Stopped);
 }
-
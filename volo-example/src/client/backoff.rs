@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use rand::Rng;
+
+// 退避抖动策略
+// None 便于测试中得到确定的等待时间；Full 能最大程度打散大量客户端的重连
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Jitter {
+    None,
+    // 固定一半 + 随机一半
+    Equal,
+    // 在 [0, 退避上限] 内随机
+    #[default]
+    Full,
+}
+
+// 指数退避：第 n 次等待 base * 2^n，不超过 max，再按 jitter 打散
+#[derive(Clone, Copy, Debug)]
+pub struct Backoff {
+    base: Duration,
+    max: Duration,
+    jitter: Jitter,
+}
+
+impl Backoff {
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self {
+            base,
+            max,
+            jitter: Jitter::default(),
+        }
+    }
+
+    // 固定间隔，不随尝试次数增长，也不加抖动
+    pub fn fixed(delay: Duration) -> Self {
+        Self::new(delay, delay).jitter(Jitter::None)
+    }
+
+    pub fn jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt).unwrap_or(u32::MAX);
+        let ceil = self.base.saturating_mul(factor).min(self.max);
+        match self.jitter {
+            Jitter::None => ceil,
+            Jitter::Equal => {
+                let half = ceil / 2;
+                half + random_up_to(ceil - half)
+            }
+            Jitter::Full => random_up_to(ceil),
+        }
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(50), Duration::from_secs(5))
+    }
+}
+
+fn random_up_to(max: Duration) -> Duration {
+    let nanos = max.as_nanos().min(u64::MAX as u128) as u64;
+    Duration::from_nanos(rand::thread_rng().gen_range(0..=nanos))
+}
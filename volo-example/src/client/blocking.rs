@@ -0,0 +1,38 @@
+use std::io;
+
+use tokio::runtime::{Builder, Handle, Runtime};
+use volo_gen::volo::example::{GetItemRequest, GetItemResponse, ItemServiceClient};
+use volo_thrift::ClientError;
+
+use super::client_error;
+
+// 供不在 tokio runtime 中运行的同步程序使用，内部持有一个 current-thread runtime，每次调用阻塞到返回
+//     let client = BlockingItemServiceClient::new(|| ItemServiceClientBuilder::new("cli").address(addr).build())?;
+//     let resp = client.get_item(GetItemRequest { id: 1024 })?;
+// 不能在已有的 async 上下文中调用：在 runtime 内阻塞会占住工作线程，此时直接返回错误
+// 连接的读写任务只在调用期间被驱动，空闲连接由连接池在下次调用时检查
+pub struct BlockingItemServiceClient {
+    client: ItemServiceClient,
+    rt: Runtime,
+}
+
+impl BlockingItemServiceClient {
+    // make_client 在内部 runtime 的上下文中执行，客户端构造时创建的后台任务归属于该 runtime
+    pub fn new(make_client: impl FnOnce() -> ItemServiceClient) -> io::Result<Self> {
+        let rt = Builder::new_current_thread().enable_all().build()?;
+        let client = {
+            let _guard = rt.enter();
+            make_client()
+        };
+        Ok(Self { client, rt })
+    }
+
+    pub fn get_item(&self, req: GetItemRequest) -> Result<GetItemResponse, ClientError> {
+        if Handle::try_current().is_ok() {
+            return Err(client_error(
+                "BlockingItemServiceClient called from within an async runtime; use ItemServiceClient instead",
+            ));
+        }
+        self.rt.block_on(self.client.get_item(req))
+    }
+}
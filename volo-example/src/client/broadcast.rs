@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use faststr::FastStr;
+use futures::future::join_all;
+use volo::context::Endpoint;
+use volo::discovery::Discover;
+use volo::net::Address;
+use volo_thrift::ClientError;
+
+// 将同一个调用并发发往所有已知 endpoint，并分别返回每个 endpoint 的结果
+// 与负载均衡只选一个实例不同，只适合缓存失效这类幂等的管理操作，需要显式构造才能使用
+// 每个 endpoint 单独计算超时，个别 endpoint 失败不影响其它结果
+pub struct Broadcast<D, C, F> {
+    discover: D,
+    endpoint: Endpoint,
+    make_client: F,
+    timeout: Duration,
+    // 按地址缓存客户端，复用连接
+    clients: Mutex<HashMap<SocketAddr, C>>,
+}
+
+impl<D, C, F> Broadcast<D, C, F>
+where
+    D: Discover,
+    C: Clone,
+    F: Fn(SocketAddr) -> C,
+{
+    // make_client 为单个地址构造客户端，例如 ItemServiceClientBuilder::new(..).address(addr).build()
+    pub fn new(service_name: impl Into<FastStr>, discover: D, make_client: F) -> Self {
+        Self {
+            discover,
+            endpoint: Endpoint::new(service_name.into()),
+            make_client,
+            timeout: Duration::from_secs(1),
+            clients: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    // 服务发现失败时返回 Err，与没有任何 endpoint 时的空列表区分开
+    pub async fn broadcast<T, G, Fut>(
+        &self,
+        f: G,
+    ) -> Result<Vec<(SocketAddr, Result<T, ClientError>)>, D::Error>
+    where
+        G: Fn(C) -> Fut,
+        Fut: Future<Output = Result<T, ClientError>>,
+    {
+        let clients = self.clients().await?;
+        Ok(join_all(clients.into_iter().map(|(addr, client)| {
+            let call = tokio::time::timeout(self.timeout, f(client));
+            async move {
+                // 与 RequestTimeoutService 一致，以 TimedOut 的传输错误返回，timeout::is_timeout 能识别
+                let result = match call.await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        let err = io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            format!("broadcast to {} timed out", addr),
+                        );
+                        Err(ClientError::Transport(err.into()))
+                    }
+                };
+                (addr, result)
+            }
+        }))
+        .await)
+    }
+
+    // 当前已知的 endpoint 及对应的客户端，已下线的地址从缓存中移除
+    async fn clients(&self) -> Result<Vec<(SocketAddr, C)>, D::Error> {
+        let instances = self.discover.discover(&self.endpoint).await?;
+        let mut cache = self.clients.lock().unwrap();
+        let addrs: Vec<SocketAddr> = instances
+            .iter()
+            .filter_map(|instance| match &instance.address {
+                Address::Ip(addr) => Some(*addr),
+                #[allow(unreachable_patterns)]
+                _ => None,
+            })
+            .collect();
+        cache.retain(|addr, _| addrs.contains(addr));
+        Ok(addrs
+            .into_iter()
+            .map(|addr| {
+                let client = cache
+                    .entry(addr)
+                    .or_insert_with(|| (self.make_client)(addr))
+                    .clone();
+                (addr, client)
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,88 @@
+use std::cell::RefCell;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use volo::context::Context;
+use volo::net::Address;
+use volo_gen::volo::example::{GetItemRequest, GetItemResponse, ItemServiceClient};
+use volo_thrift::ClientError;
+
+// 一次调用的执行信息
+#[derive(Clone, Debug, Default)]
+pub struct CallInfo {
+    // 最后一次尝试实际访问的 endpoint
+    pub endpoint: Option<Address>,
+    // 经过重试/重连后的总尝试次数
+    pub attempts: u32,
+    pub latency: Duration,
+}
+
+tokio::task_local! {
+    static CALL_INFO: RefCell<CallInfo>;
+}
+
+// 执行 fut 并收集其中客户端调用的 CallInfo
+// 需要在客户端上通过 layer_inner 安装 CallInfoLayer，负载均衡选出 endpoint 后才能记录
+pub async fn with_call_info<T>(fut: impl Future<Output = T>) -> (T, CallInfo) {
+    let start = Instant::now();
+    CALL_INFO
+        .scope(RefCell::new(CallInfo::default()), async move {
+            let out = fut.await;
+            let mut info = CALL_INFO.with(|info| info.take());
+            info.latency = start.elapsed();
+            (out, info)
+        })
+        .await
+}
+
+pub trait GetItemWithInfo {
+    fn get_item_with_info(
+        &self,
+        req: GetItemRequest,
+    ) -> impl Future<Output = Result<(GetItemResponse, CallInfo), ClientError>> + Send;
+}
+
+impl GetItemWithInfo for ItemServiceClient {
+    async fn get_item_with_info(
+        &self,
+        req: GetItemRequest,
+    ) -> Result<(GetItemResponse, CallInfo), ClientError> {
+        let (resp, info) = with_call_info(self.get_item(req)).await;
+        resp.map(|resp| (resp, info))
+    }
+}
+
+// 每次尝试都会经过这一层，记录 endpoint 与尝试次数
+#[derive(Clone, Copy, Default)]
+pub struct CallInfoLayer;
+
+impl<S> volo::Layer<S> for CallInfoLayer {
+    type Service = CallInfoService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        CallInfoService { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct CallInfoService<S> {
+    inner: S,
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for CallInfoService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req> + Send + Sync + 'static,
+    Cx: Context + Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        let endpoint = cx.rpc_info().callee().address();
+        let _ = CALL_INFO.try_with(|info| {
+            let mut info = info.borrow_mut();
+            info.attempts += 1;
+            info.endpoint = endpoint;
+        });
+        self.inner.call(cx, req).await
+    }
+}
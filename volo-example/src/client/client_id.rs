@@ -0,0 +1,69 @@
+use faststr::FastStr;
+use metainfo::{Forward, METAINFO};
+use volo::context::Context;
+
+pub const CLIENT_ID_HEADER: &str = "x-client-id";
+pub const CLIENT_VERSION_HEADER: &str = "x-client-version";
+
+// 在每次调用中带上客户端身份，服务端据此按客户端版本区分流量，灰度发布时很有用
+// client id 默认取 ClientBuilder::new 传入的服务名；调用方在 METAINFO 中已设置时不覆盖
+// 只描述直接调用方，因此放在 transient header 中，不继续向下游透传
+#[derive(Clone)]
+pub struct ClientIdLayer {
+    client_id: Option<FastStr>,
+    version: FastStr,
+}
+
+impl ClientIdLayer {
+    pub fn new(version: impl Into<FastStr>) -> Self {
+        Self {
+            client_id: None,
+            version: version.into(),
+        }
+    }
+
+    // 使用与服务名不同的 client id
+    pub fn client_id(mut self, client_id: impl Into<FastStr>) -> Self {
+        self.client_id = Some(client_id.into());
+        self
+    }
+}
+
+impl<S> volo::Layer<S> for ClientIdLayer {
+    type Service = ClientIdService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        ClientIdService { inner, layer: self }
+    }
+}
+
+#[derive(Clone)]
+pub struct ClientIdService<S> {
+    inner: S,
+    layer: ClientIdLayer,
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for ClientIdService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req> + Send + Sync + 'static,
+    Cx: Context + Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        let client_id = match &self.layer.client_id {
+            Some(id) => id.clone(),
+            None => cx.rpc_info().caller().service_name(),
+        };
+        let _ = METAINFO.try_with(|mi| {
+            let mut mi = mi.borrow_mut();
+            if mi.get_transient(CLIENT_ID_HEADER).is_none() {
+                mi.set_transient(CLIENT_ID_HEADER, client_id);
+            }
+            if mi.get_transient(CLIENT_VERSION_HEADER).is_none() {
+                mi.set_transient(CLIENT_VERSION_HEADER, self.layer.version.clone());
+            }
+        });
+        self.inner.call(cx, req).await
+    }
+}
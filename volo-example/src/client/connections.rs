@@ -0,0 +1,37 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// 对同一个 endpoint 维护 n 个相互独立的客户端，轮询分发请求
+// 每个客户端有各自的连接池，它们是在连接池之外额外叠加的并行度；
+// 子客户端开启 multiplex 后各自只占一条连接，总连接数即为 n
+// 代价是每个客户端的全部资源都乘以 n：连接池及其空闲连接、服务发现与负载均衡状态、
+// 以及客户端上挂载的各个 layer（限流、熔断等的计数按子客户端分别统计，不是整体的上限）；
+// 只为增加连接数时 n 应保持很小，需要整体限流时把限流放在 ConnectionSet 之外
+pub struct ConnectionSet<C> {
+    clients: Vec<C>,
+    next: AtomicUsize,
+}
+
+impl<C> ConnectionSet<C> {
+    pub fn new(connections_per_endpoint: usize, make_client: impl FnMut() -> C) -> Self {
+        let clients: Vec<C> = std::iter::repeat_with(make_client)
+            .take(connections_per_endpoint.max(1))
+            .collect();
+        Self {
+            clients,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn pick(&self) -> &C {
+        let i = self.next.fetch_add(1, Ordering::Relaxed) % self.clients.len();
+        &self.clients[i]
+    }
+
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+}
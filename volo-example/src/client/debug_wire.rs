@@ -0,0 +1,128 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use thrift_sniffer::decode;
+use tokio::io::{AsyncRead, AsyncWrite, Interest, ReadBuf, Ready};
+use volo::net::ready::AsyncReady;
+use volo_thrift::codec::MakeCodec;
+
+// 设置该环境变量后，客户端收发的每一帧都会解码打印到 stderr
+pub const DEBUG_WIRE_ENV: &str = "VOLO_DEBUG_WIRE";
+
+// 包装底层 codec，在读写两端旁路一份字节流用于调试输出
+// 未开启时读写直接透传，只多一次布尔判断
+#[derive(Clone)]
+pub struct DebugWireMakeCodec<M> {
+    inner: M,
+    enabled: bool,
+}
+
+impl<M> DebugWireMakeCodec<M> {
+    pub fn new(inner: M) -> Self {
+        Self {
+            inner,
+            enabled: std::env::var_os(DEBUG_WIRE_ENV).is_some(),
+        }
+    }
+}
+
+impl<R, W, M> MakeCodec<R, W> for DebugWireMakeCodec<M>
+where
+    R: AsyncRead + Send + Sync + Unpin + 'static,
+    W: AsyncWrite + Send + Sync + Unpin + 'static,
+    M: MakeCodec<WireTap<R>, WireTap<W>>,
+{
+    type Encoder = M::Encoder;
+    type Decoder = M::Decoder;
+
+    fn make_codec(&self, reader: R, writer: W) -> (Self::Encoder, Self::Decoder) {
+        self.inner.make_codec(
+            WireTap::new(reader, "response", self.enabled),
+            WireTap::new(writer, "request", self.enabled),
+        )
+    }
+}
+
+pub struct WireTap<T> {
+    inner: T,
+    direction: &'static str,
+    enabled: bool,
+    buf: Vec<u8>,
+}
+
+impl<T> WireTap<T> {
+    fn new(inner: T, direction: &'static str, enabled: bool) -> Self {
+        Self {
+            inner,
+            direction,
+            enabled,
+            buf: Vec::new(),
+        }
+    }
+
+    // 按 framed 传输的 4 字节长度前缀切分出完整帧后再解码
+    fn observe(&mut self, bytes: &[u8]) {
+        if !self.enabled {
+            return;
+        }
+        self.buf.extend_from_slice(bytes);
+        while self.buf.len() >= 4 {
+            let frame_len = u32::from_be_bytes(self.buf[..4].try_into().unwrap()) as usize;
+            if self.buf.len() < 4 + frame_len {
+                break;
+            }
+            let frame: Vec<u8> = self.buf.drain(..4 + frame_len).collect();
+            let decoded = decode::strip_theader(&frame).and_then(decode::decode_message);
+            match decoded {
+                Ok(msg) => eprintln!("[wire {}] {} bytes: {}", self.direction, frame.len(), msg),
+                Err(e) => eprintln!("[wire {}] {} bytes: {}", self.direction, frame.len(), e),
+            }
+        }
+    }
+}
+
+// 默认 codec 通过写端的就绪状态判断连接是否已关闭
+impl<T: AsyncReady + Sync> AsyncReady for WireTap<T> {
+    async fn ready(&self, interest: Interest) -> io::Result<Ready> {
+        self.inner.ready(interest).await
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for WireTap<T> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let before = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = poll {
+            let read = buf.filled()[before..].to_vec();
+            self.observe(&read);
+        }
+        poll
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for WireTap<T> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            self.observe(&buf[..n]);
+        }
+        poll
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
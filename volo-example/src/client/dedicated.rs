@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use volo::layer::Stack;
+use volo_thrift::client::ClientBuilder;
+
+// 独占连接模式：同一客户端实例上的所有调用固定在一条连接上，严格按发起顺序逐个发送
+//     let client = ItemServiceClientBuilder::new("stateful")
+//         .address(addr)
+//         .dedicated_connection()
+//         .build();
+// 以 multiplex 方式建连，每个地址只持有一条共享连接，连接忙时不会另外拨号；
+// 调用在 DedicatedConnectionLayer 中排队，上一个调用返回后才发出下一个，连接上最多一个在途请求
+// 只能配置单个地址，负载均衡会把调用分散到不同连接上；连接断开后下一次调用重新建连，
+// 依赖连接状态的协议需要自行处理断开时返回的错误
+// 以吞吐换顺序保证，不要期待高 QPS
+pub trait DedicatedConnection {
+    type Builder;
+
+    fn dedicated_connection(self) -> Self::Builder;
+}
+
+impl<IL, OL, MkClient, Req, Resp, MkT, MkC, LB> DedicatedConnection
+    for ClientBuilder<IL, OL, MkClient, Req, Resp, MkT, MkC, LB>
+{
+    type Builder =
+        ClientBuilder<IL, Stack<DedicatedConnectionLayer, OL>, MkClient, Req, Resp, MkT, MkC, LB>;
+
+    fn dedicated_connection(self) -> Self::Builder {
+        self.multiplex(true).layer_outer(DedicatedConnectionLayer)
+    }
+}
+
+// 把同一客户端实例上的调用串行化，由 dedicated_connection 安装
+#[derive(Clone, Default)]
+pub struct DedicatedConnectionLayer;
+
+impl<S> volo::Layer<S> for DedicatedConnectionLayer {
+    type Service = DedicatedConnectionService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        DedicatedConnectionService {
+            inner,
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DedicatedConnectionService<S> {
+    inner: S,
+    lock: Arc<Mutex<()>>,
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for DedicatedConnectionService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req> + Send + Sync + 'static,
+    Cx: Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        // tokio 的 Mutex 按 FIFO 唤醒等待者，保证调用顺序与发起顺序一致
+        let _guard = self.lock.lock().await;
+        self.inner.call(cx, req).await
+    }
+}
@@ -0,0 +1,43 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use volo_gen::volo::example::{GetItemRequest, GetItemResponse, ItemServiceClient};
+use volo_thrift::ClientError;
+
+// 调用最终失败时用 fallback 生成降级响应，而不是把错误返回给调用方
+// 包在整个客户端外面，只有重试、重连与负载均衡都失败后才会触发
+#[derive(Clone)]
+pub struct FallbackClient<F> {
+    client: ItemServiceClient,
+    fallback: Arc<F>,
+    fallbacks: Arc<AtomicU64>,
+}
+
+impl<F> FallbackClient<F>
+where
+    F: Fn(&GetItemRequest, &ClientError) -> GetItemResponse + Send + Sync + 'static,
+{
+    pub fn new(client: ItemServiceClient, fallback: F) -> Self {
+        Self {
+            client,
+            fallback: Arc::new(fallback),
+            fallbacks: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub async fn get_item(&self, req: GetItemRequest) -> GetItemResponse {
+        match self.client.get_item(req.clone()).await {
+            Ok(resp) => resp,
+            Err(e) => {
+                self.fallbacks.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!("get_item failed, using fallback response: {}", e);
+                (self.fallback)(&req, &e)
+            }
+        }
+    }
+
+    // 累计使用 fallback 的次数，用于上报
+    pub fn fallback_count(&self) -> u64 {
+        self.fallbacks.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,181 @@
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::Semaphore;
+use volo_thrift::ClientError;
+
+// 等待名额超时或 fail_fast 时返回的错误，作为 ClientError::Transport 的 io 错误携带
+// 请求没有发出，不会与服务端返回的异常混淆；用 is_pool_exhausted 识别
+#[derive(Debug)]
+pub struct PoolExhausted {
+    pub max: usize,
+}
+
+impl fmt::Display for PoolExhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pool exhausted: too many in-flight requests (max {})",
+            self.max
+        )
+    }
+}
+
+impl std::error::Error for PoolExhausted {}
+
+// 客户端实例级别的在途请求上限，为上层应用提供端到端背压
+// 后端整体变慢时避免请求无限堆积占用内存
+// ping-pong 模式下每个在途请求占用一条连接，这个上限也就是连接池的大小
+#[derive(Clone)]
+pub struct MaxInflightLayer {
+    gauge: InflightGauge,
+    fail_fast: bool,
+    acquire_timeout: Option<Duration>,
+}
+
+impl MaxInflightLayer {
+    // 上限为 0 时任何调用都拿不到名额，直接拒绝
+    pub fn new(max_inflight: usize) -> Self {
+        assert!(max_inflight > 0, "max_inflight must be positive");
+        Self {
+            gauge: InflightGauge {
+                semaphore: Arc::new(Semaphore::new(max_inflight)),
+                max: max_inflight,
+                waits: Default::default(),
+            },
+            fail_fast: false,
+            acquire_timeout: None,
+        }
+    }
+
+    // 达到上限时直接失败，而不是等待空闲名额
+    pub fn fail_fast(mut self, fail_fast: bool) -> Self {
+        self.fail_fast = fail_fast;
+        self
+    }
+
+    // 等待名额最多 timeout，超时后返回 pool exhausted 错误
+    // 用于区分服务端变慢与客户端自身的连接争用
+    pub fn acquire_timeout(mut self, timeout: Duration) -> Self {
+        self.acquire_timeout = Some(timeout);
+        self
+    }
+
+    // 用于上报当前在途请求数与等待名额的耗时
+    pub fn gauge(&self) -> InflightGauge {
+        self.gauge.clone()
+    }
+}
+
+#[derive(Clone)]
+pub struct InflightGauge {
+    semaphore: Arc<Semaphore>,
+    max: usize,
+    waits: Arc<AcquireWaits>,
+}
+
+#[derive(Default)]
+struct AcquireWaits {
+    count: AtomicU64,
+    total_us: AtomicU64,
+    max_us: AtomicU64,
+}
+
+impl InflightGauge {
+    pub fn current(&self) -> usize {
+        self.max - self.semaphore.available_permits()
+    }
+
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    // 累计获取名额的次数（含超时失败的）
+    pub fn acquire_count(&self) -> u64 {
+        self.waits.count.load(Ordering::Relaxed)
+    }
+
+    // 累计等待名额的总耗时，与 acquire_count 相除得到平均值
+    pub fn acquire_wait_total(&self) -> Duration {
+        Duration::from_micros(self.waits.total_us.load(Ordering::Relaxed))
+    }
+
+    pub fn acquire_wait_max(&self) -> Duration {
+        Duration::from_micros(self.waits.max_us.load(Ordering::Relaxed))
+    }
+
+    fn record_wait(&self, wait: Duration) {
+        let us = wait.as_micros() as u64;
+        self.waits.count.fetch_add(1, Ordering::Relaxed);
+        self.waits.total_us.fetch_add(us, Ordering::Relaxed);
+        self.waits.max_us.fetch_max(us, Ordering::Relaxed);
+    }
+}
+
+// 调用是否因等不到连接名额而失败
+pub fn is_pool_exhausted(err: &ClientError) -> bool {
+    match err {
+        ClientError::Transport(e) => e
+            .io_error()
+            .get_ref()
+            .is_some_and(|e| e.is::<PoolExhausted>()),
+        _ => false,
+    }
+}
+
+impl<S> volo::Layer<S> for MaxInflightLayer {
+    type Service = MaxInflightService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        MaxInflightService {
+            inner,
+            gauge: self.gauge,
+            fail_fast: self.fail_fast,
+            acquire_timeout: self.acquire_timeout,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MaxInflightService<S> {
+    inner: S,
+    gauge: InflightGauge,
+    fail_fast: bool,
+    acquire_timeout: Option<Duration>,
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for MaxInflightService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req, Error = ClientError> + Send + Sync + 'static,
+    Cx: Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        let semaphore = &self.gauge.semaphore;
+        let exhausted = || {
+            let err = io::Error::other(PoolExhausted {
+                max: self.gauge.max,
+            });
+            ClientError::Transport(err.into())
+        };
+        let start = Instant::now();
+        // semaphore 不会被关闭
+        let permit = if self.fail_fast {
+            semaphore.try_acquire().map_err(|_| exhausted())
+        } else if let Some(timeout) = self.acquire_timeout {
+            tokio::time::timeout(timeout, semaphore.acquire())
+                .await
+                .map(Result::unwrap)
+                .map_err(|_| exhausted())
+        } else {
+            Ok(semaphore.acquire().await.unwrap())
+        };
+        self.gauge.record_wait(start.elapsed());
+        let _permit = permit?;
+        self.inner.call(cx, req).await
+    }
+}
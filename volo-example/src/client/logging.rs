@@ -0,0 +1,41 @@
+use std::time::Instant;
+
+use volo::context::Context;
+
+// 记录每次调用的方法名、耗时与结果，演示如何通过 layer 注入横切逻辑而不修改生成代码
+// 作为 outer layer 时记录的是包含重试在内的整体耗时，作为 inner layer 时记录的是单次尝试
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LogLayer;
+
+impl<S> volo::Layer<S> for LogLayer {
+    type Service = LogService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        LogService { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct LogService<S> {
+    inner: S,
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for LogService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req> + Send + Sync + 'static,
+    S::Error: std::fmt::Display,
+    Cx: Context + Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        let method = cx.rpc_info().method().clone();
+        let start = Instant::now();
+        let resp = self.inner.call(cx, req).await;
+        match &resp {
+            Ok(_) => tracing::info!(%method, latency = ?start.elapsed(), "call succeeded"),
+            Err(e) => tracing::warn!(%method, latency = ?start.elapsed(), error = %e, "call failed"),
+        }
+        resp
+    }
+}
@@ -0,0 +1,79 @@
+use std::future::Future;
+
+use faststr::FastStr;
+use metainfo::{Backward, Forward, METAINFO};
+
+tokio::task_local! {
+    static METADATA: Vec<(FastStr, FastStr)>;
+}
+
+// 在 fut 中发起的调用都带上这些请求 header，例如 trace id
+// 需要在客户端上安装 MetadataLayer；服务端通过 volo_example::server::metadata 读取
+pub async fn with_metadata<T, K, V>(
+    headers: impl IntoIterator<Item = (K, V)>,
+    fut: impl Future<Output = T>,
+) -> T
+where
+    K: Into<FastStr>,
+    V: Into<FastStr>,
+{
+    let headers = headers
+        .into_iter()
+        .map(|(k, v)| (k.into(), v.into()))
+        .collect();
+    METADATA.scope(headers, fut).await
+}
+
+// 为当前调用设置一个请求 header，覆盖已有的同名值；只能在 client layer 中调用
+pub fn set_metadata(key: impl Into<FastStr>, value: impl Into<FastStr>) {
+    let _ = METAINFO.try_with(|mi| mi.borrow_mut().set_transient(key, value));
+}
+
+// 把 with_metadata 设置的 header 写入 THeader，调用方在 METAINFO 中已设置的同名 header 不覆盖
+// 只发给直接调用的服务端，因此放在 transient header 中
+#[derive(Clone, Copy, Default)]
+pub struct MetadataLayer;
+
+impl<S> volo::Layer<S> for MetadataLayer {
+    type Service = MetadataService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        MetadataService { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct MetadataService<S> {
+    inner: S,
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for MetadataService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req> + Send + Sync + 'static,
+    Cx: Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        let _ = METADATA.try_with(|headers| {
+            let _ = METAINFO.try_with(|mi| {
+                let mut mi = mi.borrow_mut();
+                for (key, value) in headers {
+                    if mi.get_transient(key).is_none() {
+                        mi.set_transient(key.clone(), value.clone());
+                    }
+                }
+            });
+        });
+        self.inner.call(cx, req).await
+    }
+}
+
+// 读取最近一次调用回包中服务端通过 set_response_metadata 设置的 header
+// 与 server_processing_time 一样需要在 METAINFO.scope 内发起调用
+pub fn response_metadata(key: &str) -> Option<FastStr> {
+    METAINFO
+        .try_with(|mi| mi.borrow().get_backward_downstream(key))
+        .ok()
+        .flatten()
+}
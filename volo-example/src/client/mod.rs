@@ -0,0 +1,70 @@
+mod backoff;
+mod blocking;
+mod broadcast;
+mod call_info;
+mod client_id;
+mod connections;
+mod debug_wire;
+mod dedicated;
+mod fallback;
+mod inflight;
+mod logging;
+mod metadata;
+mod negotiate;
+#[cfg(feature = "otel")]
+mod otel;
+mod pool;
+mod priority;
+mod reconnect;
+mod request_id;
+mod resolve;
+mod response_size;
+mod retry;
+mod timeout;
+mod timing;
+mod tls;
+mod warnings;
+mod write_batch;
+mod zone;
+
+pub use backoff::{Backoff, Jitter};
+pub use blocking::BlockingItemServiceClient;
+pub use broadcast::Broadcast;
+pub use call_info::{with_call_info, CallInfo, CallInfoLayer, CallInfoService, GetItemWithInfo};
+pub use client_id::{ClientIdLayer, ClientIdService, CLIENT_ID_HEADER, CLIENT_VERSION_HEADER};
+pub use connections::ConnectionSet;
+pub use debug_wire::{DebugWireMakeCodec, WireTap, DEBUG_WIRE_ENV};
+pub use dedicated::{DedicatedConnection, DedicatedConnectionLayer, DedicatedConnectionService};
+pub use fallback::FallbackClient;
+pub use inflight::{
+    is_pool_exhausted, InflightGauge, MaxInflightLayer, MaxInflightService, PoolExhausted,
+};
+pub use logging::{LogLayer, LogService};
+pub use metadata::{response_metadata, set_metadata, with_metadata, MetadataLayer, MetadataService};
+pub use negotiate::ProtocolNegotiation;
+#[cfg(feature = "otel")]
+pub use otel::{OtelClientLayer, OtelClientService};
+pub use pool::Pool;
+pub use priority::{with_call_opts, CallOpts, CallOptsLayer, CallOptsService};
+pub use reconnect::{ReconnectLayer, ReconnectService};
+pub use request_id::{IdFormat, RequestIdLayer, RequestIdService, DEFAULT_REQUEST_ID_HEADER};
+pub use resolve::{Resolve, Resolved, ResolverDiscover, SystemResolver};
+pub use response_size::{WarnResponseSizeDecoder, WarnResponseSizeMakeCodec};
+pub use retry::{retry_attempts, RetriesExhausted, RetryLayer, RetryService};
+pub use timeout::{is_timeout, RequestTimeoutLayer, RequestTimeoutService};
+pub use timing::server_processing_time;
+pub use tls::{tls_client_config, TlsMakeTransport, TlsReadHalf, TlsWriteHalf};
+pub use warnings::response_warnings;
+pub use write_batch::{BatchReader, BatchWriter, WriteBatchMakeCodec};
+pub use zone::{ZoneAwareLoadBalance, ZONE_TAG};
+
+use pilota::thrift::{ApplicationException, ApplicationExceptionKind};
+use volo_thrift::ClientError;
+
+// 客户端本地产生的错误，以 ApplicationException 的形式返回给调用方
+pub(crate) fn client_error(msg: impl Into<String>) -> ClientError {
+    ClientError::Application(ApplicationException::new(
+        ApplicationExceptionKind::UNKNOWN,
+        msg.into(),
+    ))
+}
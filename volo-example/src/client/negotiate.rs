@@ -0,0 +1,95 @@
+use std::fmt::Debug;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use pilota::thrift::{ApplicationExceptionKind, ProtocolExceptionKind};
+use volo_thrift::ClientError;
+
+// 按偏好顺序为同一个 endpoint 准备多种协议的客户端，例如 [Compact, Binary]
+// 调用被对端明确拒绝当前协议时换下一个协议，之后的调用都使用它；连接重置等瞬时错误不改变选择
+// 已确定的协议之后又被拒绝（如服务端升级或回滚）时重新协商，最后一个协议也被拒绝时回到第一个
+// 每个实例只对应一个 endpoint，多个 endpoint 各自创建一个
+pub struct ProtocolNegotiation<P, C> {
+    candidates: Vec<(P, C)>,
+    // 当前使用的协议下标
+    current: AtomicUsize,
+    // 当前协议是否已有调用成功
+    confirmed: AtomicBool,
+}
+
+impl<P: Debug, C> ProtocolNegotiation<P, C> {
+    pub fn new(preference: impl IntoIterator<Item = P>, mut make_client: impl FnMut(&P) -> C) -> Self {
+        let candidates: Vec<(P, C)> = preference
+            .into_iter()
+            .map(|p| {
+                let client = make_client(&p);
+                (p, client)
+            })
+            .collect();
+        assert!(!candidates.is_empty(), "protocol preference list is empty");
+        Self {
+            candidates,
+            current: AtomicUsize::new(0),
+            confirmed: AtomicBool::new(false),
+        }
+    }
+
+    // 已确定的协议
+    pub fn protocol(&self) -> Option<&P> {
+        self.confirmed
+            .load(Ordering::Acquire)
+            .then(|| &self.candidates[self.current.load(Ordering::Acquire)].0)
+    }
+
+    // idempotent 为 true 时，被拒绝的调用立即用下一个协议重发；
+    // 否则本次调用返回错误，只有之后的调用改用下一个协议
+    pub async fn call<T, F, Fut>(&self, idempotent: bool, f: F) -> Result<T, ClientError>
+    where
+        F: Fn(&C) -> Fut,
+        Fut: Future<Output = Result<T, ClientError>>,
+    {
+        let mut attempts = 0;
+        loop {
+            let i = self.current.load(Ordering::Acquire);
+            let (protocol, client) = &self.candidates[i];
+            match f(client).await {
+                Ok(resp) => {
+                    self.confirmed.store(true, Ordering::Release);
+                    return Ok(resp);
+                }
+                Err(e) if is_protocol_mismatch(&e) => {
+                    // 并发调用同时被拒绝时只切换一次
+                    let next = (i + 1) % self.candidates.len();
+                    if self
+                        .current
+                        .compare_exchange(i, next, Ordering::AcqRel, Ordering::Acquire)
+                        .is_ok()
+                    {
+                        self.confirmed.store(false, Ordering::Release);
+                        tracing::info!(
+                            "protocol {:?} rejected: {}, switching to {:?}",
+                            protocol,
+                            e,
+                            self.candidates[next].0
+                        );
+                    }
+                    attempts += 1;
+                    if !idempotent || attempts >= self.candidates.len() {
+                        return Err(e);
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+// 只有对端明确拒绝当前协议才算不匹配：服务端无法解码请求时回 PROTOCOL_ERROR 异常，
+// 或回包的版本字节不属于当前协议；连接重置、EOF 等也可能是瞬时故障，不作为依据
+fn is_protocol_mismatch(err: &ClientError) -> bool {
+    match err {
+        ClientError::Application(e) => e.kind() == ApplicationExceptionKind::PROTOCOL_ERROR,
+        ClientError::Protocol(e) => e.kind() == ProtocolExceptionKind::BadVersion,
+        _ => false,
+    }
+}
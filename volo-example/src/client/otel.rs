@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use opentelemetry::global::{self, BoxedTracer};
+use opentelemetry::propagation::TextMapPropagator;
+use opentelemetry::trace::{FutureExt, SpanKind, Status, TraceContextExt, Tracer};
+use opentelemetry::{Context as TraceContext, KeyValue};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use volo::context::Context;
+use volo_thrift::ClientError;
+
+use super::metadata::set_metadata;
+
+// 为每次调用创建一个 client span，父 span 为调用方当前的 trace context，
+// 并把 span 的 W3C trace context（traceparent）写入请求 header，服务端的 OtelServerLayer 以它作为父 span
+// 以 layer_outer_front 安装时 span 覆盖重试与重连在内的整个调用
+#[derive(Clone)]
+pub struct OtelClientLayer {
+    tracer: Arc<BoxedTracer>,
+}
+
+impl Default for OtelClientLayer {
+    fn default() -> Self {
+        Self {
+            tracer: Arc::new(global::tracer("volo-example")),
+        }
+    }
+}
+
+impl<S> volo::Layer<S> for OtelClientLayer {
+    type Service = OtelClientService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        OtelClientService {
+            inner,
+            tracer: self.tracer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct OtelClientService<S> {
+    inner: S,
+    tracer: Arc<BoxedTracer>,
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for OtelClientService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req, Error = ClientError> + Send + Sync + 'static,
+    Cx: Context + Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        let parent = TraceContext::current();
+        let method = cx.rpc_info().method().to_string();
+        let span = self
+            .tracer
+            .span_builder(method.clone())
+            .with_kind(SpanKind::Client)
+            .with_attributes([
+                KeyValue::new("rpc.system", "thrift"),
+                KeyValue::new("rpc.method", method),
+            ])
+            .start_with_context(self.tracer.as_ref(), &parent);
+        let trace_cx = parent.with_span(span);
+
+        let mut carrier = HashMap::new();
+        TraceContextPropagator::new().inject_context(&trace_cx, &mut carrier);
+        for (key, value) in carrier {
+            set_metadata(key, value);
+        }
+
+        let result = self.inner.call(cx, req).with_context(trace_cx.clone()).await;
+        let span = trace_cx.span();
+        match &result {
+            Ok(_) => span.set_status(Status::Ok),
+            Err(e) => span.set_status(Status::error(e.to_string())),
+        }
+        span.end();
+        result
+    }
+}
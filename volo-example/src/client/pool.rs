@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use volo_thrift::transport::pool::Config;
+
+use super::inflight::MaxInflightLayer;
+
+// 连接池配置：volo-thrift 的连接池负责复用空闲连接，取出时跳过已被对端关闭的连接，调用出错的连接不会放回池中
+// 连接池本身不限制连接总数，ping-pong 模式下每个在途请求占用一条连接，由 MaxInflightLayer 把在途调用数限制在 max_inflight
+// max_inflight 是整个客户端所有 endpoint 共享的上限，不是每个 endpoint 的连接数上限：
+// 只有一个 endpoint 时等价于连接数上限，多个 endpoint 时单个 endpoint 可能用满全部额度
+//
+//     let pool = Pool::new(16, 64, Duration::from_secs(30));
+//     ItemServiceClientBuilder::new("item")
+//         .pool_config(pool.config())
+//         .layer_outer(pool.layer())
+#[derive(Clone, Copy, Debug)]
+pub struct Pool {
+    // 每个 endpoint 最多保留的空闲连接数
+    pub max_idle: usize,
+    // 整个客户端同时在途的调用上限，超出的调用排队等待
+    pub max_inflight: usize,
+    // 空闲超过这个时间的连接被关闭
+    pub idle_timeout: Duration,
+}
+
+impl Pool {
+    // max_inflight 为 0 时任何调用都无法发出，直接拒绝
+    pub fn new(max_idle: usize, max_inflight: usize, idle_timeout: Duration) -> Self {
+        assert!(max_inflight > 0, "max_inflight must be positive");
+        Self {
+            max_idle: max_idle.min(max_inflight),
+            max_inflight,
+            idle_timeout,
+        }
+    }
+
+    pub fn config(&self) -> Config {
+        Config::new(self.max_idle, self.idle_timeout)
+    }
+
+    pub fn layer(&self) -> MaxInflightLayer {
+        MaxInflightLayer::new(self.max_inflight)
+    }
+}
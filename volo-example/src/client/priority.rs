@@ -0,0 +1,60 @@
+use std::future::Future;
+
+use metainfo::{Forward, METAINFO};
+
+use crate::priority::{Priority, PRIORITY_HEADER};
+
+// 单次调用的选项
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CallOpts {
+    // 服务端并发已满时的排队优先级，默认 Normal
+    pub priority: Priority,
+}
+
+tokio::task_local! {
+    static CALL_OPTS: CallOpts;
+}
+
+// 在 fut 中发起的调用都带上 opts
+// 需要在客户端上安装 CallOptsLayer，服务端安装 PrioritySchedulerLayer 后才会按优先级排队
+pub async fn with_call_opts<T>(opts: CallOpts, fut: impl Future<Output = T>) -> T {
+    CALL_OPTS.scope(opts, fut).await
+}
+
+// 把 CallOpts 写入请求 header；调用方在 METAINFO 中已设置时不覆盖
+// 只对直接调用的服务端生效，因此放在 transient header 中
+#[derive(Clone, Copy, Default)]
+pub struct CallOptsLayer;
+
+impl<S> volo::Layer<S> for CallOptsLayer {
+    type Service = CallOptsService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        CallOptsService { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct CallOptsService<S> {
+    inner: S,
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for CallOptsService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req> + Send + Sync + 'static,
+    Cx: Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        if let Ok(opts) = CALL_OPTS.try_with(|opts| *opts) {
+            let _ = METAINFO.try_with(|mi| {
+                let mut mi = mi.borrow_mut();
+                if mi.get_transient(PRIORITY_HEADER).is_none() {
+                    mi.set_transient(PRIORITY_HEADER, opts.priority.as_str());
+                }
+            });
+        }
+        self.inner.call(cx, req).await
+    }
+}
@@ -0,0 +1,112 @@
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+use volo_thrift::ClientError;
+
+use super::backoff::Backoff;
+
+// 建连失败后、重新发起调用前执行的回调
+type OnReconnect = Arc<dyn Fn() + Send + Sync>;
+
+// 建连失败时按退避策略重新发起调用
+// 服务端重启后大量客户端同时重连，抖动用来把重连时间打散
+#[derive(Clone)]
+pub struct ReconnectLayer {
+    backoff: Backoff,
+    max_attempts: u32,
+    on_reconnect: Option<OnReconnect>,
+}
+
+impl ReconnectLayer {
+    pub fn new(backoff: Backoff, max_attempts: u32) -> Self {
+        Self {
+            backoff,
+            max_attempts: max_attempts.max(1),
+            on_reconnect: None,
+        }
+    }
+
+    // 每次重连前调用，例如按域名连接时重新解析：
+    // .on_reconnect(move || discover.refresh())
+    pub fn on_reconnect(mut self, f: impl Fn() + Send + Sync + 'static) -> Self {
+        self.on_reconnect = Some(Arc::new(f));
+        self
+    }
+}
+
+impl fmt::Debug for ReconnectLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReconnectLayer")
+            .field("backoff", &self.backoff)
+            .field("max_attempts", &self.max_attempts)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for ReconnectLayer {
+    fn default() -> Self {
+        Self::new(Backoff::default(), 3)
+    }
+}
+
+impl<S> volo::Layer<S> for ReconnectLayer {
+    type Service = ReconnectService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        ReconnectService {
+            inner,
+            backoff: self.backoff,
+            max_attempts: self.max_attempts,
+            on_reconnect: self.on_reconnect,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ReconnectService<S> {
+    inner: S,
+    backoff: Backoff,
+    max_attempts: u32,
+    on_reconnect: Option<OnReconnect>,
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for ReconnectService<S>
+where
+    Req: Clone + Send + 'static,
+    S: volo::Service<Cx, Req, Error = ClientError> + Send + Sync + 'static,
+    S::Response: Send,
+    Cx: Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        let mut attempt = 0;
+        loop {
+            match self.inner.call(cx, req.clone()).await {
+                Err(e) if is_connect_error(&e) && attempt + 1 < self.max_attempts => {
+                    let delay = self.backoff.delay(attempt);
+                    tracing::debug!("connect failed: {}, reconnecting in {:?}", e, delay);
+                    if let Some(f) = &self.on_reconnect {
+                        f();
+                    }
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                resp => return resp,
+            }
+        }
+    }
+}
+
+// 只有建连阶段的错误才重连，此时请求还没有写出
+pub(crate) fn is_connect_error(err: &ClientError) -> bool {
+    match err {
+        ClientError::Transport(e) => matches!(
+            e.kind(),
+            io::ErrorKind::ConnectionRefused
+                | io::ErrorKind::NotConnected
+                | io::ErrorKind::AddrNotAvailable
+        ),
+        _ => false,
+    }
+}
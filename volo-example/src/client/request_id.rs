@@ -0,0 +1,118 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use faststr::FastStr;
+use metainfo::{Forward, METAINFO};
+
+pub const DEFAULT_REQUEST_ID_HEADER: &str = "x-request-id";
+
+// 自动生成的 id 格式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IdFormat {
+    #[default]
+    Uuid,
+    // 毫秒时间戳 << 22 | 进程内自增序号
+    Snowflake,
+}
+
+impl IdFormat {
+    fn generate(self) -> String {
+        match self {
+            IdFormat::Uuid => uuid::Uuid::new_v4().to_string(),
+            IdFormat::Snowflake => {
+                static SEQ: AtomicU64 = AtomicU64::new(0);
+                let millis = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as u64;
+                let seq = SEQ.fetch_add(1, Ordering::Relaxed) & ((1 << 22) - 1);
+                ((millis << 22) | seq).to_string()
+            }
+        }
+    }
+}
+
+type OnAssigned = Arc<dyn Fn(&str) + Send + Sync>;
+
+// 调用未携带 request id 时自动生成一个并放入 persistent header，保证每次调用都可追踪
+#[derive(Clone)]
+pub struct RequestIdLayer {
+    header: FastStr,
+    format: IdFormat,
+    on_assigned: Option<OnAssigned>,
+}
+
+impl RequestIdLayer {
+    pub fn new() -> Self {
+        Self {
+            header: FastStr::from_static_str(DEFAULT_REQUEST_ID_HEADER),
+            format: IdFormat::default(),
+            on_assigned: None,
+        }
+    }
+
+    pub fn header(mut self, header: impl Into<FastStr>) -> Self {
+        self.header = header.into();
+        self
+    }
+
+    pub fn format(mut self, format: IdFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    // 生成新 id 时回调，便于调用方记录日志
+    pub fn on_assigned(mut self, f: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        self.on_assigned = Some(Arc::new(f));
+        self
+    }
+}
+
+impl Default for RequestIdLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> volo::Layer<S> for RequestIdLayer {
+    type Service = RequestIdService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        RequestIdService { inner, layer: self }
+    }
+}
+
+#[derive(Clone)]
+pub struct RequestIdService<S> {
+    inner: S,
+    layer: RequestIdLayer,
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for RequestIdService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req> + Send + Sync + 'static,
+    Cx: Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        let assigned = METAINFO
+            .try_with(|mi| {
+                let mut mi = mi.borrow_mut();
+                if mi.get_persistent(&self.layer.header).is_some() {
+                    return None;
+                }
+                let id = self.layer.format.generate();
+                mi.set_persistent(self.layer.header.clone(), id.clone());
+                Some(id)
+            })
+            .ok()
+            .flatten();
+
+        if let (Some(id), Some(f)) = (&assigned, &self.layer.on_assigned) {
+            f(id);
+        }
+        self.inner.call(cx, req).await
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use async_broadcast::{InactiveReceiver, Receiver, Sender};
+use volo::context::Endpoint;
+use volo::discovery::{Change, Discover, Instance};
+use volo::loadbalance::error::LoadBalanceError;
+use volo::net::Address;
+
+use super::zone::ZONE_TAG;
+
+// 解析得到的一个地址，weight 与 zone 交给负载均衡使用
+#[derive(Clone, Debug)]
+pub struct Resolved {
+    pub addr: SocketAddr,
+    pub weight: u32,
+    pub zone: Option<String>,
+}
+
+// 自定义名字解析，例如在 service mesh 中向控制面查询
+pub trait Resolve: Send + Sync + 'static {
+    fn resolve(
+        &self,
+        host: &str,
+        port: u16,
+    ) -> impl Future<Output = io::Result<Vec<Resolved>>> + Send;
+}
+
+// 默认使用系统解析器
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemResolver;
+
+impl Resolve for SystemResolver {
+    async fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<Resolved>> {
+        Ok(tokio::net::lookup_host((host, port))
+            .await?
+            .map(|addr| Resolved {
+                addr,
+                weight: DEFAULT_WEIGHT,
+                zone: None,
+            })
+            .collect())
+    }
+}
+
+const DEFAULT_WEIGHT: u32 = 100;
+
+// 固定的地址列表，可以为每个地址单独标注 weight 与 zone
+impl Resolve for Vec<Resolved> {
+    async fn resolve(&self, _host: &str, _port: u16) -> io::Result<Vec<Resolved>> {
+        Ok(self.clone())
+    }
+}
+
+// 将 host:port 交给 Resolve 解析，结果作为负载均衡的实例列表
+// 通过 ClientBuilder::discover 接入，即按域名连接服务，如 ResolverDiscover::new("item.svc", 9090)
+// 负载均衡只在首次调用时解析一次，之后调用 refresh 重新解析（如配合 ReconnectLayer::on_reconnect），
+// 部署后 DNS 记录变化才能生效
+pub struct ResolverDiscover<R> {
+    host: String,
+    port: u16,
+    resolver: Arc<R>,
+    changes: Sender<Change<(String, u16)>>,
+    watcher: InactiveReceiver<Change<(String, u16)>>,
+}
+
+// resolver 放在 Arc 中共享，不要求 R: Clone
+impl<R> Clone for ResolverDiscover<R> {
+    fn clone(&self) -> Self {
+        Self {
+            host: self.host.clone(),
+            port: self.port,
+            resolver: self.resolver.clone(),
+            changes: self.changes.clone(),
+            watcher: self.watcher.clone(),
+        }
+    }
+}
+
+impl ResolverDiscover<SystemResolver> {
+    pub fn new(host: impl Into<String>, port: u16) -> Self {
+        Self::with_resolver(host, port, SystemResolver)
+    }
+}
+
+impl<R: Resolve> ResolverDiscover<R> {
+    pub fn with_resolver(host: impl Into<String>, port: u16, resolver: R) -> Self {
+        let (mut changes, watcher) = async_broadcast::broadcast(1);
+        // 只有最新一次的解析结果有意义，旧的直接丢弃
+        changes.set_overflow(true);
+        Self {
+            host: host.into(),
+            port,
+            resolver: Arc::new(resolver),
+            changes,
+            watcher: watcher.deactivate(),
+        }
+    }
+
+    // 在后台重新解析，结果替换负载均衡中的实例列表；解析失败时保留原有列表
+    pub fn refresh(&self) {
+        let this = self.clone();
+        tokio::spawn(async move {
+            match this.instances().await {
+                Ok(all) => {
+                    let _ = this.changes.try_broadcast(Change {
+                        key: (this.host.clone(), this.port),
+                        all,
+                        added: Vec::new(),
+                        updated: Vec::new(),
+                        removed: Vec::new(),
+                    });
+                }
+                Err(e) => tracing::warn!("failed to re-resolve {}:{}: {}", this.host, this.port, e),
+            }
+        });
+    }
+
+    async fn instances(&self) -> Result<Vec<Arc<Instance>>, LoadBalanceError> {
+        let resolved = self
+            .resolver
+            .resolve(&self.host, self.port)
+            .await
+            .map_err(|e| LoadBalanceError::Discover(Box::new(e)))?;
+        Ok(resolved
+            .into_iter()
+            .map(|r| {
+                let mut tags = HashMap::new();
+                if let Some(zone) = r.zone {
+                    tags.insert(ZONE_TAG.into(), zone.into());
+                }
+                Arc::new(Instance {
+                    address: Address::from(r.addr),
+                    weight: r.weight,
+                    tags,
+                })
+            })
+            .collect())
+    }
+}
+
+impl<R: Resolve> Discover for ResolverDiscover<R> {
+    type Key = (String, u16);
+    type Error = LoadBalanceError;
+
+    async fn discover<'s>(
+        &'s self,
+        _endpoint: &'s Endpoint,
+    ) -> Result<Vec<Arc<Instance>>, Self::Error> {
+        self.instances().await
+    }
+
+    fn key(&self, _endpoint: &Endpoint) -> Self::Key {
+        (self.host.clone(), self.port)
+    }
+
+    fn watch(&self, _keys: Option<&[Self::Key]>) -> Option<Receiver<Change<Self::Key>>> {
+        Some(self.watcher.activate_cloned())
+    }
+}
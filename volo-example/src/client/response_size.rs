@@ -0,0 +1,75 @@
+use std::future::Future;
+
+use bytes::Bytes;
+use tokio::io::AsyncRead;
+use volo::util::buf_reader::BufReader;
+use volo_thrift::codec::default::{MakeZeroCopyCodec, ZeroCopyDecoder};
+use volo_thrift::context::ThriftContext;
+use volo_thrift::{EntryMessage, ThriftException, ThriftMessage};
+
+// 响应超过阈值时打一条 warn 日志，包含方法名与实际大小，不影响调用结果
+// 与帧大小上限不同，只用于在响应持续变大、真正触发上限之前发现问题
+// 包在最内层的协议 codec 外，如
+// DefaultMakeCodec::new(MakeTTHeaderCodec::new(MakeFramedCodec::new(WarnResponseSizeMakeCodec::new(MakeThriftCodec::new(), n))))
+// 只对 framed/TTHeader 传输生效：无帧长的 buffered 传输拿不到完整帧，直接透传
+#[derive(Clone)]
+pub struct WarnResponseSizeMakeCodec<M> {
+    inner: M,
+    threshold: usize,
+}
+
+impl<M> WarnResponseSizeMakeCodec<M> {
+    pub fn new(inner: M, threshold: usize) -> Self {
+        Self { inner, threshold }
+    }
+}
+
+impl<M: MakeZeroCopyCodec> MakeZeroCopyCodec for WarnResponseSizeMakeCodec<M> {
+    type Encoder = M::Encoder;
+    type Decoder = WarnResponseSizeDecoder<M::Decoder>;
+
+    fn make_codec(&self) -> (Self::Encoder, Self::Decoder) {
+        let (encoder, decoder) = self.inner.make_codec();
+        let decoder = WarnResponseSizeDecoder {
+            inner: decoder,
+            threshold: self.threshold,
+        };
+        (encoder, decoder)
+    }
+}
+
+pub struct WarnResponseSizeDecoder<D> {
+    inner: D,
+    threshold: usize,
+}
+
+impl<D: ZeroCopyDecoder> ZeroCopyDecoder for WarnResponseSizeDecoder<D> {
+    fn decode<Msg: Send + EntryMessage, Cx: ThriftContext>(
+        &mut self,
+        cx: &mut Cx,
+        bytes: &mut Bytes,
+    ) -> Result<Option<ThriftMessage<Msg>>, ThriftException> {
+        let size = bytes.len();
+        if size > self.threshold {
+            tracing::warn!(
+                method = %cx.rpc_info().method(),
+                size,
+                threshold = self.threshold,
+                "response larger than warning threshold"
+            );
+        }
+        self.inner.decode(cx, bytes)
+    }
+
+    fn decode_async<
+        Msg: Send + EntryMessage,
+        Cx: ThriftContext,
+        R: AsyncRead + Unpin + Send + Sync,
+    >(
+        &mut self,
+        cx: &mut Cx,
+        reader: &mut BufReader<R>,
+    ) -> impl Future<Output = Result<Option<ThriftMessage<Msg>>, ThriftException>> + Send {
+        self.inner.decode_async(cx, reader)
+    }
+}
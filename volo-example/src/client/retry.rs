@@ -0,0 +1,164 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+use faststr::FastStr;
+use volo::context::Context;
+use volo_thrift::ClientError;
+
+use super::backoff::Backoff;
+use super::reconnect::is_connect_error;
+
+// 调用失败后按退避策略重试，固定间隔用 Backoff::fixed，指数退避用 Backoff::new
+// 建连失败时请求还没有写出，任何方法都可以重试；连接被重置、超时等失败发生时请求可能已经被服务端处理，
+// 只有通过 idempotent 声明为幂等的方法才会重试
+// 重试过的调用最终以传输错误失败时，返回的错误带上总尝试次数，见 retry_attempts；
+// 服务端返回的异常与协议错误原样返回，次数只记录在日志中
+#[derive(Clone, Debug)]
+pub struct RetryLayer {
+    max_attempts: u32,
+    backoff: Backoff,
+    idempotent: Arc<HashSet<FastStr>>,
+}
+
+impl RetryLayer {
+    pub fn new(max_attempts: u32, backoff: Backoff) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            backoff,
+            idempotent: Default::default(),
+        }
+    }
+
+    // 可以在请求写出后安全重试的方法，如只读的 GetItem
+    pub fn idempotent<I, M>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = M>,
+        M: Into<FastStr>,
+    {
+        self.idempotent = Arc::new(methods.into_iter().map(Into::into).collect());
+        self
+    }
+}
+
+impl<S> volo::Layer<S> for RetryLayer {
+    type Service = RetryService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        RetryService {
+            inner,
+            max_attempts: self.max_attempts,
+            backoff: self.backoff,
+            idempotent: self.idempotent,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RetryService<S> {
+    inner: S,
+    max_attempts: u32,
+    backoff: Backoff,
+    idempotent: Arc<HashSet<FastStr>>,
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for RetryService<S>
+where
+    Req: Clone + Send + 'static,
+    S: volo::Service<Cx, Req, Error = ClientError> + Send + Sync + 'static,
+    S::Response: Send,
+    Cx: Context + Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        let idempotent = self.idempotent.contains(cx.rpc_info().method());
+        let mut attempt = 0;
+        loop {
+            match self.inner.call(cx, req.clone()).await {
+                Err(e) if attempt + 1 < self.max_attempts && is_retryable(&e, idempotent) => {
+                    let delay = self.backoff.delay(attempt);
+                    tracing::debug!(
+                        "call failed on attempt {}: {}, retrying in {:?}",
+                        attempt + 1,
+                        e,
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    if attempt == 0 {
+                        return Err(e);
+                    }
+                    tracing::warn!("call failed after {} attempts: {}", attempt + 1, e);
+                    return Err(with_attempts(e, attempt + 1));
+                }
+                resp => return resp,
+            }
+        }
+    }
+}
+
+// 请求写出后才可能出现的瞬时错误只对幂等方法重试
+fn is_retryable(err: &ClientError, idempotent: bool) -> bool {
+    if is_connect_error(err) {
+        return true;
+    }
+    if !idempotent {
+        return false;
+    }
+    match err {
+        ClientError::Transport(e) => matches!(
+            e.kind(),
+            io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::BrokenPipe
+                | io::ErrorKind::UnexpectedEof
+                | io::ErrorKind::TimedOut
+        ),
+        _ => false,
+    }
+}
+
+// 重试后仍失败的传输错误携带的 io 错误，io 错误类型与最后一次失败相同
+#[derive(Debug)]
+pub struct RetriesExhausted {
+    pub attempts: u32,
+    pub last_error: String,
+}
+
+impl fmt::Display for RetriesExhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (after {} attempts)", self.last_error, self.attempts)
+    }
+}
+
+impl std::error::Error for RetriesExhausted {}
+
+// 传输错误换成携带 RetriesExhausted 的同类型 io 错误；其余错误原样返回
+fn with_attempts(err: ClientError, attempts: u32) -> ClientError {
+    match err {
+        ClientError::Transport(e) => {
+            let kind = e.kind();
+            let exhausted = RetriesExhausted {
+                attempts,
+                last_error: e.to_string(),
+            };
+            ClientError::Transport(io::Error::new(kind, exhausted).into())
+        }
+        other => other,
+    }
+}
+
+// 重试用尽后失败的传输错误的总尝试次数；没有重试过或不是传输错误时返回 None
+pub fn retry_attempts(err: &ClientError) -> Option<u32> {
+    match err {
+        ClientError::Transport(e) => e
+            .io_error()
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<RetriesExhausted>())
+            .map(|e| e.attempts),
+        _ => None,
+    }
+}
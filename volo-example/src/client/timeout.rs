@@ -0,0 +1,65 @@
+use std::io;
+use std::time::Duration;
+
+use volo_thrift::ClientError;
+
+// 为每次调用设置整体截止时间，默认（不加这个 layer）没有超时
+// 作为 outer layer 时计时覆盖建连、发送与等待回包的全过程，而不只是读回包
+// 与 ReconnectLayer 一起使用时放在其外层，重试也计入同一个截止时间
+#[derive(Clone, Copy)]
+pub struct RequestTimeoutLayer {
+    timeout: Duration,
+}
+
+impl RequestTimeoutLayer {
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+}
+
+// 调用是否超时：超过 RequestTimeoutLayer 的截止时间，或传输层本身超时（如建连、TLS 握手）
+// 超时以 ClientError::Transport 返回，io 错误类型为 TimedOut，服务端返回的异常不会被误判
+pub fn is_timeout(err: &ClientError) -> bool {
+    matches!(
+        err,
+        ClientError::Transport(e) if e.kind() == io::ErrorKind::TimedOut
+    )
+}
+
+impl<S> volo::Layer<S> for RequestTimeoutLayer {
+    type Service = RequestTimeoutService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        RequestTimeoutService {
+            inner,
+            timeout: self.timeout,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RequestTimeoutService<S> {
+    inner: S,
+    timeout: Duration,
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for RequestTimeoutService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req, Error = ClientError> + Send + Sync + 'static,
+    Cx: Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        match tokio::time::timeout(self.timeout, self.inner.call(cx, req)).await {
+            Ok(result) => result,
+            Err(_) => {
+                let err = io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("request timeout: no response within {:?}", self.timeout),
+                );
+                Err(ClientError::Transport(err.into()))
+            }
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use std::time::Duration;
+
+use metainfo::{Backward, METAINFO};
+
+use crate::server::PROCESSING_TIME_HEADER;
+
+// 读取最近一次调用回包中服务端上报的处理耗时
+// 需要在 METAINFO.scope 内发起调用，否则回包 header 不会保留给调用方
+pub fn server_processing_time() -> Option<Duration> {
+    METAINFO
+        .try_with(|mi| {
+            mi.borrow()
+                .get_backward_downstream(PROCESSING_TIME_HEADER)
+                .and_then(|v| v.parse::<u64>().ok())
+                .map(Duration::from_millis)
+        })
+        .ok()
+        .flatten()
+}
@@ -0,0 +1,161 @@
+use std::io;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
+use std::time::Duration;
+
+use anyhow::Context;
+use rustls::pki_types::ServerName;
+use rustls::RootCertStore;
+use tokio::io::{AsyncRead, AsyncWrite, Interest, ReadBuf, Ready};
+use tokio::net::TcpStream;
+use tokio_rustls::{TlsConnector, TlsStream};
+use volo::net::conn::{ConnStream, OwnedReadHalf, OwnedWriteHalf};
+use volo::net::dial::MakeTransport;
+use volo::net::ready::AsyncReady;
+use volo::net::Address;
+
+use crate::tls::{load_certs, TlsPolicy, HANDSHAKE_TIMEOUT};
+
+// 客户端 TLS 配置：信任 ca_path 中的根证书，按策略限制协议版本与密码套件
+// 服务端只支持更低版本或被排除的套件时握手失败，而不是静默降级
+pub fn tls_client_config(ca_path: &Path, policy: &TlsPolicy) -> anyhow::Result<rustls::ClientConfig> {
+    let mut roots = RootCertStore::empty();
+    for cert in load_certs(ca_path)? {
+        roots
+            .add(cert)
+            .with_context(|| format!("invalid CA certificate in {}", ca_path.display()))?;
+    }
+    Ok(rustls::ClientConfig::builder_with_provider(policy.provider()?)
+        .with_protocol_versions(policy.versions())
+        .context("invalid TLS policy")?
+        .with_root_certificates(roots)
+        .with_no_client_auth())
+}
+
+// 建连后先完成 TLS 握手再交给 volo-thrift：ClientBuilder::make_transport(TlsMakeTransport::new(config, sni))
+// sni 为 None 时以对端 IP 校验证书（证书需包含对应的 IP SAN）
+// 对端是明文服务端时握手因连接被关闭而失败，最长等待 HANDSHAKE_TIMEOUT，不会一直挂起
+#[derive(Clone)]
+pub struct TlsMakeTransport {
+    connector: TlsConnector,
+    server_name: Option<ServerName<'static>>,
+    connect_timeout: Option<Duration>,
+}
+
+impl TlsMakeTransport {
+    pub fn new(config: rustls::ClientConfig, server_name: Option<ServerName<'static>>) -> Self {
+        Self {
+            connector: TlsConnector::from(Arc::new(config)),
+            server_name,
+            connect_timeout: None,
+        }
+    }
+}
+
+impl MakeTransport for TlsMakeTransport {
+    type ReadHalf = TlsReadHalf;
+    type WriteHalf = TlsWriteHalf;
+
+    async fn make_transport(&self, addr: Address) -> io::Result<(TlsReadHalf, TlsWriteHalf)> {
+        let Address::Ip(addr) = addr else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "TLS is only supported over TCP",
+            ));
+        };
+        let connect = TcpStream::connect(addr);
+        let tcp = match self.connect_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, connect)
+                .await
+                .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "connect timed out"))??,
+            None => connect.await?,
+        };
+        tcp.set_nodelay(true)?;
+        // 复制一份 socket 句柄，只用于就绪检查
+        let tcp = tcp.into_std()?;
+        let socket = Arc::new(TcpStream::from_std(tcp.try_clone()?)?);
+        let tcp = TcpStream::from_std(tcp)?;
+
+        let server_name = self
+            .server_name
+            .clone()
+            .unwrap_or_else(|| ServerName::IpAddress(addr.ip().into()));
+        let stream = tokio::time::timeout(HANDSHAKE_TIMEOUT, self.connector.connect(server_name, tcp))
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "TLS handshake timed out"))??;
+        let (read, write) = ConnStream::Rustls(TlsStream::Client(stream)).into_split();
+        Ok((
+            TlsReadHalf {
+                inner: read,
+                socket: socket.clone(),
+            },
+            TlsWriteHalf {
+                inner: write,
+                socket,
+            },
+        ))
+    }
+
+    fn set_connect_timeout(&mut self, timeout: Option<Duration>) {
+        self.connect_timeout = timeout;
+    }
+
+    // 读写超时由 volo-thrift 的调用超时统一控制
+    fn set_read_timeout(&mut self, _timeout: Option<Duration>) {}
+
+    fn set_write_timeout(&mut self, _timeout: Option<Duration>) {}
+}
+
+// volo 的 Rustls 连接没有实现就绪检查（调用即 panic），而连接池复用连接前要通过写端判断连接是否已关闭
+// 读写仍走 TLS 连接，就绪状态取自同一 socket 的另一个句柄
+pub struct TlsReadHalf {
+    inner: OwnedReadHalf,
+    socket: Arc<TcpStream>,
+}
+
+pub struct TlsWriteHalf {
+    inner: OwnedWriteHalf,
+    socket: Arc<TcpStream>,
+}
+
+impl AsyncReady for TlsReadHalf {
+    async fn ready(&self, interest: Interest) -> io::Result<Ready> {
+        self.socket.ready(interest).await
+    }
+}
+
+impl AsyncReady for TlsWriteHalf {
+    async fn ready(&self, interest: Interest) -> io::Result<Ready> {
+        self.socket.ready(interest).await
+    }
+}
+
+impl AsyncRead for TlsReadHalf {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for TlsWriteHalf {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
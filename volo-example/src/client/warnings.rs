@@ -0,0 +1,18 @@
+use metainfo::{Backward, METAINFO};
+
+use crate::server::WARNINGS_HEADER;
+
+// 读取最近一次调用回包中的 warning
+// 非空说明调用成功但结果不完整，例如批量接口只返回了部分条目
+// 与 server_processing_time 一样需要在 METAINFO.scope 内发起调用
+pub fn response_warnings() -> Vec<String> {
+    METAINFO
+        .try_with(|mi| {
+            mi.borrow()
+                .get_backward_downstream(WARNINGS_HEADER)
+                .map(|v| v.lines().map(str::to_string).collect())
+        })
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
@@ -0,0 +1,242 @@
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, Interest, ReadBuf, Ready};
+use tokio::sync::Notify;
+use tokio::time::Instant;
+use volo::net::ready::AsyncReady;
+use volo_thrift::codec::MakeCodec;
+
+const DEFAULT_MAX_BATCH_BYTES: usize = 64 * 1024;
+
+// 合并一个时间窗口内写出的请求，用一次 write 发送，以少量延迟换更少的系统调用
+// 适合 multiplex 下大量并发小请求的场景；缓冲达到 max_batch_bytes 时立即发送
+// 不使用这个 codec 即为默认行为：每个请求编码后立即写出
+// 写出在后台任务中完成，flush 只把数据交给后台任务而不等待写出：encoder 每条消息都会 flush，等待写出就无法合并
+// 写连接失败后读端随即返回同一错误，等待回包的调用立即失败，而不是挂起到超时
+#[derive(Clone)]
+pub struct WriteBatchMakeCodec<M> {
+    inner: M,
+    window: Duration,
+    max_batch_bytes: usize,
+}
+
+impl<M> WriteBatchMakeCodec<M> {
+    pub fn new(inner: M, window: Duration) -> Self {
+        Self {
+            inner,
+            window,
+            max_batch_bytes: DEFAULT_MAX_BATCH_BYTES,
+        }
+    }
+
+    pub fn max_batch_bytes(mut self, max_batch_bytes: usize) -> Self {
+        self.max_batch_bytes = max_batch_bytes.max(1);
+        self
+    }
+}
+
+impl<R, W, M> MakeCodec<R, W> for WriteBatchMakeCodec<M>
+where
+    R: AsyncRead + Send + Sync + Unpin + 'static,
+    W: AsyncWrite + Send + Sync + Unpin + 'static,
+    M: MakeCodec<BatchReader<R>, BatchWriter>,
+{
+    type Encoder = M::Encoder;
+    type Decoder = M::Decoder;
+
+    fn make_codec(&self, reader: R, writer: W) -> (Self::Encoder, Self::Decoder) {
+        let (reader, writer) = self.wrap(reader, writer);
+        self.inner.make_codec(reader, writer)
+    }
+}
+
+impl<M> WriteBatchMakeCodec<M> {
+    // 包装一条连接的读写两端并启动后台写出任务
+    pub fn wrap<R, W>(&self, reader: R, writer: W) -> (BatchReader<R>, BatchWriter)
+    where
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        let shared = Arc::new(Shared::default());
+        tokio::spawn(flush_loop(
+            writer,
+            shared.clone(),
+            self.window,
+            self.max_batch_bytes,
+        ));
+        (
+            BatchReader {
+                inner: reader,
+                shared: shared.clone(),
+            },
+            BatchWriter {
+                shared,
+                max_batch_bytes: self.max_batch_bytes,
+            },
+        )
+    }
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    notify: Notify,
+}
+
+#[derive(Default)]
+struct State {
+    buf: Vec<u8>,
+    // 写连接失败后，之后的每次写入、flush 与读取都返回该错误
+    error: Option<(io::ErrorKind, String)>,
+    closed: bool,
+    // 缓冲已满时等待后台任务腾出空间的写入方
+    blocked: Option<Waker>,
+    // 正在等待回包的读取方，写失败时唤醒它返回错误
+    reading: Option<Waker>,
+}
+
+// 读取前先检查写出是否已失败；请求没有发出去，对应的回包不会到来
+pub struct BatchReader<R> {
+    inner: R,
+    shared: Arc<Shared>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for BatchReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        {
+            let mut state = self.shared.state.lock().unwrap();
+            state.check()?;
+            state.reading = Some(cx.waker().clone());
+        }
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<R: AsyncReady + Sync> AsyncReady for BatchReader<R> {
+    async fn ready(&self, interest: Interest) -> io::Result<Ready> {
+        self.inner.ready(interest).await
+    }
+}
+
+// 写入只进入共享缓冲区，由后台任务按窗口合并后写到连接
+pub struct BatchWriter {
+    shared: Arc<Shared>,
+    max_batch_bytes: usize,
+}
+
+impl State {
+    fn check(&self) -> io::Result<()> {
+        match &self.error {
+            Some((kind, msg)) => Err(io::Error::new(*kind, msg.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+impl AsyncWrite for BatchWriter {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let mut state = self.shared.state.lock().unwrap();
+        state.check()?;
+        // 后台任务来不及写出时施加背压，避免缓冲无限增长
+        if state.buf.len() >= self.max_batch_bytes * 4 {
+            state.blocked = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+        state.buf.extend_from_slice(buf);
+        drop(state);
+        self.shared.notify.notify_one();
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    // 数据在窗口结束时由后台任务写出并 flush，这里只返回已经发生的写错误
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(self.shared.state.lock().unwrap().check())
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let mut state = self.shared.state.lock().unwrap();
+        state.closed = true;
+        let res = state.check();
+        drop(state);
+        self.shared.notify.notify_one();
+        Poll::Ready(res)
+    }
+}
+
+// 连接由后台任务持有，这里按写出是否已失败报告；连接池据此丢弃写失败的连接
+impl AsyncReady for BatchWriter {
+    async fn ready(&self, _interest: Interest) -> io::Result<Ready> {
+        let state = self.shared.state.lock().unwrap();
+        if state.error.is_some() || state.closed {
+            Ok(Ready::READ_CLOSED | Ready::WRITE_CLOSED)
+        } else {
+            Ok(Ready::WRITABLE)
+        }
+    }
+}
+
+impl Drop for BatchWriter {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().closed = true;
+        self.shared.notify.notify_one();
+    }
+}
+
+async fn flush_loop<W: AsyncWrite + Unpin>(
+    mut writer: W,
+    shared: Arc<Shared>,
+    window: Duration,
+    max_batch_bytes: usize,
+) {
+    loop {
+        shared.notify.notified().await;
+
+        // 第一次写入后等待窗口结束，期间缓冲达到上限或连接关闭则提前发送
+        let deadline = Instant::now() + window;
+        loop {
+            {
+                let state = shared.state.lock().unwrap();
+                if state.buf.len() >= max_batch_bytes || state.closed {
+                    break;
+                }
+            }
+            tokio::select! {
+                _ = tokio::time::sleep_until(deadline) => break,
+                _ = shared.notify.notified() => {}
+            }
+        }
+
+        let (batch, closed) = {
+            let mut state = shared.state.lock().unwrap();
+            if let Some(waker) = state.blocked.take() {
+                waker.wake();
+            }
+            (std::mem::take(&mut state.buf), state.closed)
+        };
+        if !batch.is_empty() {
+            let res = match writer.write_all(&batch).await {
+                Ok(()) => writer.flush().await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = res {
+                let mut state = shared.state.lock().unwrap();
+                state.error = Some((e.kind(), e.to_string()));
+                for waker in [state.blocked.take(), state.reading.take()].into_iter().flatten() {
+                    waker.wake();
+                }
+                return;
+            }
+        }
+        if closed {
+            let _ = writer.shutdown().await;
+            return;
+        }
+    }
+}
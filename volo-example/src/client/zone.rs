@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+use rand::seq::SliceRandom;
+use volo::context::Endpoint;
+use volo::discovery::{Change, Discover, Instance};
+use volo::loadbalance::error::LoadBalanceError;
+use volo::loadbalance::LoadBalance;
+use volo::net::Address;
+
+// 实例 tags 中表示所在可用区的键
+pub const ZONE_TAG: &str = "zone";
+
+type Instances = Arc<Vec<Arc<Instance>>>;
+
+// 同可用区优先的负载均衡
+// 每次选取时先按权重随机排列本区实例，再接上其它区的实例
+// 本区实例建连失败时客户端沿着这个顺序重试，自然溢出到其它区；volo 默认不重试，需要设置 ClientBuilder::retry_count
+// 未设置 local_zone 或实例没有 zone 标签时退化为普通的加权随机
+pub struct ZoneAwareLoadBalance<K> {
+    local_zone: Option<String>,
+    instances: Arc<Mutex<HashMap<K, Instances>>>,
+}
+
+// 克隆出的负载均衡共享同一份实例列表
+impl<K> Clone for ZoneAwareLoadBalance<K> {
+    fn clone(&self) -> Self {
+        Self {
+            local_zone: self.local_zone.clone(),
+            instances: self.instances.clone(),
+        }
+    }
+}
+
+impl<K> Default for ZoneAwareLoadBalance<K> {
+    fn default() -> Self {
+        Self {
+            local_zone: None,
+            instances: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl<K> ZoneAwareLoadBalance<K> {
+    pub fn local_zone(mut self, zone: impl Into<String>) -> Self {
+        self.local_zone = Some(zone.into());
+        self
+    }
+
+    fn is_local(&self, instance: &Instance) -> bool {
+        match &self.local_zone {
+            Some(zone) => instance.tags.get(ZONE_TAG).map(|z| z.as_ref()) == Some(zone.as_str()),
+            None => false,
+        }
+    }
+
+    fn order(&self, instances: &[Arc<Instance>]) -> Vec<Address> {
+        let (local, remote): (Vec<_>, Vec<_>) =
+            instances.iter().cloned().partition(|i| self.is_local(i));
+        let mut rng = rand::thread_rng();
+        let mut addrs = Vec::with_capacity(instances.len());
+        for group in [local, remote] {
+            // 权重为 0 的实例只在没有其它选择时使用
+            let (weighted, zero): (Vec<_>, Vec<_>) =
+                group.into_iter().partition(|i| i.weight > 0);
+            if let Ok(picked) =
+                weighted.choose_multiple_weighted(&mut rng, weighted.len(), |i| i.weight as f64)
+            {
+                addrs.extend(picked.map(|i| i.address.clone()));
+            }
+            addrs.extend(zero.into_iter().map(|i| i.address.clone()));
+        }
+        addrs
+    }
+}
+
+impl<D> LoadBalance<D> for ZoneAwareLoadBalance<D::Key>
+where
+    D: Discover,
+    D::Key: Hash + Eq,
+    LoadBalanceError: From<D::Error>,
+{
+    type InstanceIter = std::vec::IntoIter<Address>;
+
+    async fn get_picker<'future>(
+        &'future self,
+        endpoint: &'future Endpoint,
+        discover: &'future D,
+    ) -> Result<Self::InstanceIter, LoadBalanceError> {
+        let key = discover.key(endpoint);
+        let cached = self.instances.lock().unwrap().get(&key).cloned();
+        let instances = match cached {
+            Some(instances) => instances,
+            None => {
+                let instances = Arc::new(discover.discover(endpoint).await?);
+                // 只有能 watch 的 discover 才会通过 rebalance 推送变化，否则每次重新 discover，
+                // 避免实例列表与可用区归属停留在第一次的结果
+                if discover.watch(Some(std::slice::from_ref(&key))).is_some() {
+                    self.instances
+                        .lock()
+                        .unwrap()
+                        .insert(key, instances.clone());
+                }
+                instances
+            }
+        };
+        Ok(self.order(&instances).into_iter())
+    }
+
+    fn rebalance(&self, changes: Change<D::Key>) {
+        self.instances
+            .lock()
+            .unwrap()
+            .insert(changes.key, Arc::new(changes.all));
+    }
+}
@@ -1,8 +1,28 @@
-use faststr::FastStr;
+// #[volo::service] 把 async fn call 展开为返回 impl Future 的函数，clippy 会在展开后的代码上报 manual_async_fn
+#![allow(clippy::manual_async_fn)]
+
 use volo_gen::volo::example::Item;
 use ahash::AHashMap;
 
-pub struct S;
+pub mod client;
+// 客户端与服务端共用的调用优先级定义
+pub mod priority;
+pub mod server;
+// 客户端与服务端共用的 TLS 策略与证书加载
+pub mod tls;
+
+use server::Health;
+
+#[derive(Clone, Default)]
+pub struct S {
+    health: Health,
+}
+
+impl S {
+    pub fn new(health: Health) -> Self {
+        Self { health }
+    }
+}
 
 impl volo_gen::volo::example::ItemService for S {
     async fn get_item(
@@ -10,19 +30,59 @@ impl volo_gen::volo::example::ItemService for S {
         req: volo_gen::volo::example::GetItemRequest,
     ) -> ::core::result::Result<volo_gen::volo::example::GetItemResponse, ::volo_thrift::ServerError>
     {
-        let item = Item {
-            id: req.id,
-            title: format!("Item {}", req.id).into(), // 将 String 转换为 FastStr
-            content: format!("This is the content for item {}", req.id).into(), // 将 String 转换为 FastStr
-            extra: Some(AHashMap::new()), // 这里可以为空的 AHashMap
-        };
-
+        if let Some(trace_id) = server::metadata(server::DEFAULT_TRACE_ID_HEADER) {
+            tracing::debug!("get_item {} trace id {}", req.id, trace_id);
+        }
         let response = volo_gen::volo::example::GetItemResponse {
-            item,
+            item: make_item(req.id),
         };
 
         Ok(response)
     }
+
+    // 批量查询：无效的 id 不会让整个调用失败，而是跳过并附带一条 warning
+    async fn get_items(
+        &self,
+        req: volo_gen::volo::example::GetItemsRequest,
+    ) -> ::core::result::Result<volo_gen::volo::example::GetItemsResponse, ::volo_thrift::ServerError>
+    {
+        let mut items = Vec::with_capacity(req.ids.len());
+        for id in req.ids {
+            if id <= 0 {
+                server::add_warning(format!("id {}: invalid id", id));
+                continue;
+            }
+            items.push(make_item(id));
+        }
+        Ok(volo_gen::volo::example::GetItemsResponse { items })
+    }
+
+    async fn check(
+        &self,
+    ) -> ::core::result::Result<volo_gen::volo::example::HealthCheckResponse, ::volo_thrift::ServerError>
+    {
+        Ok(volo_gen::volo::example::HealthCheckResponse {
+            status: self.health.status(),
+        })
+    }
+
+    // oneway：返回值不会发回客户端，客户端也不会等待这里执行完
+    async fn fire_and_forget(
+        &self,
+        event: volo_gen::volo::example::ItemEvent,
+    ) -> ::core::result::Result<(), ::volo_thrift::ServerError> {
+        tracing::info!("item {} event: {}", event.id, event.event);
+        Ok(())
+    }
+}
+
+fn make_item(id: i64) -> Item {
+    Item {
+        id,
+        title: format!("Item {}", id).into(), // 将 String 转换为 FastStr
+        content: format!("This is the content for item {}", id).into(), // 将 String 转换为 FastStr
+        extra: Some(AHashMap::new()), // 这里可以为空的 AHashMap
+    }
 }
 
 // pub struct S;
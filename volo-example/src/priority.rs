@@ -0,0 +1,46 @@
+use std::fmt;
+use std::str::FromStr;
+
+// 客户端标注调用优先级的 THeader 键
+pub const PRIORITY_HEADER: &str = "x-priority";
+
+// 调用优先级，未标注时为 Normal
+// 服务端并发已满时，排队的请求按 High > Normal > Low 的顺序获得执行机会，同级按到达顺序
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    // 尽力而为的后台任务，如预热、批量导出
+    Low,
+    #[default]
+    Normal,
+    // 延迟敏感的在线请求
+    High,
+}
+
+impl Priority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::Low => "low",
+            Priority::Normal => "normal",
+            Priority::High => "high",
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Priority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "low" => Ok(Priority::Low),
+            "normal" => Ok(Priority::Normal),
+            "high" => Ok(Priority::High),
+            _ => Err(format!("unknown priority {:?}", s)),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use faststr::FastStr;
+use volo::context::Context;
+
+use super::metadata;
+
+// 一条审计记录：调用方身份等 header、方法、结果与时间
+#[derive(Clone, Debug)]
+pub struct AuditRecord {
+    pub timestamp: SystemTime,
+    pub method: FastStr,
+    pub headers: Vec<(FastStr, FastStr)>,
+    // 失败时为错误描述
+    pub outcome: Result<(), String>,
+}
+
+// 审计日志的输出目标，与普通日志分开配置
+pub trait AuditSink: Send + Sync + 'static {
+    fn write(&self, record: &AuditRecord);
+}
+
+// 默认输出到 target 为 "audit" 的 tracing 事件，可在 subscriber 中单独路由
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TracingAuditSink;
+
+impl AuditSink for TracingAuditSink {
+    fn write(&self, record: &AuditRecord) {
+        let timestamp = record
+            .timestamp
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        match &record.outcome {
+            Ok(()) => tracing::info!(
+                target: "audit",
+                timestamp,
+                method = %record.method,
+                headers = ?record.headers,
+                outcome = "ok",
+            ),
+            Err(e) => tracing::info!(
+                target: "audit",
+                timestamp,
+                method = %record.method,
+                headers = ?record.headers,
+                outcome = "error",
+                error = %e,
+            ),
+        }
+    }
+}
+
+// 为每个请求写一条审计记录，handler 出错时同样记录
+#[derive(Clone)]
+pub struct AuditLayer {
+    keys: Arc<[FastStr]>,
+    sink: Arc<dyn AuditSink>,
+}
+
+impl AuditLayer {
+    // keys 为需要记录的 THeader info header，如调用方身份、request id
+    pub fn new<K: Into<FastStr>>(keys: impl IntoIterator<Item = K>) -> Self {
+        Self {
+            keys: keys.into_iter().map(Into::into).collect(),
+            sink: Arc::new(TracingAuditSink),
+        }
+    }
+
+    pub fn sink(mut self, sink: impl AuditSink) -> Self {
+        self.sink = Arc::new(sink);
+        self
+    }
+}
+
+impl<S> volo::Layer<S> for AuditLayer {
+    type Service = AuditService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        AuditService {
+            inner,
+            keys: self.keys,
+            sink: self.sink,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct AuditService<S> {
+    inner: S,
+    keys: Arc<[FastStr]>,
+    sink: Arc<dyn AuditSink>,
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for AuditService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req> + Send + Sync + 'static,
+    S::Error: fmt::Display,
+    Cx: Context + Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        let headers: Vec<(FastStr, FastStr)> = self
+            .keys
+            .iter()
+            .filter_map(|key| metadata(key).map(|value| (key.clone(), value)))
+            .collect();
+        let method = cx.rpc_info().method().clone();
+        let timestamp = SystemTime::now();
+
+        let resp = self.inner.call(cx, req).await;
+
+        self.sink.write(&AuditRecord {
+            timestamp,
+            method,
+            headers,
+            outcome: resp.as_ref().map(|_| ()).map_err(|e| e.to_string()),
+        });
+        resp
+    }
+}
@@ -0,0 +1,236 @@
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context as TaskContext, Poll, Waker};
+
+use bytes::BytesMut;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, Interest, ReadBuf, Ready};
+use tokio::sync::Notify;
+use tokio_util::sync::{CancellationToken, WaitForCancellationFuture};
+use volo::context::Context;
+use volo::net::ready::AsyncReady;
+use volo_thrift::codec::{Decoder, MakeCodec};
+use volo_thrift::context::ThriftContext;
+use volo_thrift::{EntryMessage, ThriftMessage};
+
+// 请求级取消信号：所属连接关闭后被触发
+// handler 可以轮询 is_cancelled()，或在 select! 中等待 cancelled()
+#[derive(Clone, Debug)]
+pub struct RequestCancellation(CancellationToken);
+
+impl RequestCancellation {
+    pub fn is_cancelled(&self) -> bool {
+        self.0.is_cancelled()
+    }
+
+    pub fn cancelled(&self) -> WaitForCancellationFuture<'_> {
+        self.0.cancelled()
+    }
+}
+
+tokio::task_local! {
+    static CANCELLATION: RequestCancellation;
+}
+
+// 在 handler 中获取当前请求的取消信号，未经过 CancellationLayer 时返回 None
+pub fn cancellation() -> Option<RequestCancellation> {
+    CANCELLATION.try_with(|c| c.clone()).ok()
+}
+
+// 为每条连接创建一个取消令牌，对端关闭连接、读连接出错或解码失败时触发
+// ping-pong 模式下处理请求期间 decoder 不会读连接，因此由后台任务持续预读连接，读到 EOF 立即取消，
+// 预读的数据原样交给 decoder；对端只关闭写方向（half-close）也视为断开
+#[derive(Clone)]
+pub struct CancelOnCloseMakeCodec<M> {
+    inner: M,
+}
+
+impl<M> CancelOnCloseMakeCodec<M> {
+    pub fn new(inner: M) -> Self {
+        Self { inner }
+    }
+}
+
+impl<R, W, M> MakeCodec<R, W> for CancelOnCloseMakeCodec<M>
+where
+    R: AsyncRead + Send + Sync + Unpin + 'static,
+    W: AsyncWrite + Send + Sync + Unpin + 'static,
+    M: MakeCodec<ReadAheadReader, W>,
+{
+    type Encoder = M::Encoder;
+    type Decoder = CancelOnCloseDecoder<M::Decoder>;
+
+    fn make_codec(&self, reader: R, writer: W) -> (Self::Encoder, Self::Decoder) {
+        let token = CancellationToken::new();
+        let shared = Arc::new(ReadAhead::default());
+        tokio::spawn(read_ahead(reader, shared.clone(), token.clone()));
+        let (encoder, decoder) = self.inner.make_codec(ReadAheadReader { shared }, writer);
+        let decoder = CancelOnCloseDecoder {
+            inner: decoder,
+            token,
+        };
+        (encoder, decoder)
+    }
+}
+
+// 预读缓冲的上限；对端连续发送大量数据时暂停预读，直到 decoder 取走一部分
+const MAX_READ_AHEAD: usize = 64 * 1024;
+
+#[derive(Default)]
+struct ReadAhead {
+    state: Mutex<ReadAheadState>,
+    // decoder 取走数据后通知后台任务继续预读
+    drained: Notify,
+}
+
+#[derive(Default)]
+struct ReadAheadState {
+    buf: BytesMut,
+    eof: bool,
+    error: Option<io::Error>,
+    waker: Option<Waker>,
+}
+
+// decoder 读取的连接，数据来自后台预读任务
+pub struct ReadAheadReader {
+    shared: Arc<ReadAhead>,
+}
+
+impl AsyncRead for ReadAheadReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let mut state = self.shared.state.lock().unwrap();
+        if !state.buf.is_empty() {
+            let n = buf.remaining().min(state.buf.len());
+            buf.put_slice(&state.buf.split_to(n));
+            drop(state);
+            self.shared.drained.notify_one();
+            return Poll::Ready(Ok(()));
+        }
+        if let Some(e) = state.error.take() {
+            return Poll::Ready(Err(e));
+        }
+        if state.eof {
+            return Poll::Ready(Ok(()));
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+// 连接由后台预读任务持有，这里按预读的状态报告，不等待新的数据
+impl AsyncReady for ReadAheadReader {
+    async fn ready(&self, _interest: Interest) -> io::Result<Ready> {
+        let state = self.shared.state.lock().unwrap();
+        if state.eof || state.error.is_some() {
+            Ok(Ready::READ_CLOSED)
+        } else {
+            Ok(Ready::READABLE)
+        }
+    }
+}
+
+async fn read_ahead<R: AsyncRead + Unpin>(
+    mut reader: R,
+    shared: Arc<ReadAhead>,
+    token: CancellationToken,
+) {
+    let mut chunk = vec![0; 8 * 1024];
+    loop {
+        while shared.state.lock().unwrap().buf.len() >= MAX_READ_AHEAD {
+            shared.drained.notified().await;
+        }
+        // decoder 被释放时连接已经关闭，停止预读
+        let res = tokio::select! {
+            res = reader.read(&mut chunk) => res,
+            _ = token.cancelled() => return,
+        };
+        let mut state = shared.state.lock().unwrap();
+        let closed = match res {
+            Ok(0) => {
+                state.eof = true;
+                true
+            }
+            Ok(n) => {
+                state.buf.extend_from_slice(&chunk[..n]);
+                false
+            }
+            Err(e) => {
+                state.error = Some(e);
+                true
+            }
+        };
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        drop(state);
+        if closed {
+            token.cancel();
+            return;
+        }
+    }
+}
+
+pub struct CancelOnCloseDecoder<D> {
+    inner: D,
+    token: CancellationToken,
+}
+
+impl<D: Decoder> Decoder for CancelOnCloseDecoder<D> {
+    async fn decode<Msg: Send + EntryMessage, Cx: ThriftContext>(
+        &mut self,
+        cx: &mut Cx,
+    ) -> Result<Option<ThriftMessage<Msg>>, volo_thrift::ThriftException> {
+        let res = self.inner.decode(cx).await;
+        match &res {
+            Ok(Some(_)) => {
+                cx.extensions_mut()
+                    .insert(RequestCancellation(self.token.clone()));
+            }
+            // EOF 或解码失败，连接随后会被关闭
+            _ => self.token.cancel(),
+        }
+        res
+    }
+}
+
+impl<D> Drop for CancelOnCloseDecoder<D> {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
+// 把解码阶段放入 extensions 的取消信号暴露给 handler
+#[derive(Clone, Copy, Default)]
+pub struct CancellationLayer;
+
+impl<S> volo::Layer<S> for CancellationLayer {
+    type Service = CancellationService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        CancellationService { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct CancellationService<S> {
+    inner: S,
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for CancellationService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req> + Send + Sync + 'static,
+    Cx: Context + Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        match cx.extensions().get::<RequestCancellation>().cloned() {
+            Some(token) => CANCELLATION.scope(token, self.inner.call(cx, req)).await,
+            None => self.inner.call(cx, req).await,
+        }
+    }
+}
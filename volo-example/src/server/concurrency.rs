@@ -0,0 +1,81 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Semaphore;
+use volo_thrift::ServerError;
+
+use super::server_error;
+
+// 拒绝请求时返回的错误消息前缀
+pub const OVERLOADED: &str = "server overloaded";
+
+// 限制同时执行的 handler 数量，为服务端提供背压
+// 超出的请求默认立即以 ApplicationException 拒绝，客户端收到完整的 Thrift 异常而不是连接被断开；
+// 设置 queue_timeout 后先排队等待名额，超时仍未拿到再拒绝。需要按优先级排队时使用 PrioritySchedulerLayer
+#[derive(Clone)]
+pub struct ConcurrencyLimitLayer {
+    max_concurrent: usize,
+    queue_timeout: Option<Duration>,
+}
+
+impl ConcurrencyLimitLayer {
+    pub fn new(max_concurrent: usize) -> Self {
+        assert!(max_concurrent > 0, "max_concurrent must be positive");
+        Self {
+            max_concurrent,
+            queue_timeout: None,
+        }
+    }
+
+    pub fn queue_timeout(mut self, timeout: Duration) -> Self {
+        self.queue_timeout = Some(timeout);
+        self
+    }
+}
+
+impl<S> volo::Layer<S> for ConcurrencyLimitLayer {
+    type Service = ConcurrencyLimitService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        ConcurrencyLimitService {
+            inner,
+            semaphore: Arc::new(Semaphore::new(self.max_concurrent)),
+            max_concurrent: self.max_concurrent,
+            queue_timeout: self.queue_timeout,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ConcurrencyLimitService<S> {
+    inner: S,
+    semaphore: Arc<Semaphore>,
+    max_concurrent: usize,
+    queue_timeout: Option<Duration>,
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for ConcurrencyLimitService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req, Error = ServerError> + Send + Sync + 'static,
+    Cx: Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        // semaphore 不会被关闭
+        let permit = match self.queue_timeout {
+            None => self.semaphore.try_acquire().ok(),
+            Some(timeout) => tokio::time::timeout(timeout, self.semaphore.acquire())
+                .await
+                .ok()
+                .map(Result::unwrap),
+        };
+        let Some(_permit) = permit else {
+            return Err(server_error(format!(
+                "{}: more than {} concurrent requests",
+                OVERLOADED, self.max_concurrent
+            )));
+        };
+        self.inner.call(cx, req).await
+    }
+}
@@ -0,0 +1,152 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use volo::context::Context;
+use volo::net::Address;
+use volo_thrift::codec::{Decoder, MakeCodec};
+use volo_thrift::context::ThriftContext;
+use volo_thrift::{EntryMessage, ThriftMessage};
+
+// 连接级信息，在该连接上的第一个请求解码完成时构造
+#[derive(Clone, Debug)]
+pub struct ConnInfo {
+    pub peer: Address,
+}
+
+#[derive(Clone)]
+struct ConnContext(Arc<dyn Any + Send + Sync>);
+
+tokio::task_local! {
+    static CONN_CONTEXT: ConnContext;
+}
+
+// 在 handler 中读取当前请求所属连接的上下文
+pub fn conn_context<C: Send + Sync + 'static>() -> Option<Arc<C>> {
+    CONN_CONTEXT
+        .try_with(|cx| cx.0.clone())
+        .ok()
+        .and_then(|cx| cx.downcast::<C>().ok())
+}
+
+// 连接上下文由每条连接的 decoder 持有，在第一个请求解码后构造一次，同一连接上的所有请求共享同一个 Arc
+// 连接关闭时随 decoder 一起释放，不需要按地址查表或定期回收
+// 需要用 make_codec 包装服务端 codec，并加上 ConnContextLayer 把上下文交给 handler
+pub struct ConnContextInit<F> {
+    init: Arc<F>,
+}
+
+impl<F> ConnContextInit<F> {
+    pub fn on_new_connection(init: F) -> Self {
+        Self {
+            init: Arc::new(init),
+        }
+    }
+
+    pub fn make_codec<M>(&self, inner: M) -> ConnContextMakeCodec<M, F> {
+        ConnContextMakeCodec {
+            inner,
+            init: self.init.clone(),
+        }
+    }
+}
+
+pub struct ConnContextMakeCodec<M, F> {
+    inner: M,
+    init: Arc<F>,
+}
+
+impl<M: Clone, F> Clone for ConnContextMakeCodec<M, F> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            init: self.init.clone(),
+        }
+    }
+}
+
+impl<R, W, M, F, C> MakeCodec<R, W> for ConnContextMakeCodec<M, F>
+where
+    R: AsyncRead + Send + Sync + Unpin + 'static,
+    W: AsyncWrite + Send + Sync + Unpin + 'static,
+    M: MakeCodec<R, W>,
+    F: Fn(&ConnInfo) -> C + Send + Sync + 'static,
+    C: Send + Sync + 'static,
+{
+    type Encoder = M::Encoder;
+    type Decoder = ConnContextDecoder<M::Decoder, F>;
+
+    fn make_codec(&self, reader: R, writer: W) -> (Self::Encoder, Self::Decoder) {
+        let (encoder, decoder) = self.inner.make_codec(reader, writer);
+        let decoder = ConnContextDecoder {
+            inner: decoder,
+            init: self.init.clone(),
+            conn: None,
+        };
+        (encoder, decoder)
+    }
+}
+
+pub struct ConnContextDecoder<D, F> {
+    inner: D,
+    init: Arc<F>,
+    conn: Option<ConnContext>,
+}
+
+impl<D, F, C> Decoder for ConnContextDecoder<D, F>
+where
+    D: Decoder,
+    F: Fn(&ConnInfo) -> C + Send + Sync + 'static,
+    C: Send + Sync + 'static,
+{
+    async fn decode<Msg: Send + EntryMessage, Cx: ThriftContext>(
+        &mut self,
+        cx: &mut Cx,
+    ) -> Result<Option<ThriftMessage<Msg>>, volo_thrift::ThriftException> {
+        let res = self.inner.decode(cx).await;
+        if let Ok(Some(_)) = &res {
+            // 对端地址在解码前由框架写入 caller
+            if self.conn.is_none() {
+                if let Some(peer) = cx.rpc_info().caller().address() {
+                    self.conn = Some(ConnContext(Arc::new((self.init)(&ConnInfo { peer }))));
+                }
+            }
+            if let Some(conn) = &self.conn {
+                cx.extensions_mut().insert(conn.clone());
+            }
+        }
+        res
+    }
+}
+
+// 把解码阶段放入 extensions 的连接上下文暴露给 handler
+#[derive(Clone, Copy, Default)]
+pub struct ConnContextLayer;
+
+impl<S> volo::Layer<S> for ConnContextLayer {
+    type Service = ConnContextService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        ConnContextService { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct ConnContextService<S> {
+    inner: S,
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for ConnContextService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req> + Send + Sync + 'static,
+    Cx: Context + Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        match cx.extensions().get::<ConnContext>().cloned() {
+            Some(conn) => CONN_CONTEXT.scope(conn, self.inner.call(cx, req)).await,
+            None => self.inner.call(cx, req).await,
+        }
+    }
+}
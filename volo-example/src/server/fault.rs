@@ -0,0 +1,124 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use faststr::FastStr;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use volo::context::Context;
+use volo_thrift::ServerError;
+
+use super::server_error;
+
+// 针对某个方法的故障注入规则
+#[derive(Clone, Debug)]
+pub struct FaultRule {
+    pub method: FastStr,
+    // 命中时在调用 handler 前等待的时间
+    pub delay: Option<Duration>,
+    // 被选中的请求中，直接返回错误而不调用 handler 的比例
+    pub error_rate: f64,
+    // 被选中注入故障的请求比例
+    pub fraction: f64,
+}
+
+impl FaultRule {
+    pub fn new(method: impl Into<FastStr>) -> Self {
+        Self {
+            method: method.into(),
+            delay: None,
+            error_rate: 0.0,
+            fraction: 1.0,
+        }
+    }
+
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    pub fn error_rate(mut self, error_rate: f64) -> Self {
+        self.error_rate = error_rate;
+        self
+    }
+
+    pub fn fraction(mut self, fraction: f64) -> Self {
+        self.fraction = fraction;
+        self
+    }
+}
+
+// 混沌测试用的故障注入，仅在开启 fault-injection feature 时编译
+// 相同 seed 下的注入决策序列是确定的，便于复现
+#[derive(Clone)]
+pub struct FaultInjectionLayer {
+    rules: Arc<[FaultRule]>,
+    rng: Arc<Mutex<StdRng>>,
+}
+
+impl FaultInjectionLayer {
+    pub fn new(rules: impl IntoIterator<Item = FaultRule>, seed: u64) -> Self {
+        Self {
+            rules: rules.into_iter().collect(),
+            rng: Arc::new(Mutex::new(StdRng::seed_from_u64(seed))),
+        }
+    }
+}
+
+impl<S> volo::Layer<S> for FaultInjectionLayer {
+    type Service = FaultInjectionService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        FaultInjectionService { inner, layer: self }
+    }
+}
+
+#[derive(Clone)]
+pub struct FaultInjectionService<S> {
+    inner: S,
+    layer: FaultInjectionLayer,
+}
+
+enum Fault {
+    None,
+    Delay(Duration),
+    Error,
+}
+
+impl FaultInjectionLayer {
+    fn decide(&self, method: &str) -> Fault {
+        let Some(rule) = self.rules.iter().find(|r| r.method == method) else {
+            return Fault::None;
+        };
+        let mut rng = self.rng.lock().unwrap();
+        if !rng.gen_bool(rule.fraction.clamp(0.0, 1.0)) {
+            return Fault::None;
+        }
+        if rng.gen_bool(rule.error_rate.clamp(0.0, 1.0)) {
+            return Fault::Error;
+        }
+        match rule.delay {
+            Some(delay) => Fault::Delay(delay),
+            None => Fault::None,
+        }
+    }
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for FaultInjectionService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req, Error = ServerError> + Send + Sync + 'static,
+    Cx: Context + Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        let method = cx.rpc_info().method().clone();
+        match self.layer.decide(&method) {
+            Fault::None => (),
+            Fault::Delay(delay) => tokio::time::sleep(delay).await,
+            Fault::Error => {
+                return Err(server_error(format!("injected fault for {}", method)));
+            }
+        }
+        self.inner.call(cx, req).await
+    }
+}
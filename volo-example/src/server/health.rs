@@ -0,0 +1,66 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use volo_gen::volo::example::ServingStatus;
+
+type Readiness = Arc<dyn Fn() -> bool + Send + Sync>;
+
+// 服务健康状态：未配置 readiness 时始终 SERVING
+// 配置后由回调判断下游依赖（如数据库）是否就绪，结果缓存 cache_ttl 避免频繁探测
+// 启动阶段依赖未就绪时服务照常接受连接，但健康检查返回 NOT_SERVING，负载均衡会暂缓导流
+#[derive(Clone)]
+pub struct Health {
+    readiness: Option<Readiness>,
+    cache_ttl: Duration,
+    cached: Arc<Mutex<Option<(Instant, bool)>>>,
+}
+
+impl Health {
+    pub fn new() -> Self {
+        Self {
+            readiness: None,
+            cache_ttl: Duration::from_secs(1),
+            cached: Default::default(),
+        }
+    }
+
+    pub fn readiness(mut self, f: impl Fn() -> bool + Send + Sync + 'static) -> Self {
+        self.readiness = Some(Arc::new(f));
+        self
+    }
+
+    pub fn cache_ttl(mut self, cache_ttl: Duration) -> Self {
+        self.cache_ttl = cache_ttl;
+        self
+    }
+
+    pub fn status(&self) -> ServingStatus {
+        if self.is_ready() {
+            ServingStatus::SERVING
+        } else {
+            ServingStatus::NOT_SERVING
+        }
+    }
+
+    fn is_ready(&self) -> bool {
+        let Some(readiness) = &self.readiness else {
+            return true;
+        };
+
+        let mut cached = self.cached.lock().unwrap();
+        if let Some((at, ready)) = *cached {
+            if at.elapsed() < self.cache_ttl {
+                return ready;
+            }
+        }
+        let ready = readiness();
+        *cached = Some((Instant::now(), ready));
+        ready
+    }
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self::new()
+    }
+}
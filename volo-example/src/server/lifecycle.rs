@@ -0,0 +1,149 @@
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use tokio::io::{AsyncRead, AsyncWrite};
+use volo::net::incoming::MakeIncoming;
+use volo_thrift::codec::{Decoder, MakeCodec};
+use volo_thrift::context::ThriftContext;
+use volo_thrift::{EntryMessage, ThriftMessage};
+
+// 服务端生命周期事件
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ServerEvent {
+    // 已在该地址上开始监听，监听失败时不会触发
+    Bound(SocketAddr),
+    // 收到退出信号，在关闭连接之前触发，适合在此从服务发现中摘除
+    Draining,
+    // 所有连接都已处理完毕，run 返回
+    Stopped,
+    // 连接 id 在进程内递增，同一条连接的 accepted 与 closed 使用相同的 id
+    ConnectionAccepted(u64),
+    ConnectionClosed(u64),
+}
+
+type OnEvent = Arc<dyn Fn(ServerEvent) + Send + Sync>;
+
+// 以类型化回调的方式向控制面暴露生命周期事件，区别于日志
+// 连接事件需要用 make_codec 包装服务端 codec；Bound 需要用 make_incoming 包装传给 run 的地址；Draining 需要注册 shutdown_hook
+#[derive(Clone, Default)]
+pub struct ServerEvents {
+    handlers: Vec<OnEvent>,
+    next_conn_id: Arc<AtomicU64>,
+}
+
+impl ServerEvents {
+    pub fn on_event(mut self, f: impl Fn(ServerEvent) + Send + Sync + 'static) -> Self {
+        self.handlers.push(Arc::new(f));
+        self
+    }
+
+    pub fn emit(&self, event: ServerEvent) {
+        for handler in &self.handlers {
+            handler(event.clone());
+        }
+    }
+
+    pub fn make_codec<M>(&self, inner: M) -> LifecycleMakeCodec<M> {
+        LifecycleMakeCodec {
+            inner,
+            events: self.clone(),
+        }
+    }
+
+    // 代替地址传给 Server::run，监听成功后才触发 Bound：
+    //     server.run(events.make_incoming(addr, volo::net::Address::from(addr)))
+    pub fn make_incoming<M>(&self, addr: SocketAddr, inner: M) -> LifecycleMakeIncoming<M> {
+        LifecycleMakeIncoming {
+            inner,
+            addr,
+            events: self.clone(),
+        }
+    }
+
+    // 交给 Server::register_shutdown_hook，收到退出信号后、关闭连接前执行
+    pub fn shutdown_hook(&self) -> impl FnOnce() -> BoxFuture<'static, ()> + Send + 'static {
+        let events = self.clone();
+        move || {
+            Box::pin(async move {
+                events.emit(ServerEvent::Draining);
+            })
+        }
+    }
+}
+
+impl fmt::Debug for ServerEvents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServerEvents")
+            .field("handlers", &self.handlers.len())
+            .finish()
+    }
+}
+
+pub struct LifecycleMakeIncoming<M> {
+    inner: M,
+    addr: SocketAddr,
+    events: ServerEvents,
+}
+
+impl<M: MakeIncoming + Send> MakeIncoming for LifecycleMakeIncoming<M> {
+    type Incoming = M::Incoming;
+
+    async fn make_incoming(self) -> std::io::Result<M::Incoming> {
+        let incoming = self.inner.make_incoming().await?;
+        self.events.emit(ServerEvent::Bound(self.addr));
+        Ok(incoming)
+    }
+}
+
+#[derive(Clone)]
+pub struct LifecycleMakeCodec<M> {
+    inner: M,
+    events: ServerEvents,
+}
+
+impl<R, W, M> MakeCodec<R, W> for LifecycleMakeCodec<M>
+where
+    R: AsyncRead + Send + Sync + Unpin + 'static,
+    W: AsyncWrite + Send + Sync + Unpin + 'static,
+    M: MakeCodec<R, W>,
+{
+    type Encoder = M::Encoder;
+    type Decoder = LifecycleDecoder<M::Decoder>;
+
+    fn make_codec(&self, reader: R, writer: W) -> (Self::Encoder, Self::Decoder) {
+        let (encoder, decoder) = self.inner.make_codec(reader, writer);
+        let conn_id = self.events.next_conn_id.fetch_add(1, Ordering::Relaxed);
+        self.events.emit(ServerEvent::ConnectionAccepted(conn_id));
+        let decoder = LifecycleDecoder {
+            inner: decoder,
+            events: self.events.clone(),
+            conn_id,
+        };
+        (encoder, decoder)
+    }
+}
+
+// 连接关闭时解码器随之释放
+pub struct LifecycleDecoder<D> {
+    inner: D,
+    events: ServerEvents,
+    conn_id: u64,
+}
+
+impl<D: Decoder> Decoder for LifecycleDecoder<D> {
+    async fn decode<Msg: Send + EntryMessage, Cx: ThriftContext>(
+        &mut self,
+        cx: &mut Cx,
+    ) -> Result<Option<ThriftMessage<Msg>>, volo_thrift::ThriftException> {
+        self.inner.decode(cx).await
+    }
+}
+
+impl<D> Drop for LifecycleDecoder<D> {
+    fn drop(&mut self) {
+        self.events.emit(ServerEvent::ConnectionClosed(self.conn_id));
+    }
+}
@@ -0,0 +1,27 @@
+use volo_thrift::codec::default::framed::MakeFramedCodec;
+use volo_thrift::codec::default::thrift::MakeThriftCodec;
+use volo_thrift::codec::default::ttheader::MakeTTHeaderCodec;
+use volo_thrift::codec::default::DefaultMakeCodec;
+
+use super::MaxStringSizeMakeCodec;
+
+// 请求中单个字符串字段的上限
+pub const MAX_STRING_SIZE: usize = 1 << 20;
+
+// 单个请求帧的上限，framed 长度前缀超出时不分配缓冲区，直接以协议错误关闭连接
+// volo-thrift 默认是 16 MiB；ItemService 的请求只有少量字段，字符串又已限制在 MAX_STRING_SIZE，4 MiB 足够
+pub const MAX_FRAME_SIZE: usize = 4 << 20;
+
+pub type LimitedMakeCodec =
+    DefaultMakeCodec<MakeTTHeaderCodec<MakeFramedCodec<MaxStringSizeMakeCodec<MakeThriftCodec>>>>;
+
+// 服务端使用的请求 codec：TTHeader/framed 之上的 Binary 协议，帧大小与字符串长度都受限
+pub fn limited_make_codec() -> LimitedMakeCodec {
+    DefaultMakeCodec::new(MakeTTHeaderCodec::new(
+        MakeFramedCodec::new(MaxStringSizeMakeCodec::new(
+            MakeThriftCodec::new(),
+            MAX_STRING_SIZE,
+        ))
+        .with_max_frame_size(MAX_FRAME_SIZE as i32),
+    ))
+}
@@ -0,0 +1,22 @@
+use faststr::FastStr;
+use metainfo::{Backward, Forward, METAINFO};
+
+// 读取当前请求中的 header，依次查找 upstream、transient 与 persistent
+// 客户端设置的 transient header 到达服务端后位于 upstream 中，transient 只含本进程 layer 写入的值
+// 只能在 handler 或服务端 layer 中调用
+pub fn metadata(key: &str) -> Option<FastStr> {
+    METAINFO
+        .try_with(|mi| {
+            let mi = mi.borrow();
+            mi.get_upstream(key)
+                .or_else(|| mi.get_transient(key))
+                .or_else(|| mi.get_persistent(key))
+        })
+        .ok()
+        .flatten()
+}
+
+// 设置回包 header，客户端在 METAINFO.scope 内调用后通过 response_metadata 读取
+pub fn set_response_metadata(key: impl Into<FastStr>, value: impl Into<FastStr>) {
+    let _ = METAINFO.try_with(|mi| mi.borrow_mut().set_backward_transient(key, value));
+}
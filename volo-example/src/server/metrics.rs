@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use faststr::FastStr;
+use pilota::thrift::ApplicationExceptionKind;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use volo::context::Context;
+use volo_thrift::ServerError;
+
+// 延迟直方图的桶上界，单位秒
+const LATENCY_BUCKETS: [f64; 8] = [0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0];
+
+// 错误按异常类型分别计数，标签取值见 error_kind
+const EXCEPTION_KINDS: [(ApplicationExceptionKind, &str); 11] = [
+    (ApplicationExceptionKind::UNKNOWN, "unknown"),
+    (ApplicationExceptionKind::UNKNOWN_METHOD, "unknown_method"),
+    (ApplicationExceptionKind::INVALID_MESSAGE_TYPE, "invalid_message_type"),
+    (ApplicationExceptionKind::WRONG_METHOD_NAME, "wrong_method_name"),
+    (ApplicationExceptionKind::BAD_SEQUENCE_ID, "bad_sequence_id"),
+    (ApplicationExceptionKind::MISSING_RESULT, "missing_result"),
+    (ApplicationExceptionKind::INTERNAL_ERROR, "internal_error"),
+    (ApplicationExceptionKind::PROTOCOL_ERROR, "protocol_error"),
+    (ApplicationExceptionKind::INVALID_TRANSFORM, "invalid_transform"),
+    (ApplicationExceptionKind::INVALID_PROTOCOL, "invalid_protocol"),
+    (ApplicationExceptionKind::UNSUPPORTED_CLIENT_TYPE, "unsupported_client_type"),
+];
+
+fn error_kind(err: &ServerError) -> &'static str {
+    match err {
+        ServerError::Application(e) => EXCEPTION_KINDS
+            .iter()
+            .find(|(kind, _)| *kind == e.kind())
+            .map_or("unknown", |(_, name)| name),
+        ServerError::Biz(_) => "biz",
+    }
+}
+
+#[derive(Debug, Default)]
+struct MethodMetrics {
+    requests: u64,
+    errors: BTreeMap<&'static str, u64>,
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    latency_sum: f64,
+}
+
+// 按方法统计的请求数、错误数与延迟分布
+#[derive(Clone, Default)]
+pub struct Metrics {
+    methods: Arc<Mutex<BTreeMap<FastStr, MethodMetrics>>>,
+}
+
+impl Metrics {
+    pub fn layer(&self) -> MetricsLayer {
+        MetricsLayer {
+            metrics: self.clone(),
+        }
+    }
+
+    fn record(&self, method: &FastStr, latency: f64, error: Option<&'static str>) {
+        let mut methods = self.methods.lock().unwrap();
+        let m = methods.entry(method.clone()).or_default();
+        m.requests += 1;
+        if let Some(kind) = error {
+            *m.errors.entry(kind).or_default() += 1;
+        }
+        m.latency_sum += latency;
+        for (bucket, le) in m.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if latency <= le {
+                *bucket += 1;
+            }
+        }
+    }
+
+    // Prometheus 文本格式（0.0.4）；openmetrics 为 true 时输出 OpenMetrics 格式，
+    // 两者只在 counter 的 TYPE 行与结尾的 # EOF 上不同
+    pub fn render(&self, openmetrics: bool) -> String {
+        let methods = self.methods.lock().unwrap();
+        let mut out = String::new();
+        let counter_suffix = if openmetrics { "" } else { "_total" };
+
+        let _ = writeln!(out, "# TYPE rpc_requests{} counter", counter_suffix);
+        for (method, m) in methods.iter() {
+            let _ = writeln!(out, "rpc_requests_total{{method=\"{}\"}} {}", method, m.requests);
+        }
+        let _ = writeln!(out, "# TYPE rpc_errors{} counter", counter_suffix);
+        for (method, m) in methods.iter() {
+            for (kind, count) in &m.errors {
+                let _ = writeln!(
+                    out,
+                    "rpc_errors_total{{method=\"{}\",kind=\"{}\"}} {}",
+                    method, kind, count
+                );
+            }
+        }
+        out.push_str("# TYPE rpc_latency_seconds histogram\n");
+        for (method, m) in methods.iter() {
+            for (count, le) in m.buckets.iter().zip(LATENCY_BUCKETS) {
+                let _ = writeln!(
+                    out,
+                    "rpc_latency_seconds_bucket{{method=\"{}\",le=\"{}\"}} {}",
+                    method, le, count
+                );
+            }
+            let _ = writeln!(
+                out,
+                "rpc_latency_seconds_bucket{{method=\"{}\",le=\"+Inf\"}} {}",
+                method, m.requests
+            );
+            let _ = writeln!(out, "rpc_latency_seconds_sum{{method=\"{}\"}} {}", method, m.latency_sum);
+            let _ = writeln!(out, "rpc_latency_seconds_count{{method=\"{}\"}} {}", method, m.requests);
+        }
+        if openmetrics {
+            out.push_str("# EOF\n");
+        }
+        out
+    }
+
+    // 在独立端口上提供 metrics，与 Thrift 监听互不影响
+    // 只处理最简单的 GET 请求，每个连接回一次后关闭；Accept 中声明 OpenMetrics 时按 OpenMetrics 格式返回
+    pub async fn serve(self, addr: SocketAddr) -> std::io::Result<()> {
+        let listener = TcpListener::bind(addr).await?;
+        loop {
+            let (mut stream, _) = listener.accept().await?;
+            let metrics = self.clone();
+            tokio::spawn(async move {
+                let mut buf = [0u8; 1024];
+                let n = stream.read(&mut buf).await.unwrap_or(0);
+                let openmetrics = String::from_utf8_lossy(&buf[..n])
+                    .to_ascii_lowercase()
+                    .contains("application/openmetrics-text");
+                let body = metrics.render(openmetrics);
+                let content_type = if openmetrics {
+                    "application/openmetrics-text; version=1.0.0; charset=utf-8"
+                } else {
+                    "text/plain; version=0.0.4; charset=utf-8"
+                };
+                let resp = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    content_type,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(resp.as_bytes()).await;
+            });
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MetricsLayer {
+    metrics: Metrics,
+}
+
+impl<S> volo::Layer<S> for MetricsLayer {
+    type Service = MetricsService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        MetricsService {
+            inner,
+            metrics: self.metrics,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MetricsService<S> {
+    inner: S,
+    metrics: Metrics,
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for MetricsService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req, Error = ServerError> + Send + Sync + 'static,
+    Cx: Context + Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        let method = cx.rpc_info().method().clone();
+        let start = Instant::now();
+        let resp = self.inner.call(cx, req).await;
+        let error = resp.as_ref().err().map(error_kind);
+        self.metrics
+            .record(&method, start.elapsed().as_secs_f64(), error);
+        resp
+    }
+}
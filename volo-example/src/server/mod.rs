@@ -0,0 +1,64 @@
+mod audit;
+mod cancel;
+mod concurrency;
+mod conn_context;
+#[cfg(feature = "fault-injection")]
+mod fault;
+mod health;
+mod lifecycle;
+mod limits;
+mod metadata;
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "otel")]
+mod otel;
+mod panic;
+mod priority;
+mod shutdown;
+mod streaming;
+mod string_limit;
+mod timing;
+mod tls;
+mod warnings;
+
+pub use audit::{AuditLayer, AuditRecord, AuditService, AuditSink, TracingAuditSink};
+pub use cancel::{
+    cancellation, CancelOnCloseDecoder, CancelOnCloseMakeCodec, CancellationLayer,
+    CancellationService, ReadAheadReader, RequestCancellation,
+};
+pub use concurrency::{ConcurrencyLimitLayer, ConcurrencyLimitService, OVERLOADED};
+pub use conn_context::{
+    conn_context, ConnContextDecoder, ConnContextInit, ConnContextLayer, ConnContextMakeCodec,
+    ConnContextService, ConnInfo,
+};
+#[cfg(feature = "fault-injection")]
+pub use fault::{FaultInjectionLayer, FaultInjectionService, FaultRule};
+pub use health::Health;
+pub use lifecycle::{
+    LifecycleDecoder, LifecycleMakeCodec, LifecycleMakeIncoming, ServerEvent, ServerEvents,
+};
+pub use limits::{limited_make_codec, LimitedMakeCodec, MAX_FRAME_SIZE, MAX_STRING_SIZE};
+pub use metadata::{metadata, set_response_metadata};
+#[cfg(feature = "metrics")]
+pub use metrics::{Metrics, MetricsLayer, MetricsService};
+#[cfg(feature = "otel")]
+pub use otel::{OtelServerLayer, OtelServerService};
+pub use panic::{PanicIsolationLayer, PanicIsolationService, DEFAULT_TRACE_ID_HEADER};
+pub use priority::{PrioritySchedulerLayer, PrioritySchedulerService};
+pub use shutdown::{Drain, DrainLayer, DrainService};
+pub use streaming::{FramingProbe, SharedWriter, StreamingEncoder, StreamingMakeCodec};
+pub use string_limit::{MaxStringSizeDecoder, MaxStringSizeMakeCodec};
+pub use timing::{ProcessingTimeLayer, ProcessingTimeService, PROCESSING_TIME_HEADER};
+pub use tls::{CertReloader, TlsAddress, TlsIncoming};
+pub use warnings::{add_warning, WARNINGS_HEADER};
+
+use pilota::thrift::{ApplicationException, ApplicationExceptionKind};
+use volo_thrift::ServerError;
+
+// 服务端框架层产生的错误，以 INTERNAL_ERROR 的 ApplicationException 返回给客户端
+pub(crate) fn server_error(msg: impl Into<String>) -> ServerError {
+    ServerError::Application(ApplicationException::new(
+        ApplicationExceptionKind::INTERNAL_ERROR,
+        msg.into(),
+    ))
+}
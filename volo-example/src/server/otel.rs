@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use opentelemetry::global::{self, BoxedTracer};
+use opentelemetry::propagation::TextMapPropagator;
+use opentelemetry::trace::{FutureExt, SpanKind, Status, TraceContextExt, Tracer};
+use opentelemetry::KeyValue;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use volo::context::Context;
+use volo_thrift::ServerError;
+
+use super::metadata;
+
+// 为每次 handler 调用创建一个 server span，父 span 取自请求 header 中的 W3C trace context（traceparent）
+// span 记录方法名与结果，起止时间即处理耗时；tracer 取自 global::set_tracer_provider 安装的 provider
+#[derive(Clone)]
+pub struct OtelServerLayer {
+    tracer: Arc<BoxedTracer>,
+}
+
+impl Default for OtelServerLayer {
+    fn default() -> Self {
+        Self {
+            tracer: Arc::new(global::tracer("volo-example")),
+        }
+    }
+}
+
+impl<S> volo::Layer<S> for OtelServerLayer {
+    type Service = OtelServerService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        OtelServerService {
+            inner,
+            tracer: self.tracer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct OtelServerService<S> {
+    inner: S,
+    tracer: Arc<BoxedTracer>,
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for OtelServerService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req, Error = ServerError> + Send + Sync + 'static,
+    Cx: Context + Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        let propagator = TraceContextPropagator::new();
+        let carrier: HashMap<String, String> = propagator
+            .fields()
+            .filter_map(|key| metadata(key).map(|value| (key.to_string(), value.to_string())))
+            .collect();
+        let parent = propagator.extract(&carrier);
+
+        let method = cx.rpc_info().method().to_string();
+        let span = self
+            .tracer
+            .span_builder(method.clone())
+            .with_kind(SpanKind::Server)
+            .with_attributes([
+                KeyValue::new("rpc.system", "thrift"),
+                KeyValue::new("rpc.method", method),
+            ])
+            .start_with_context(self.tracer.as_ref(), &parent);
+        let trace_cx = parent.with_span(span);
+
+        let result = self.inner.call(cx, req).with_context(trace_cx.clone()).await;
+        let span = trace_cx.span();
+        match &result {
+            Ok(_) => span.set_status(Status::Ok),
+            Err(e) => span.set_status(Status::error(e.to_string())),
+        }
+        span.end();
+        result
+    }
+}
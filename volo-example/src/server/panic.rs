@@ -0,0 +1,141 @@
+use std::any::Any;
+use std::panic::AssertUnwindSafe;
+
+use faststr::FastStr;
+use futures::FutureExt;
+use tracing::Level;
+use volo_thrift::context::ThriftContext;
+use volo_thrift::ServerError;
+
+use super::{metadata, server_error};
+
+// 默认从该 THeader 键读取 trace id，与日志关联
+pub const DEFAULT_TRACE_ID_HEADER: &str = "x-trace-id";
+
+// 捕获 handler 的 panic 并以 INTERNAL_ERROR 返回，避免整条连接被断开
+// panic 和 handler 返回的错误都会输出一条带方法、seq id、调用方与 trace id 的结构化日志
+#[derive(Clone)]
+pub struct PanicIsolationLayer {
+    level: Level,
+    trace_header: FastStr,
+}
+
+impl Default for PanicIsolationLayer {
+    fn default() -> Self {
+        Self {
+            level: Level::ERROR,
+            trace_header: FastStr::from_static_str(DEFAULT_TRACE_ID_HEADER),
+        }
+    }
+}
+
+impl PanicIsolationLayer {
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn trace_header(mut self, header: impl Into<FastStr>) -> Self {
+        self.trace_header = header.into();
+        self
+    }
+}
+
+impl<S> volo::Layer<S> for PanicIsolationLayer {
+    type Service = PanicIsolationService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        PanicIsolationService {
+            inner,
+            level: self.level,
+            trace_header: self.trace_header,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PanicIsolationService<S> {
+    inner: S,
+    level: Level,
+    trace_header: FastStr,
+}
+
+// 一次失败调用的上下文，在调用 handler 之前取好
+struct FailureContext {
+    method: FastStr,
+    seq_id: i32,
+    caller: String,
+    trace_id: String,
+}
+
+impl FailureContext {
+    fn log(&self, level: Level, kind: &str, error: &str) {
+        // tracing 的宏要求 level 为常量，只能逐个展开
+        macro_rules! emit {
+            ($level:expr) => {
+                tracing::event!(
+                    $level,
+                    method = %self.method,
+                    seq_id = self.seq_id,
+                    caller = %self.caller,
+                    trace_id = %self.trace_id,
+                    kind,
+                    error,
+                    "rpc failed",
+                )
+            };
+        }
+        match level {
+            Level::TRACE => emit!(Level::TRACE),
+            Level::DEBUG => emit!(Level::DEBUG),
+            Level::INFO => emit!(Level::INFO),
+            Level::WARN => emit!(Level::WARN),
+            _ => emit!(Level::ERROR),
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("<non-string panic payload>")
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for PanicIsolationService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req, Error = ServerError> + Send + Sync + 'static,
+    Cx: ThriftContext,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        let failure = FailureContext {
+            method: cx.rpc_info().method().clone(),
+            seq_id: cx.seq_id(),
+            caller: cx
+                .rpc_info()
+                .caller()
+                .address()
+                .map(|addr| addr.to_string())
+                .unwrap_or_default(),
+            trace_id: metadata(&self.trace_header)
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+        };
+
+        match AssertUnwindSafe(self.inner.call(cx, req)).catch_unwind().await {
+            Ok(Ok(resp)) => Ok(resp),
+            Ok(Err(e)) => {
+                failure.log(self.level, "error", &e.to_string());
+                Err(e)
+            }
+            Err(payload) => {
+                let msg = panic_message(payload.as_ref());
+                failure.log(self.level, "panic", msg);
+                Err(server_error(format!("handler panicked: {}", msg)))
+            }
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::{Arc, Mutex};
+
+use metainfo::{Forward, METAINFO};
+use tokio::sync::oneshot;
+
+use crate::priority::{Priority, PRIORITY_HEADER};
+
+// 排队中的请求：优先级高的先出队，同级时序号小的先出队
+type Waiter = (Priority, Reverse<u64>, WaiterSender);
+
+struct WaiterSender(oneshot::Sender<()>);
+
+impl PartialEq for WaiterSender {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for WaiterSender {}
+
+impl PartialOrd for WaiterSender {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for WaiterSender {
+    fn cmp(&self, _: &Self) -> std::cmp::Ordering {
+        std::cmp::Ordering::Equal
+    }
+}
+
+struct State {
+    available: usize,
+    next_seq: u64,
+    waiters: BinaryHeap<Waiter>,
+}
+
+// 按优先级排队的并发限制：同时最多执行 max_concurrency 个 handler
+// 只影响排队顺序，不会打断已经在执行的低优先级请求
+#[derive(Clone)]
+pub struct PrioritySchedulerLayer {
+    max_concurrency: usize,
+}
+
+impl PrioritySchedulerLayer {
+    pub fn new(max_concurrency: usize) -> Self {
+        assert!(max_concurrency > 0, "max_concurrency must be positive");
+        Self { max_concurrency }
+    }
+}
+
+impl<S> volo::Layer<S> for PrioritySchedulerLayer {
+    type Service = PrioritySchedulerService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        PrioritySchedulerService {
+            inner,
+            state: Arc::new(Mutex::new(State {
+                available: self.max_concurrency,
+                next_seq: 0,
+                waiters: BinaryHeap::new(),
+            })),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PrioritySchedulerService<S> {
+    inner: S,
+    state: Arc<Mutex<State>>,
+}
+
+// 归还一个并发名额，直接交给优先级最高的等待者
+fn release(state: &Mutex<State>) {
+    let mut state = state.lock().unwrap();
+    // 等待者可能已经取消，跳过这些已关闭的通道
+    while let Some((_, _, WaiterSender(tx))) = state.waiters.pop() {
+        if tx.send(()).is_ok() {
+            return;
+        }
+    }
+    state.available += 1;
+}
+
+// 持有期间占用一个并发名额
+struct Permit {
+    state: Arc<Mutex<State>>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        release(&self.state);
+    }
+}
+
+// 排队中的请求被取消时，如果名额已经交到手上则转交出去
+struct Waiting {
+    rx: oneshot::Receiver<()>,
+    state: Arc<Mutex<State>>,
+}
+
+impl Drop for Waiting {
+    fn drop(&mut self) {
+        self.rx.close();
+        if self.rx.try_recv().is_ok() {
+            release(&self.state);
+        }
+    }
+}
+
+impl<S> PrioritySchedulerService<S> {
+    async fn acquire(&self, priority: Priority) -> Permit {
+        let waiting = {
+            let mut state = self.state.lock().unwrap();
+            if state.available > 0 {
+                state.available -= 1;
+                None
+            } else {
+                let (tx, rx) = oneshot::channel();
+                let seq = state.next_seq;
+                state.next_seq += 1;
+                state.waiters.push((priority, Reverse(seq), WaiterSender(tx)));
+                Some(Waiting {
+                    rx,
+                    state: self.state.clone(),
+                })
+            }
+        };
+        if let Some(mut waiting) = waiting {
+            // 发送方只会在交出名额时发送，不会在此之前被丢弃
+            let _ = (&mut waiting.rx).await;
+        }
+        Permit {
+            state: self.state.clone(),
+        }
+    }
+}
+
+// 读取请求中的优先级，缺省或无法识别时按 Normal 处理
+// 客户端写入的 transient header 到达服务端后位于 upstream 中
+fn request_priority() -> Priority {
+    METAINFO
+        .try_with(|mi| {
+            mi.borrow()
+                .get_upstream(PRIORITY_HEADER)
+                .and_then(|v| v.parse().ok())
+        })
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for PrioritySchedulerService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req> + Send + Sync + 'static,
+    Cx: Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        let _permit = self.acquire(request_priority()).await;
+        self.inner.call(cx, req).await
+    }
+}
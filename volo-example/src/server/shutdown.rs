@@ -0,0 +1,124 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Notify;
+use volo_thrift::ServerError;
+
+use super::server_error;
+
+// 由调用方提供的退出信号触发的优雅退出，用于滚动发布：
+//     let drain = Drain::default();
+//     let server = ItemServiceServer::new(S::default()).layer_front(drain.layer());
+//     drain.run_with_shutdown(server.run(addr), signal, Duration::from_secs(30)).await;
+// 信号触发后不再 accept 新连接，已建立连接上的新请求直接返回错误，等在途的 handler 执行完或超过 grace 后返回
+#[derive(Clone, Default)]
+pub struct Drain {
+    state: Arc<DrainState>,
+}
+
+#[derive(Default)]
+struct DrainState {
+    draining: AtomicBool,
+    in_flight: AtomicUsize,
+    idle: Notify,
+}
+
+impl Drain {
+    // 通过 layer_front 安装在最外层，统计全部在途请求
+    pub fn layer(&self) -> DrainLayer {
+        DrainLayer {
+            drain: self.clone(),
+        }
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.state.in_flight.load(Ordering::Acquire)
+    }
+
+    // serve 为 Server::run 返回的 future；serve 先于信号结束时直接返回其结果
+    // 信号触发后丢弃 serve 停止 accept，已在处理中的连接由各自的任务继续执行
+    pub async fn run_with_shutdown<F: Future>(
+        &self,
+        serve: F,
+        signal: impl Future<Output = ()>,
+        grace: Duration,
+    ) -> Option<F::Output> {
+        tokio::select! {
+            out = serve => return Some(out),
+            _ = signal => {}
+        }
+        self.state.draining.store(true, Ordering::Release);
+        tracing::info!("shutting down, waiting for {} in-flight requests", self.in_flight());
+        if tokio::time::timeout(grace, self.wait_idle()).await.is_err() {
+            tracing::warn!(
+                "grace period {:?} elapsed with {} requests still in flight",
+                grace,
+                self.in_flight()
+            );
+        }
+        None
+    }
+
+    async fn wait_idle(&self) {
+        loop {
+            let idle = self.state.idle.notified();
+            if self.in_flight() == 0 {
+                return;
+            }
+            idle.await;
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DrainLayer {
+    drain: Drain,
+}
+
+impl<S> volo::Layer<S> for DrainLayer {
+    type Service = DrainService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        DrainService {
+            inner,
+            drain: self.drain,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DrainService<S> {
+    inner: S,
+    drain: Drain,
+}
+
+// handler 结束（包括被取消）时减少计数
+struct InFlight<'a>(&'a DrainState);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        if self.0.in_flight.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for DrainService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req, Error = ServerError> + Send + Sync + 'static,
+    Cx: Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        let state = &self.drain.state;
+        state.in_flight.fetch_add(1, Ordering::AcqRel);
+        let _in_flight = InFlight(state);
+        if state.draining.load(Ordering::Acquire) {
+            return Err(server_error("server is shutting down"));
+        }
+        self.inner.call(cx, req).await
+    }
+}
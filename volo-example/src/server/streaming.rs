@@ -0,0 +1,448 @@
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use bytes::{Bytes, BytesMut};
+use faststr::FastStr;
+use metainfo::{Backward, METAINFO, RPC_PREFIX_BACKWARD};
+use pilota::thrift::binary::TBinaryProtocol;
+use pilota::thrift::{
+    ProtocolException, ProtocolExceptionKind, TLengthProtocol, TListIdentifier, TMapIdentifier,
+    TMessageIdentifier, TOutputProtocol, TSetIdentifier, TStructIdentifier, TType,
+};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, Interest, ReadBuf, Ready};
+use tokio::runtime::{Handle, RuntimeFlavor};
+use volo::net::ready::AsyncReady;
+use volo_thrift::codec::{Encoder, MakeCodec};
+use volo_thrift::context::ThriftContext;
+use volo_thrift::{EntryMessage, ThriftException, ThriftMessage};
+
+const DEFAULT_THRESHOLD: usize = 1 << 20;
+const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+// 与 volo-thrift 客户端默认的帧大小上限一致，更大的响应客户端收到后也会拒绝
+const DEFAULT_MAX_FRAME_SIZE: usize = 16 << 20;
+
+// 大响应边编码边写出：Binary 编码结果每攒满 chunk_size 就写到连接，编码缓冲的峰值与响应大小无关
+// 默认 encoder 会把整条响应编码进一个缓冲区再写出，返回大量 Item 时这份缓冲与响应本身一样大
+// framed 与 TTHeader 都要在帧头写出整帧长度，因此先用同一个 encoder 只计数不保留地编码一遍算出长度，再编码写出
+// 编码结果不小于 threshold 时才走这条路径，更小的响应与异常仍交给内层 codec
+// 传输方式取自该连接上第一个请求：TTHeader 回 TTHeader（带上 backward transient header，如 x-warnings），
+// framed 回 framed；其它传输与协议（buffered、Compact）始终交给内层 codec
+// 同步的 Thrift 编码中写连接需要 block_in_place，只在多线程 runtime 上生效，current_thread runtime 下也交给内层 codec
+#[derive(Clone)]
+pub struct StreamingMakeCodec<M> {
+    inner: M,
+    threshold: usize,
+    chunk_size: usize,
+    max_frame_size: usize,
+}
+
+impl<M> StreamingMakeCodec<M> {
+    pub fn new(inner: M) -> Self {
+        Self {
+            inner,
+            threshold: DEFAULT_THRESHOLD,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+        }
+    }
+
+    pub fn threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    // 分块只限制服务端的编码缓冲，线上仍是一整帧，客户端要收下整帧才能解码
+    // 帧长超过该值时不写出任何数据，以 SizeLimit 协议错误结束调用；应与客户端的帧大小上限一致
+    pub fn max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.max_frame_size = max_frame_size;
+        self
+    }
+}
+
+impl<R, W, M> MakeCodec<R, W> for StreamingMakeCodec<M>
+where
+    R: AsyncRead + Send + Sync + Unpin + 'static,
+    W: AsyncWrite + Send + Sync + Unpin + 'static,
+    M: MakeCodec<FramingProbe<R>, SharedWriter<W>>,
+{
+    type Encoder = StreamingEncoder<M::Encoder, W>;
+    type Decoder = M::Decoder;
+
+    fn make_codec(&self, reader: R, writer: W) -> (Self::Encoder, Self::Decoder) {
+        let transport = Arc::new(AtomicU8::new(framing::UNKNOWN));
+        let writer = SharedWriter(Arc::new(Mutex::new(writer)));
+        let reader = FramingProbe {
+            inner: reader,
+            head: Vec::new(),
+            framing: transport.clone(),
+        };
+        let (encoder, decoder) = self.inner.make_codec(reader, writer.clone());
+        let encoder = StreamingEncoder {
+            inner: encoder,
+            writer,
+            framing: transport,
+            threshold: self.threshold,
+            chunk_size: self.chunk_size,
+            max_frame_size: self.max_frame_size,
+        };
+        (encoder, decoder)
+    }
+}
+
+// 连接的传输方式，由第一个请求的帧头判断
+mod framing {
+    pub const UNKNOWN: u8 = 0;
+    pub const TTHEADER: u8 = 1;
+    pub const FRAMED: u8 = 2;
+    // buffered 传输或非 Binary 协议，不分块写出
+    pub const OTHER: u8 = 3;
+
+    // 帧长之后是 TTHeader magic 或 Binary 协议的版本号
+    pub fn detect(head: &[u8]) -> u8 {
+        match head[4..6] {
+            [0x10, 0x00] => TTHEADER,
+            [0x80, 0x01] => FRAMED,
+            _ => OTHER,
+        }
+    }
+}
+
+// 读取第一个请求的前 6 个字节判断连接的传输方式，数据原样交给 decoder
+pub struct FramingProbe<R> {
+    inner: R,
+    head: Vec<u8>,
+    framing: Arc<AtomicU8>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for FramingProbe<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let before = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = poll {
+            if self.head.len() < 6 {
+                let n = (6 - self.head.len()).min(buf.filled().len() - before);
+                self.head
+                    .extend_from_slice(&buf.filled()[before..before + n]);
+                if self.head.len() == 6 {
+                    self.framing
+                        .store(framing::detect(&self.head), Ordering::Relaxed);
+                }
+            }
+        }
+        poll
+    }
+}
+
+impl<R: AsyncReady + Sync> AsyncReady for FramingProbe<R> {
+    async fn ready(&self, interest: Interest) -> io::Result<Ready> {
+        self.inner.ready(interest).await
+    }
+}
+
+// 内层 encoder 与分块写出共用连接的写端；encode 依次执行，不会同时写
+pub struct SharedWriter<W>(Arc<Mutex<W>>);
+
+impl<W> Clone for SharedWriter<W> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+// 持有 std Mutex 时不能等待，写端总是报告可写；只有客户端连接池会据此判断连接是否关闭，服务端不受影响
+impl<W: Send> AsyncReady for SharedWriter<W> {
+    async fn ready(&self, _interest: Interest) -> io::Result<Ready> {
+        Ok(Ready::WRITABLE)
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for SharedWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut *self.0.lock().unwrap()).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut *self.0.lock().unwrap()).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut *self.0.lock().unwrap()).poll_shutdown(cx)
+    }
+}
+
+pub struct StreamingEncoder<E, W> {
+    inner: E,
+    writer: SharedWriter<W>,
+    framing: Arc<AtomicU8>,
+    threshold: usize,
+    chunk_size: usize,
+    max_frame_size: usize,
+}
+
+impl<E, W> Encoder for StreamingEncoder<E, W>
+where
+    E: Encoder,
+    W: AsyncWrite + Send + Sync + Unpin + 'static,
+{
+    async fn encode<Req: Send + EntryMessage, Cx: ThriftContext>(
+        &mut self,
+        cx: &mut Cx,
+        msg: ThriftMessage<Req>,
+    ) -> Result<(), ThriftException> {
+        let transport = self.framing.load(Ordering::Relaxed);
+        let streamable = matches!(transport, framing::TTHEADER | framing::FRAMED)
+            && Handle::current().runtime_flavor() == RuntimeFlavor::MultiThread;
+        let data = match &msg.data {
+            Ok(data) if streamable => data,
+            _ => return self.inner.encode(cx, msg).await,
+        };
+
+        let mut counter = ChunkedProtocol::<W>::new(self.chunk_size, None);
+        encode_message(cx, &msg, data, &mut counter)?;
+        let payload_len = counter.finish()?;
+        if payload_len < self.threshold {
+            return self.inner.encode(cx, msg).await;
+        }
+
+        let prefix = match transport {
+            framing::TTHEADER => ttheader_prefix(cx.seq_id(), &backward_headers(), payload_len),
+            _ => (payload_len as u32).to_be_bytes().to_vec(),
+        };
+        let frame_size = prefix.len() - 4 + payload_len;
+        if frame_size > self.max_frame_size {
+            return Err(ThriftException::Protocol(ProtocolException::new(
+                ProtocolExceptionKind::SizeLimit,
+                format!(
+                    "response frame of {} bytes exceeds the {} byte limit",
+                    frame_size, self.max_frame_size
+                ),
+            )));
+        }
+
+        let handle = Handle::current();
+        let writer = self.writer.0.clone();
+        let chunk_size = self.chunk_size;
+        let cx = &*cx;
+        tokio::task::block_in_place(|| {
+            let mut writer = writer.lock().unwrap();
+            handle.block_on(writer.write_all(&prefix))?;
+            let mut protocol = ChunkedProtocol::new(chunk_size, Some((&handle, &mut *writer)));
+            encode_message(cx, &msg, data, &mut protocol)?;
+            protocol.finish()?;
+            handle.block_on(writer.flush())?;
+            Ok(())
+        })
+    }
+
+    async fn is_closed(&self) -> bool {
+        self.inner.is_closed().await
+    }
+}
+
+fn encode_message<Req: EntryMessage, Cx: ThriftContext, P: TOutputProtocol>(
+    cx: &Cx,
+    msg: &ThriftMessage<Req>,
+    data: &Req,
+    protocol: &mut P,
+) -> Result<(), ThriftException> {
+    protocol.write_message_begin(&TMessageIdentifier::new(
+        cx.rpc_info().method().clone(),
+        msg.meta.msg_type,
+        cx.seq_id(),
+    ))?;
+    data.encode(protocol)?;
+    protocol.write_message_end()
+}
+
+// handler 与服务端 layer 设置的回包 header，默认 TTHeader encoder 同样从这里取
+fn backward_headers() -> Vec<(FastStr, FastStr)> {
+    METAINFO
+        .try_with(|mi| {
+            mi.borrow()
+                .get_all_backward_transients()
+                .map(|headers| {
+                    headers
+                        .iter()
+                        .map(|(k, v)| (k.clone(), v.clone()))
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+        .unwrap_or_default()
+}
+
+// TTHeader 帧头：长度、magic、flags、seq id、以 4 字节为单位的头部长度，
+// 头部为 Binary 协议、无 transform，以及一个字符串键值 info 块，补零对齐到 4 字节
+fn ttheader_prefix(seq_id: i32, headers: &[(FastStr, FastStr)], payload_len: usize) -> Vec<u8> {
+    let mut header = vec![0x00, 0x00, 0x01];
+    header.extend_from_slice(&(headers.len() as u16).to_be_bytes());
+    for (key, value) in headers {
+        // 与默认 encoder 一致，键带上 backward 前缀，客户端据此放入 backward downstream
+        let key = format!("{}{}", RPC_PREFIX_BACKWARD, key);
+        for s in [key.as_str(), value.as_str()] {
+            header.extend_from_slice(&(s.len() as u16).to_be_bytes());
+            header.extend_from_slice(s.as_bytes());
+        }
+    }
+    header.resize(header.len().next_multiple_of(4), 0);
+
+    let frame_len = 10 + header.len() + payload_len;
+    let mut prefix = Vec::with_capacity(4 + 10 + header.len());
+    prefix.extend_from_slice(&(frame_len as u32).to_be_bytes());
+    prefix.extend_from_slice(&[0x10, 0x00, 0x00, 0x00]);
+    prefix.extend_from_slice(&seq_id.to_be_bytes());
+    prefix.extend_from_slice(&((header.len() / 4) as u16).to_be_bytes());
+    prefix.extend_from_slice(&header);
+    prefix
+}
+
+// 用 TBinaryProtocol 编码到一个小缓冲区，超过 chunk_size 就写到连接并清空
+// sink 为 None 时只累计字节数，用于算出帧长
+struct ChunkedProtocol<'a, W> {
+    buf: BytesMut,
+    chunk_size: usize,
+    written: usize,
+    sink: Option<(&'a Handle, &'a mut W)>,
+}
+
+impl<'a, W: AsyncWrite + Unpin> ChunkedProtocol<'a, W> {
+    fn new(chunk_size: usize, sink: Option<(&'a Handle, &'a mut W)>) -> Self {
+        Self {
+            buf: BytesMut::new(),
+            chunk_size,
+            written: 0,
+            sink,
+        }
+    }
+
+    fn drain(&mut self) -> Result<(), ThriftException> {
+        self.written += self.buf.len();
+        if let Some((handle, writer)) = &mut self.sink {
+            handle.block_on(writer.write_all(&self.buf))?;
+        }
+        self.buf.clear();
+        Ok(())
+    }
+
+    fn maybe_drain(&mut self) -> Result<(), ThriftException> {
+        if self.buf.len() >= self.chunk_size {
+            self.drain()?;
+        }
+        Ok(())
+    }
+
+    // 写出剩余数据，返回编码的总字节数
+    fn finish(mut self) -> Result<usize, ThriftException> {
+        self.drain()?;
+        Ok(self.written)
+    }
+}
+
+// 长度计算与 Binary 协议一致，编码时不会用到
+macro_rules! delegate_len {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method(&mut self, $($arg: $ty),*) -> usize {
+                TBinaryProtocol::new(&mut self.buf, false).$method($($arg),*)
+            }
+        )*
+    };
+}
+
+impl<W> TLengthProtocol for ChunkedProtocol<'_, W> {
+    delegate_len! {
+        message_begin_len(identifier: &TMessageIdentifier);
+        message_end_len();
+        struct_begin_len(identifier: &TStructIdentifier);
+        struct_end_len();
+        field_begin_len(field_type: TType, id: Option<i16>);
+        field_end_len();
+        field_stop_len();
+        bool_len(b: bool);
+        bytes_len(b: &[u8]);
+        bytes_vec_len(b: &[u8]);
+        byte_len(b: u8);
+        uuid_len(u: [u8; 16]);
+        i8_len(i: i8);
+        i16_len(i: i16);
+        i32_len(i: i32);
+        i64_len(i: i64);
+        double_len(d: f64);
+        string_len(s: &str);
+        faststr_len(s: &FastStr);
+        list_begin_len(identifier: TListIdentifier);
+        list_end_len();
+        set_begin_len(identifier: TSetIdentifier);
+        set_end_len();
+        map_begin_len(identifier: TMapIdentifier);
+        map_end_len();
+    }
+}
+
+macro_rules! delegate {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method(&mut self, $($arg: $ty),*) -> Result<(), ThriftException> {
+                TBinaryProtocol::new(&mut self.buf, false).$method($($arg),*)?;
+                self.maybe_drain()
+            }
+        )*
+    };
+}
+
+impl<W: AsyncWrite + Unpin> TOutputProtocol for ChunkedProtocol<'_, W> {
+    type BufMut = BytesMut;
+
+    delegate! {
+        write_message_begin(identifier: &TMessageIdentifier);
+        write_message_end();
+        write_struct_begin(identifier: &TStructIdentifier);
+        write_struct_end();
+        write_field_begin(field_type: TType, id: i16);
+        write_field_end();
+        write_field_stop();
+        write_byte(b: u8);
+        write_bytes(b: Bytes);
+        write_bytes_vec(b: &[u8]);
+        write_bool(b: bool);
+        write_i8(i: i8);
+        write_i16(i: i16);
+        write_i32(i: i32);
+        write_i64(i: i64);
+        write_double(d: f64);
+        write_uuid(u: [u8; 16]);
+        write_string(s: &str);
+        write_faststr(s: FastStr);
+        write_list_begin(identifier: TListIdentifier);
+        write_list_end();
+        write_set_begin(identifier: TSetIdentifier);
+        write_set_end();
+        write_map_begin(identifier: TMapIdentifier);
+        write_map_end();
+        write_bytes_without_len(b: Bytes);
+    }
+
+    // 整条消息编码完后由 finish 写出剩余数据
+    fn flush(&mut self) -> Result<(), ThriftException> {
+        Ok(())
+    }
+
+    fn buf_mut(&mut self) -> &mut Self::BufMut {
+        &mut self.buf
+    }
+}
@@ -0,0 +1,84 @@
+use std::future::Future;
+
+use bytes::Bytes;
+use pilota::thrift::{ProtocolException, ProtocolExceptionKind};
+use thrift_sniffer::decode::{self, DecodeError, DecodeOptions};
+use tokio::io::AsyncRead;
+use volo::util::buf_reader::BufReader;
+use volo_thrift::codec::default::{MakeZeroCopyCodec, ZeroCopyDecoder};
+use volo_thrift::context::ThriftContext;
+use volo_thrift::{EntryMessage, ThriftException, ThriftMessage};
+
+// 限制请求中单个 string/binary 字段的声明长度，比帧大小上限更细
+// 包在最内层的协议 codec 外，解码前先遍历一遍整帧（Binary 与 Compact 均支持），超限时返回 SizeLimit 协议错误
+// 仅对 framed/TTHeader 传输生效：无帧长的 buffered 传输拿不到完整帧，直接透传
+#[derive(Clone)]
+pub struct MaxStringSizeMakeCodec<M> {
+    inner: M,
+    max_string_size: usize,
+}
+
+impl<M> MaxStringSizeMakeCodec<M> {
+    pub fn new(inner: M, max_string_size: usize) -> Self {
+        Self {
+            inner,
+            max_string_size,
+        }
+    }
+}
+
+impl<M: MakeZeroCopyCodec> MakeZeroCopyCodec for MaxStringSizeMakeCodec<M> {
+    type Encoder = M::Encoder;
+    type Decoder = MaxStringSizeDecoder<M::Decoder>;
+
+    fn make_codec(&self) -> (Self::Encoder, Self::Decoder) {
+        let (encoder, decoder) = self.inner.make_codec();
+        let decoder = MaxStringSizeDecoder {
+            inner: decoder,
+            max_string_size: self.max_string_size,
+        };
+        (encoder, decoder)
+    }
+}
+
+pub struct MaxStringSizeDecoder<D> {
+    inner: D,
+    max_string_size: usize,
+}
+
+impl<D: ZeroCopyDecoder> ZeroCopyDecoder for MaxStringSizeDecoder<D> {
+    fn decode<Msg: Send + EntryMessage, Cx: ThriftContext>(
+        &mut self,
+        cx: &mut Cx,
+        bytes: &mut Bytes,
+    ) -> Result<Option<ThriftMessage<Msg>>, ThriftException> {
+        // 整帧不超过上限时不可能有超长字段，只有更大的帧才多遍历一次
+        if bytes.len() > self.max_string_size {
+            let options = DecodeOptions {
+                max_string_size: Some(self.max_string_size),
+            };
+            // 其它解码错误留给内层 codec 按原样报告
+            if let Err(e @ DecodeError::LengthTooLarge { .. }) =
+                decode::validate_message(bytes, &options)
+            {
+                return Err(ThriftException::Protocol(ProtocolException::new(
+                    ProtocolExceptionKind::SizeLimit,
+                    e.to_string(),
+                )));
+            }
+        }
+        self.inner.decode(cx, bytes)
+    }
+
+    fn decode_async<
+        Msg: Send + EntryMessage,
+        Cx: ThriftContext,
+        R: AsyncRead + Unpin + Send + Sync,
+    >(
+        &mut self,
+        cx: &mut Cx,
+        reader: &mut BufReader<R>,
+    ) -> impl Future<Output = Result<Option<ThriftMessage<Msg>>, ThriftException>> + Send {
+        self.inner.decode_async(cx, reader)
+    }
+}
@@ -0,0 +1,45 @@
+use std::time::Instant;
+
+use metainfo::{Backward, METAINFO};
+
+// 回包中携带服务端处理耗时的 THeader 键，单位毫秒
+pub const PROCESSING_TIME_HEADER: &str = "x-server-processing-ms";
+
+// 统计 handler 执行耗时并写入回包 header
+// 编解码在 layer 之外完成，因此这里只覆盖 handler 本身
+#[derive(Clone, Copy, Default)]
+pub struct ProcessingTimeLayer;
+
+impl<S> volo::Layer<S> for ProcessingTimeLayer {
+    type Service = ProcessingTimeService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        ProcessingTimeService { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct ProcessingTimeService<S> {
+    inner: S,
+}
+
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for ProcessingTimeService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req> + Send + Sync + 'static,
+    Cx: Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        let start = Instant::now();
+        let resp = self.inner.call(cx, req).await;
+        let elapsed_ms = start.elapsed().as_millis().to_string();
+
+        // 失败的调用同样带上耗时，方便客户端区分网络与服务端延迟
+        let _ = METAINFO.try_with(|mi| {
+            mi.borrow_mut()
+                .set_backward_transient(PROCESSING_TIME_HEADER, elapsed_ms)
+        });
+        resp
+    }
+}
@@ -0,0 +1,187 @@
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{anyhow, Context};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_rustls::{TlsAcceptor, TlsStream};
+use volo::net::conn::{Conn, ConnInfo, ConnStream};
+use volo::net::incoming::{Incoming, MakeIncoming};
+use volo::net::Address;
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::CertifiedKey;
+
+use crate::tls::{load_certs, TlsPolicy, HANDSHAKE_TIMEOUT};
+
+// 可热更新的服务端证书
+// 每次握手时读取当前证书，替换后只影响新连接，已建立的连接继续使用原证书
+#[derive(Debug)]
+pub struct CertReloader {
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    current: RwLock<Arc<CertifiedKey>>,
+}
+
+impl CertReloader {
+    pub fn load(cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let cert_path = cert_path.into();
+        let key_path = key_path.into();
+        let current = load_certified_key(&cert_path, &key_path)?;
+        Ok(Self {
+            cert_path,
+            key_path,
+            current: RwLock::new(Arc::new(current)),
+        })
+    }
+
+    // 重新读取证书与私钥，校验二者匹配后再替换
+    // 校验失败时返回错误，继续使用旧证书
+    pub fn reload(&self) -> anyhow::Result<()> {
+        let next = load_certified_key(&self.cert_path, &self.key_path)?;
+        *self.current.write().unwrap() = Arc::new(next);
+        tracing::info!("reloaded TLS certificate from {}", self.cert_path.display());
+        Ok(())
+    }
+
+    // 轮询证书文件的修改时间，发生变化时自动 reload
+    pub fn watch(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let this = self.clone();
+        tokio::spawn(async move {
+            let mut last = this.modified();
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let modified = this.modified();
+                if modified == last {
+                    continue;
+                }
+                last = modified;
+                if let Err(e) = this.reload() {
+                    tracing::error!("rejected TLS certificate reload: {:#}", e);
+                }
+            }
+        })
+    }
+
+    pub fn server_config(self: &Arc<Self>) -> rustls::ServerConfig {
+        rustls::ServerConfig::builder()
+            .with_no_client_auth()
+            .with_cert_resolver(self.clone())
+    }
+
+    // 按策略限制协议版本与密码套件，拒绝达不到要求的客户端
+    pub fn server_config_with(
+        self: &Arc<Self>,
+        policy: &TlsPolicy,
+    ) -> anyhow::Result<rustls::ServerConfig> {
+        Ok(rustls::ServerConfig::builder_with_provider(policy.provider()?)
+            .with_protocol_versions(policy.versions())
+            .context("invalid TLS policy")?
+            .with_no_client_auth()
+            .with_cert_resolver(self.clone()))
+    }
+
+    fn modified(&self) -> Option<(SystemTime, SystemTime)> {
+        let mtime = |p: &Path| p.metadata().and_then(|m| m.modified()).ok();
+        Some((mtime(&self.cert_path)?, mtime(&self.key_path)?))
+    }
+}
+
+impl ResolvesServerCert for CertReloader {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.current.read().unwrap().clone())
+    }
+}
+
+fn load_certified_key(cert_path: &Path, key_path: &Path) -> anyhow::Result<CertifiedKey> {
+    let certs = load_certs(cert_path)?;
+    let mut reader = BufReader::new(
+        File::open(key_path).with_context(|| format!("failed to open {}", key_path.display()))?,
+    );
+    let key = rustls_pemfile::private_key(&mut reader)
+        .with_context(|| format!("invalid private key in {}", key_path.display()))?
+        .ok_or_else(|| anyhow!("no private key found in {}", key_path.display()))?;
+    let signing_key = rustls::crypto::ring::sign::any_supported_type(&key)
+        .context("unsupported private key type")?;
+
+    let certified = CertifiedKey::new(certs, signing_key);
+    certified
+        .keys_match()
+        .context("certificate does not match private key")?;
+    Ok(certified)
+}
+
+// 以 TLS 监听的地址，代替 Address 传给 Server::run：
+//     server.run(TlsAddress::new(addr, reloader.server_config()))
+// 握手在独立的任务中进行，明文或慢速客户端不会阻塞其它连接；握手失败的连接直接关闭，明文客户端收到连接关闭的错误
+pub struct TlsAddress {
+    addr: SocketAddr,
+    config: Arc<rustls::ServerConfig>,
+}
+
+impl TlsAddress {
+    pub fn new(addr: SocketAddr, config: rustls::ServerConfig) -> Self {
+        Self {
+            addr,
+            config: Arc::new(config),
+        }
+    }
+}
+
+impl MakeIncoming for TlsAddress {
+    type Incoming = TlsIncoming;
+
+    async fn make_incoming(self) -> io::Result<TlsIncoming> {
+        let listener = TcpListener::bind(self.addr).await?;
+        let (tx, rx) = mpsc::channel(128);
+        tokio::spawn(accept_tls(listener, TlsAcceptor::from(self.config), tx));
+        Ok(TlsIncoming { conns: rx })
+    }
+}
+
+// 已完成握手的连接
+#[derive(Debug)]
+pub struct TlsIncoming {
+    conns: mpsc::Receiver<Conn>,
+}
+
+impl Incoming for TlsIncoming {
+    async fn accept(&mut self) -> io::Result<Option<Conn>> {
+        Ok(self.conns.recv().await)
+    }
+}
+
+async fn accept_tls(listener: TcpListener, acceptor: TlsAcceptor, tx: mpsc::Sender<Conn>) {
+    while !tx.is_closed() {
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                // 文件描述符耗尽等错误是暂时的，稍后重试
+                tracing::warn!("failed to accept connection: {}", e);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+        let acceptor = acceptor.clone();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(Ok(stream)) => {
+                    let conn = Conn {
+                        stream: ConnStream::Rustls(TlsStream::Server(stream)),
+                        info: ConnInfo {
+                            peer_addr: Some(Address::from(peer)),
+                        },
+                    };
+                    let _ = tx.send(conn).await;
+                }
+                Ok(Err(e)) => tracing::warn!("TLS handshake with {} failed: {}", peer, e),
+                Err(_) => tracing::warn!("TLS handshake with {} timed out", peer),
+            }
+        });
+    }
+}
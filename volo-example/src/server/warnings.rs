@@ -0,0 +1,18 @@
+use metainfo::{Backward, METAINFO};
+
+// 回包中携带 warning 的 THeader 键，多条之间以换行分隔
+pub const WARNINGS_HEADER: &str = "x-warnings";
+
+// 在 handler 中为本次成功的调用附加一条 warning，例如批量接口中部分条目失败的原因
+// 调用方收到的仍是成功响应，需要通过 header 判断结果是否完整
+pub fn add_warning(warning: impl AsRef<str>) {
+    let warning = warning.as_ref().replace('\n', " ");
+    let _ = METAINFO.try_with(|mi| {
+        let mut mi = mi.borrow_mut();
+        let value = match mi.get_backward_transient(WARNINGS_HEADER) {
+            Some(prev) => format!("{}\n{}", prev, warning),
+            None => warning,
+        };
+        mi.set_backward_transient(WARNINGS_HEADER, value);
+    });
+}
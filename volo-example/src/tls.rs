@@ -0,0 +1,106 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context};
+use rustls::crypto::{ring, CryptoProvider};
+use rustls::pki_types::CertificateDer;
+use rustls::{CipherSuite, SupportedCipherSuite, SupportedProtocolVersion};
+
+// TLS 握手的最长时间，客户端与服务端共用，超时的连接直接关闭
+pub(crate) const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+static TLS13_ONLY: &[&SupportedProtocolVersion] = &[&rustls::version::TLS13];
+
+// 允许协商的最低 TLS 版本
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TlsVersion {
+    #[default]
+    Tls12,
+    Tls13,
+}
+
+// 允许使用的密码套件；rustls 默认的套件已全部是带前向保密的 AEAD
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum CipherPolicy {
+    #[default]
+    Default,
+    // 只允许 AES-GCM，满足部分合规要求
+    AesGcmOnly,
+    Only(Vec<CipherSuite>),
+}
+
+// 客户端与服务端共用的 TLS 协商策略
+// 对端无法满足时握手直接失败（protocol_version / handshake_failure alert），不会降级协商
+#[derive(Clone, Debug, Default)]
+pub struct TlsPolicy {
+    min_version: TlsVersion,
+    ciphers: CipherPolicy,
+}
+
+impl TlsPolicy {
+    pub fn min_version(mut self, version: TlsVersion) -> Self {
+        self.min_version = version;
+        self
+    }
+
+    pub fn ciphers(mut self, ciphers: CipherPolicy) -> Self {
+        self.ciphers = ciphers;
+        self
+    }
+
+    pub(crate) fn provider(&self) -> anyhow::Result<Arc<CryptoProvider>> {
+        let mut provider = ring::default_provider();
+        provider.cipher_suites.retain(|suite| self.allows(suite));
+        if provider.cipher_suites.is_empty() {
+            return Err(anyhow!(
+                "TLS policy leaves no usable cipher suite (min version {:?}, ciphers {:?})",
+                self.min_version,
+                self.ciphers
+            ));
+        }
+        Ok(Arc::new(provider))
+    }
+
+    pub(crate) fn versions(&self) -> &'static [&'static SupportedProtocolVersion] {
+        match self.min_version {
+            TlsVersion::Tls12 => rustls::ALL_VERSIONS,
+            TlsVersion::Tls13 => TLS13_ONLY,
+        }
+    }
+
+    fn allows(&self, suite: &SupportedCipherSuite) -> bool {
+        if self.min_version == TlsVersion::Tls13 && suite.version() != &rustls::version::TLS13 {
+            return false;
+        }
+        let name = suite.suite();
+        match &self.ciphers {
+            CipherPolicy::Default => true,
+            CipherPolicy::AesGcmOnly => matches!(
+                name,
+                CipherSuite::TLS13_AES_128_GCM_SHA256
+                    | CipherSuite::TLS13_AES_256_GCM_SHA384
+                    | CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256
+                    | CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384
+                    | CipherSuite::TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256
+                    | CipherSuite::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384
+            ),
+            CipherPolicy::Only(allowed) => allowed.contains(&name),
+        }
+    }
+}
+
+pub(crate) fn load_certs(path: &Path) -> anyhow::Result<Vec<CertificateDer<'static>>> {
+    let mut reader = BufReader::new(
+        File::open(path).with_context(|| format!("failed to open {}", path.display()))?,
+    );
+    let certs = rustls_pemfile::certs(&mut reader)
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("invalid certificate in {}", path.display()))?;
+    if certs.is_empty() {
+        return Err(anyhow!("no certificate found in {}", path.display()));
+    }
+    Ok(certs)
+}
@@ -0,0 +1,39 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use volo_example::client::BlockingItemServiceClient;
+use volo_example::S;
+use volo_gen::volo::example::{GetItemRequest, ItemServiceClientBuilder, ItemServiceServer};
+
+// 普通的同步测试，服务端运行在单独线程的 runtime 中
+#[test]
+fn blocking_get_item() {
+    let addr: SocketAddr = "127.0.0.1:19105".parse().unwrap();
+    std::thread::spawn(move || {
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(async move {
+                ItemServiceServer::new(S::default())
+                    .run(volo::net::Address::from(addr))
+                    .await
+                    .unwrap();
+            });
+    });
+    std::thread::sleep(Duration::from_millis(100));
+
+    let client = BlockingItemServiceClient::new(|| {
+        ItemServiceClientBuilder::new("blocking")
+            .address(addr)
+            .build()
+    })
+    .unwrap();
+    let resp = client.get_item(GetItemRequest { id: 1024 }).unwrap();
+    assert_eq!(resp.item.id, 1024);
+
+    // 在 async 上下文中调用直接返回错误，而不是阻塞或 panic
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let result = rt.block_on(async { client.get_item(GetItemRequest { id: 1024 }) });
+    assert!(result.is_err());
+}
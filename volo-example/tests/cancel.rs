@@ -0,0 +1,94 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::sync::Notify;
+use volo_example::server::{cancellation, CancelOnCloseMakeCodec, CancellationLayer};
+use volo_example::S;
+use volo_gen::volo::example::ItemServiceServer;
+use volo_thrift::codec::default::DefaultMakeCodec;
+
+// GetItem(id = 1024) 请求帧：TTHeader，消息体为 Binary 协议
+const GET_ITEM: &[u8] = &[
+    0x00, 0x00, 0x00, 0x31, // 帧长 49
+    0x10, 0x00, // magic
+    0x00, 0x00, // flags
+    0x00, 0x00, 0x00, 0x01, // seq id
+    0x00, 0x01, // 头部长度 1 * 4
+    0x00, 0x00, 0x00, 0x00, // protocol id Binary，无 transform，填充
+    0x80, 0x01, 0x00, 0x01, // Binary call
+    0x00, 0x00, 0x00, 0x07, b'G', b'e', b't', b'I', b't', b'e', b'm', // 方法名
+    0x00, 0x00, 0x00, 0x01, // seq id
+    0x0c, 0x00, 0x01, // field 1: GetItemRequest
+    0x0a, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x00, // field 1: i64 1024
+    0x00, 0x00,
+];
+
+// handler 等待取消信号，收到后通知测试
+#[derive(Clone)]
+struct WaitForCancelLayer(Arc<Notify>);
+
+impl<S> volo::Layer<S> for WaitForCancelLayer {
+    type Service = WaitForCancelService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        WaitForCancelService {
+            inner,
+            cancelled: self.0,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct WaitForCancelService<S> {
+    inner: S,
+    cancelled: Arc<Notify>,
+}
+
+#[allow(clippy::manual_async_fn)]
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for WaitForCancelService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req> + Send + Sync + 'static,
+    Cx: Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        let cancellation = cancellation().expect("CancellationLayer is installed");
+        tokio::select! {
+            _ = cancellation.cancelled() => self.cancelled.notify_one(),
+            _ = tokio::time::sleep(Duration::from_secs(5)) => {}
+        }
+        self.inner.call(cx, req).await
+    }
+}
+
+// ping-pong 模式下客户端在 handler 执行期间断开，handler 应当收到取消信号
+#[tokio::test]
+async fn disconnect_cancels_pingpong_request() {
+    let addr: SocketAddr = "127.0.0.1:19108".parse().unwrap();
+    let cancelled = Arc::new(Notify::new());
+    let layer = WaitForCancelLayer(cancelled.clone());
+    tokio::spawn(async move {
+        ItemServiceServer::new(S::default())
+            .make_codec(CancelOnCloseMakeCodec::new(DefaultMakeCodec::default()))
+            .layer(layer)
+            .layer_front(CancellationLayer)
+            .run(volo::net::Address::from(addr))
+            .await
+            .unwrap();
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(GET_ITEM).await.unwrap();
+    // 等请求进入 handler 后断开
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    drop(stream);
+
+    tokio::time::timeout(Duration::from_secs(1), cancelled.notified())
+        .await
+        .expect("handler was not cancelled after the client disconnected");
+}
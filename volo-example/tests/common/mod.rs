@@ -0,0 +1,35 @@
+use std::time::Duration;
+
+// 测试中慢请求在 handler 中停留的时间
+pub const HANDLER_DELAY: Duration = Duration::from_millis(300);
+
+// 让每个请求在 handler 中停留一段时间，模拟慢请求
+#[derive(Clone, Copy)]
+pub struct SlowLayer;
+
+impl<S> volo::Layer<S> for SlowLayer {
+    type Service = SlowService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        SlowService { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct SlowService<S> {
+    inner: S,
+}
+
+#[allow(clippy::manual_async_fn)]
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for SlowService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req> + Send + Sync + 'static,
+    Cx: Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        tokio::time::sleep(HANDLER_DELAY).await;
+        self.inner.call(cx, req).await
+    }
+}
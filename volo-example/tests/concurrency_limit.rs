@@ -0,0 +1,51 @@
+mod common;
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use futures::future::join_all;
+use volo_example::server::{ConcurrencyLimitLayer, OVERLOADED};
+use volo_example::S;
+use volo_gen::volo::example::{GetItemRequest, ItemServiceClientBuilder, ItemServiceServer};
+use volo_thrift::ClientError;
+
+use common::SlowLayer;
+
+const MAX_CONCURRENT: usize = 2;
+const CALLS: usize = 6;
+
+// 同时发起超过上限的慢请求，只有 MAX_CONCURRENT 个被执行，其余收到 overloaded 异常
+#[tokio::test]
+async fn rejects_requests_above_limit() {
+    let addr: SocketAddr = "127.0.0.1:19098".parse().unwrap();
+    tokio::spawn(async move {
+        ItemServiceServer::new(S::default())
+            .layer_front(ConcurrencyLimitLayer::new(MAX_CONCURRENT))
+            .layer(SlowLayer)
+            .run(volo::net::Address::from(addr))
+            .await
+            .unwrap();
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let client = ItemServiceClientBuilder::new("limit").address(addr).build();
+    let results = join_all((0..CALLS).map(|i| {
+        let client = client.clone();
+        async move { client.get_item(GetItemRequest { id: i as i64 }).await }
+    }))
+    .await;
+
+    let mut ok = 0;
+    for result in results {
+        match result {
+            Ok(_) => ok += 1,
+            Err(ClientError::Application(e)) => assert!(
+                e.message().starts_with(OVERLOADED),
+                "unexpected exception: {}",
+                e.message()
+            ),
+            Err(e) => panic!("expected an overloaded exception, got {:?}", e),
+        }
+    }
+    assert_eq!(ok, MAX_CONCURRENT);
+}
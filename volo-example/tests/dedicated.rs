@@ -0,0 +1,58 @@
+mod common;
+
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::future::join_all;
+use volo_example::client::DedicatedConnection;
+use volo_example::server::{ConnContextInit, ConnInfo};
+use volo_example::S;
+use volo_gen::volo::example::{GetItemRequest, ItemServiceClientBuilder, ItemServiceServer};
+use volo_thrift::codec::default::DefaultMakeCodec;
+
+use common::{SlowLayer, HANDLER_DELAY};
+
+const CALLS: i64 = 4;
+
+// 并发发起的调用全部走同一条连接，并且逐个执行
+#[tokio::test]
+async fn calls_share_one_connection_in_order() {
+    let addr: SocketAddr = "127.0.0.1:19109".parse().unwrap();
+    let connections = Arc::new(AtomicUsize::new(0));
+    let counter = connections.clone();
+    let conn_context = ConnContextInit::on_new_connection(move |_: &ConnInfo| {
+        counter.fetch_add(1, Ordering::Relaxed);
+    });
+    tokio::spawn(async move {
+        ItemServiceServer::new(S::default())
+            .multiplex(true)
+            .make_codec(conn_context.make_codec(DefaultMakeCodec::default()))
+            .layer(SlowLayer)
+            .run(volo::net::Address::from(addr))
+            .await
+            .unwrap();
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let client = ItemServiceClientBuilder::new("dedicated")
+        .address(addr)
+        .dedicated_connection()
+        .build();
+    let start = Instant::now();
+    let resps = join_all((0..CALLS).map(|id| client.get_item(GetItemRequest { id }))).await;
+    let elapsed = start.elapsed();
+
+    for (id, resp) in (0..CALLS).zip(resps) {
+        assert_eq!(resp.unwrap().item.id, id);
+    }
+    assert_eq!(connections.load(Ordering::Relaxed), 1);
+    // 服务端 multiplex 下并发请求可以同时执行，耗时叠加说明客户端逐个发送
+    assert!(
+        elapsed >= HANDLER_DELAY * CALLS as u32,
+        "{} calls took {:?}",
+        CALLS,
+        elapsed
+    );
+}
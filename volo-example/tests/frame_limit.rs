@@ -0,0 +1,89 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use volo_example::server::{limited_make_codec, MAX_FRAME_SIZE};
+use volo_example::S;
+use volo_gen::volo::example::{GetItemRequest, ItemServiceClientBuilder, ItemServiceServer};
+
+// 记录进程内单次分配的最大字节数
+struct LargestAlloc;
+
+static LARGEST: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for LargestAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LARGEST.fetch_max(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        LARGEST.fetch_max(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOC: LargestAlloc = LargestAlloc;
+
+// framed 长度前缀后跟 Binary call 的开头，声明的帧长远大于实际发送的字节
+fn oversized_frame(declared: u32) -> Vec<u8> {
+    let mut frame = declared.to_be_bytes().to_vec();
+    frame.extend_from_slice(&[0x80, 0x01, 0x00, 0x01, 0x00, 0x00, 0x00, 0x07]);
+    frame.extend_from_slice(b"GetItem");
+    frame
+}
+
+// 帧长超过上限时服务端不按声明长度分配缓冲区，直接关闭连接，之后照常处理正常请求
+#[tokio::test]
+async fn oversized_frame_is_rejected_without_allocating() {
+    let addr: SocketAddr = "127.0.0.1:19114".parse().unwrap();
+    tokio::spawn(async move {
+        ItemServiceServer::new(S::default())
+            .make_codec(limited_make_codec())
+            .run(volo::net::Address::from(addr))
+            .await
+            .unwrap();
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // 刚超过本服务的上限（仍低于 volo-thrift 默认的 16 MiB），以及离谱的 2 GiB
+    for declared in [MAX_FRAME_SIZE as u32 + 1, i32::MAX as u32] {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(&oversized_frame(declared)).await.unwrap();
+        let mut buf = [0; 64];
+        let closed = tokio::time::timeout(Duration::from_secs(1), async {
+            loop {
+                match stream.read(&mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => continue,
+                }
+            }
+        })
+        .await;
+        assert!(
+            closed.is_ok(),
+            "connection with a {} byte frame was not closed",
+            declared
+        );
+    }
+    let largest = LARGEST.load(Ordering::Relaxed);
+    assert!(
+        largest < MAX_FRAME_SIZE,
+        "allocated {} bytes for a rejected frame",
+        largest
+    );
+
+    let client = ItemServiceClientBuilder::new("frame-limit")
+        .address(addr)
+        .build();
+    let resp = client.get_item(GetItemRequest { id: 1 }).await.unwrap();
+    assert_eq!(resp.item.id, 1);
+}
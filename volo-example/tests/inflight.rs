@@ -0,0 +1,53 @@
+mod common;
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use futures::future::join_all;
+use volo_example::client::{is_pool_exhausted, MaxInflightLayer};
+use volo_example::S;
+use volo_gen::volo::example::{GetItemRequest, ItemServiceClientBuilder, ItemServiceServer};
+
+use common::SlowLayer;
+
+const MAX_INFLIGHT: usize = 2;
+const CALLS: usize = 5;
+
+// fail_fast 时超出上限的调用立即失败，错误能被 is_pool_exhausted 识别
+#[tokio::test]
+async fn fail_fast_above_limit() {
+    let addr: SocketAddr = "127.0.0.1:19107".parse().unwrap();
+    tokio::spawn(async move {
+        ItemServiceServer::new(S::default())
+            .layer(SlowLayer)
+            .run(volo::net::Address::from(addr))
+            .await
+            .unwrap();
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let client = ItemServiceClientBuilder::new("inflight")
+        .address(addr)
+        .layer_outer(MaxInflightLayer::new(MAX_INFLIGHT).fail_fast(true))
+        .build();
+    let results = join_all((0..CALLS).map(|id| {
+        let client = client.clone();
+        async move { client.get_item(GetItemRequest { id: id as i64 }).await }
+    }))
+    .await;
+
+    let mut ok = 0;
+    for result in results {
+        match result {
+            Ok(_) => ok += 1,
+            Err(e) => assert!(is_pool_exhausted(&e), "unexpected error: {:?}", e),
+        }
+    }
+    assert_eq!(ok, MAX_INFLIGHT);
+}
+
+#[test]
+#[should_panic(expected = "max_inflight must be positive")]
+fn zero_limit_is_rejected() {
+    MaxInflightLayer::new(0);
+}
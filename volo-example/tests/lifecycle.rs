@@ -0,0 +1,48 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use volo_example::server::{ServerEvent, ServerEvents};
+use volo_example::S;
+use volo_gen::volo::example::ItemServiceServer;
+
+fn recording_events() -> (ServerEvents, Arc<Mutex<Vec<ServerEvent>>>) {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let recorder = seen.clone();
+    let events =
+        ServerEvents::default().on_event(move |event| recorder.lock().unwrap().push(event));
+    (events, seen)
+}
+
+// 监听成功后才触发 Bound
+#[tokio::test]
+async fn bound_is_emitted_after_listening() {
+    let addr: SocketAddr = "127.0.0.1:19116".parse().unwrap();
+    let (events, seen) = recording_events();
+    let incoming = events.make_incoming(addr, volo::net::Address::from(addr));
+    tokio::spawn(async move {
+        ItemServiceServer::new(S::default())
+            .run(incoming)
+            .await
+            .unwrap();
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert_eq!(*seen.lock().unwrap(), vec![ServerEvent::Bound(addr)]);
+    tokio::net::TcpStream::connect(addr).await.unwrap();
+}
+
+// 端口已被占用时 run 返回错误，不触发 Bound
+#[tokio::test]
+async fn failed_bind_emits_nothing() {
+    let addr: SocketAddr = "127.0.0.1:19117".parse().unwrap();
+    let _occupied = tokio::net::TcpListener::bind(addr).await.unwrap();
+    let (events, seen) = recording_events();
+
+    let result = ItemServiceServer::new(S::default())
+        .run(events.make_incoming(addr, volo::net::Address::from(addr)))
+        .await;
+
+    assert!(result.is_err());
+    assert!(seen.lock().unwrap().is_empty());
+}
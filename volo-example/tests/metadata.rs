@@ -0,0 +1,73 @@
+use std::cell::RefCell;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use metainfo::{MetaInfo, METAINFO};
+use volo_example::client::{response_metadata, with_metadata, MetadataLayer};
+use volo_example::server::{metadata, set_response_metadata, DEFAULT_TRACE_ID_HEADER};
+use volo_example::S;
+use volo_gen::volo::example::{GetItemRequest, ItemServiceClientBuilder, ItemServiceServer};
+
+// 在 handler 之前读取请求 header，原样写回回包 header
+#[derive(Clone, Copy)]
+struct EchoTraceIdLayer;
+
+impl<S> volo::Layer<S> for EchoTraceIdLayer {
+    type Service = EchoTraceIdService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        EchoTraceIdService { inner }
+    }
+}
+
+#[derive(Clone)]
+struct EchoTraceIdService<S> {
+    inner: S,
+}
+
+#[allow(clippy::manual_async_fn)]
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for EchoTraceIdService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req> + Send + Sync + 'static,
+    Cx: Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        if let Some(trace_id) = metadata(DEFAULT_TRACE_ID_HEADER) {
+            set_response_metadata(DEFAULT_TRACE_ID_HEADER, trace_id);
+        }
+        self.inner.call(cx, req).await
+    }
+}
+
+#[tokio::test]
+async fn metadata_round_trip() {
+    let addr: SocketAddr = "127.0.0.1:19100".parse().unwrap();
+    tokio::spawn(async move {
+        ItemServiceServer::new(S::default())
+            .layer(EchoTraceIdLayer)
+            .run(volo::net::Address::from(addr))
+            .await
+            .unwrap();
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let client = ItemServiceClientBuilder::new("metadata")
+        .address(addr)
+        .layer_outer(MetadataLayer)
+        .build();
+
+    METAINFO
+        .scope(RefCell::new(MetaInfo::default()), async {
+            with_metadata([(DEFAULT_TRACE_ID_HEADER, "trace-1024")], async {
+                client.get_item(GetItemRequest { id: 1024 }).await.unwrap();
+            })
+            .await;
+            assert_eq!(
+                response_metadata(DEFAULT_TRACE_ID_HEADER).as_deref(),
+                Some("trace-1024")
+            );
+        })
+        .await;
+}
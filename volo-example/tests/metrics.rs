@@ -0,0 +1,53 @@
+#![cfg(feature = "metrics")]
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use volo_example::server::Metrics;
+use volo_example::S;
+use volo_gen::volo::example::{GetItemRequest, ItemServiceClientBuilder, ItemServiceServer};
+
+async fn scrape(addr: SocketAddr) -> String {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .unwrap();
+    let mut resp = String::new();
+    stream.read_to_string(&mut resp).await.unwrap();
+    resp
+}
+
+#[tokio::test]
+async fn scrape_after_get_item_calls() {
+    let addr: SocketAddr = "127.0.0.1:19102".parse().unwrap();
+    let metrics_addr: SocketAddr = "127.0.0.1:19103".parse().unwrap();
+    let metrics = Metrics::default();
+    tokio::spawn(metrics.clone().serve(metrics_addr));
+    let layer = metrics.layer();
+    tokio::spawn(async move {
+        ItemServiceServer::new(S::default())
+            .layer_front(layer)
+            .run(volo::net::Address::from(addr))
+            .await
+            .unwrap();
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let client = ItemServiceClientBuilder::new("metrics").address(addr).build();
+    for id in 0..3 {
+        client.get_item(GetItemRequest { id }).await.unwrap();
+    }
+
+    let resp = scrape(metrics_addr).await;
+    assert!(resp.contains("Content-Type: text/plain; version=0.0.4"), "{}", resp);
+    assert!(resp.contains("rpc_requests_total{method=\"GetItem\"} 3"), "{}", resp);
+    assert!(
+        resp.contains("rpc_latency_seconds_count{method=\"GetItem\"} 3"),
+        "{}",
+        resp
+    );
+    assert!(!resp.contains("rpc_errors_total{method=\"GetItem\""), "{}", resp);
+}
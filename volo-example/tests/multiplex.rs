@@ -0,0 +1,58 @@
+mod common;
+
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::future::join_all;
+use volo_example::server::{ConnContextInit, ConnContextLayer, ConnInfo};
+use volo_example::S;
+use volo_gen::volo::example::{GetItemRequest, ItemServiceClientBuilder, ItemServiceServer};
+use volo_thrift::codec::default::DefaultMakeCodec;
+
+use common::{SlowLayer, HANDLER_DELAY};
+
+const CONCURRENCY: i64 = 16;
+
+// multiplex 下并发调用共用一条连接，回包按 seq id 分发给各自的调用方，慢请求不会阻塞后面的请求
+#[tokio::test]
+async fn concurrent_calls_share_one_connection() {
+    let addr: SocketAddr = "127.0.0.1:19104".parse().unwrap();
+    let connections = Arc::new(AtomicUsize::new(0));
+    let counter = connections.clone();
+    let conn_context = ConnContextInit::on_new_connection(move |_: &ConnInfo| {
+        counter.fetch_add(1, Ordering::Relaxed);
+    });
+    tokio::spawn(async move {
+        ItemServiceServer::new(S::default())
+            .multiplex(true)
+            .make_codec(conn_context.make_codec(DefaultMakeCodec::default()))
+            .layer(SlowLayer)
+            .layer_front(ConnContextLayer)
+            .run(volo::net::Address::from(addr))
+            .await
+            .unwrap();
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let client = ItemServiceClientBuilder::new("multiplex")
+        .address(addr)
+        .multiplex(true)
+        .build();
+    let start = Instant::now();
+    let resps = join_all((0..CONCURRENCY).map(|id| client.get_item(GetItemRequest { id }))).await;
+    let elapsed = start.elapsed();
+
+    for (id, resp) in (0..CONCURRENCY).zip(resps) {
+        assert_eq!(resp.unwrap().item.id, id);
+    }
+    assert_eq!(connections.load(Ordering::Relaxed), 1);
+    // 串行处理需要 CONCURRENCY * HANDLER_DELAY
+    assert!(
+        elapsed < HANDLER_DELAY * 3,
+        "{} concurrent calls took {:?}",
+        CONCURRENCY,
+        elapsed
+    );
+}
@@ -0,0 +1,84 @@
+use std::future::{ready, Ready};
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use pilota::thrift::{ApplicationException, ApplicationExceptionKind};
+use volo_example::client::ProtocolNegotiation;
+use volo_thrift::ClientError;
+
+// 候选“客户端”只是协议名，服务端只接受 Binary
+fn negotiation() -> ProtocolNegotiation<&'static str, &'static str> {
+    ProtocolNegotiation::new(["compact", "binary"], |p| *p)
+}
+
+fn rejected() -> ClientError {
+    ClientError::Application(ApplicationException::new(
+        ApplicationExceptionKind::PROTOCOL_ERROR,
+        "unknown protocol",
+    ))
+}
+
+fn binary_only(client: &&str) -> Ready<Result<&'static str, ClientError>> {
+    ready(match *client {
+        "binary" => Ok("ok"),
+        _ => Err(rejected()),
+    })
+}
+
+#[tokio::test]
+async fn idempotent_call_is_resent_with_next_protocol() {
+    let negotiation = negotiation();
+    assert_eq!(negotiation.call(true, binary_only).await.unwrap(), "ok");
+    assert_eq!(negotiation.protocol(), Some(&"binary"));
+}
+
+#[tokio::test]
+async fn non_idempotent_call_is_not_replayed() {
+    let negotiation = negotiation();
+    let sent = AtomicUsize::new(0);
+    let result = negotiation
+        .call(false, |client| {
+            sent.fetch_add(1, Ordering::Relaxed);
+            binary_only(client)
+        })
+        .await;
+    assert!(result.is_err());
+    assert_eq!(sent.load(Ordering::Relaxed), 1);
+    // 之后的调用直接使用下一个协议
+    assert_eq!(negotiation.call(false, binary_only).await.unwrap(), "ok");
+    assert_eq!(negotiation.protocol(), Some(&"binary"));
+}
+
+#[tokio::test]
+async fn transient_errors_do_not_change_protocol() {
+    let negotiation = negotiation();
+    let result = negotiation
+        .call(true, |_| async {
+            Err::<(), _>(ClientError::Transport(
+                io::Error::from(io::ErrorKind::ConnectionReset).into(),
+            ))
+        })
+        .await;
+    assert!(result.is_err());
+    assert_eq!(negotiation.protocol(), None);
+
+    let accepted = negotiation.call(true, |client| {
+        let client = *client;
+        async move { Ok::<_, ClientError>(client) }
+    });
+    assert_eq!(accepted.await.unwrap(), "compact");
+    assert_eq!(negotiation.protocol(), Some(&"compact"));
+}
+
+#[tokio::test]
+async fn rejected_protocol_is_renegotiated() {
+    let negotiation = negotiation();
+    negotiation
+        .call(true, |_| async { Ok::<_, ClientError>(()) })
+        .await
+        .unwrap();
+    assert_eq!(negotiation.protocol(), Some(&"compact"));
+    // 服务端之后不再接受 Compact
+    assert_eq!(negotiation.call(true, binary_only).await.unwrap(), "ok");
+    assert_eq!(negotiation.protocol(), Some(&"binary"));
+}
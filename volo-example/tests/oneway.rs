@@ -0,0 +1,38 @@
+mod common;
+
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use volo_example::S;
+use volo_gen::volo::example::{ItemEvent, ItemServiceClientBuilder, ItemServiceServer};
+
+use common::{SlowLayer, HANDLER_DELAY};
+
+// oneway 调用在请求写出后就返回，不等服务端 handler 执行完
+#[tokio::test]
+async fn oneway_returns_before_handler_finishes() {
+    let addr: SocketAddr = "127.0.0.1:19099".parse().unwrap();
+    tokio::spawn(async move {
+        ItemServiceServer::new(S::default())
+            .layer(SlowLayer)
+            .run(volo::net::Address::from(addr))
+            .await
+            .unwrap();
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let client = ItemServiceClientBuilder::new("oneway").address(addr).build();
+    let start = Instant::now();
+    client
+        .fire_and_forget(ItemEvent {
+            id: 1024,
+            event: "viewed".into(),
+        })
+        .await
+        .unwrap();
+    assert!(
+        start.elapsed() < HANDLER_DELAY / 2,
+        "oneway call waited {:?} for the handler",
+        start.elapsed()
+    );
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "otel")]
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use opentelemetry::global;
+use opentelemetry::trace::SpanKind;
+use opentelemetry_sdk::testing::trace::InMemorySpanExporter;
+use opentelemetry_sdk::trace::TracerProvider;
+use volo_example::client::OtelClientLayer;
+use volo_example::server::OtelServerLayer;
+use volo_example::S;
+use volo_gen::volo::example::{GetItemRequest, ItemServiceClientBuilder, ItemServiceServer};
+
+// 客户端 span 的 trace context 经 THeader 传到服务端，服务端 span 以它为父 span
+#[tokio::test]
+async fn server_span_is_child_of_client_span() {
+    let exporter = InMemorySpanExporter::default();
+    global::set_tracer_provider(
+        TracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build(),
+    );
+
+    let addr: SocketAddr = "127.0.0.1:19101".parse().unwrap();
+    tokio::spawn(async move {
+        ItemServiceServer::new(S::default())
+            .layer_front(OtelServerLayer::default())
+            .run(volo::net::Address::from(addr))
+            .await
+            .unwrap();
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let client = ItemServiceClientBuilder::new("otel")
+        .address(addr)
+        .layer_outer_front(OtelClientLayer::default())
+        .build();
+    client.get_item(GetItemRequest { id: 1024 }).await.unwrap();
+
+    let spans = exporter.get_finished_spans().unwrap();
+    let client_span = spans.iter().find(|s| s.span_kind == SpanKind::Client).unwrap();
+    let server_span = spans.iter().find(|s| s.span_kind == SpanKind::Server).unwrap();
+    assert_eq!(server_span.name, "GetItem");
+    assert_eq!(
+        server_span.span_context.trace_id(),
+        client_span.span_context.trace_id()
+    );
+    assert_eq!(server_span.parent_span_id, client_span.span_context.span_id());
+}
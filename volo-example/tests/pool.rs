@@ -0,0 +1,112 @@
+mod common;
+
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::join_all;
+use volo_example::client::Pool;
+use volo_example::server::{ConnContextInit, ConnInfo};
+use volo_example::S;
+use volo_gen::volo::example::{GetItemRequest, ItemServiceClientBuilder, ItemServiceServer};
+use volo_thrift::codec::default::DefaultMakeCodec;
+
+use common::SlowLayer;
+
+const MAX_INFLIGHT: usize = 2;
+const CALLS: i64 = 8;
+
+// 记录同时在 handler 中的请求数的峰值
+#[derive(Clone)]
+struct PeakLayer {
+    current: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+}
+
+impl<S> volo::Layer<S> for PeakLayer {
+    type Service = PeakService<S>;
+
+    fn layer(self, inner: S) -> Self::Service {
+        PeakService { inner, layer: self }
+    }
+}
+
+#[derive(Clone)]
+struct PeakService<S> {
+    inner: S,
+    layer: PeakLayer,
+}
+
+#[allow(clippy::manual_async_fn)]
+#[volo::service]
+impl<Cx, Req, S> volo::Service<Cx, Req> for PeakService<S>
+where
+    Req: Send + 'static,
+    S: volo::Service<Cx, Req> + Send + Sync + 'static,
+    Cx: Send + 'static,
+{
+    async fn call(&self, cx: &mut Cx, req: Req) -> Result<S::Response, S::Error> {
+        let now = self.layer.current.fetch_add(1, Ordering::SeqCst) + 1;
+        self.layer.peak.fetch_max(now, Ordering::SeqCst);
+        let resp = self.inner.call(cx, req).await;
+        self.layer.current.fetch_sub(1, Ordering::SeqCst);
+        resp
+    }
+}
+
+// 单个 endpoint 时，并发调用数超过 max_inflight 时建立的连接与同时执行的请求都不超过 max_inflight，其余调用排队等待
+#[tokio::test]
+async fn connections_never_exceed_max_inflight() {
+    let addr: SocketAddr = "127.0.0.1:19113".parse().unwrap();
+    let connections = Arc::new(AtomicUsize::new(0));
+    let counter = connections.clone();
+    let conn_context = ConnContextInit::on_new_connection(move |_: &ConnInfo| {
+        counter.fetch_add(1, Ordering::Relaxed);
+    });
+    let peak = PeakLayer {
+        current: Default::default(),
+        peak: Default::default(),
+    };
+    let server_peak = peak.clone();
+    tokio::spawn(async move {
+        ItemServiceServer::new(S::default())
+            .make_codec(conn_context.make_codec(DefaultMakeCodec::default()))
+            .layer(server_peak)
+            .layer(SlowLayer)
+            .run(volo::net::Address::from(addr))
+            .await
+            .unwrap();
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let pool = Pool::new(MAX_INFLIGHT, MAX_INFLIGHT, Duration::from_secs(30));
+    let client = ItemServiceClientBuilder::new("pool")
+        .address(addr)
+        .pool_config(pool.config())
+        .layer_outer(pool.layer())
+        .build();
+    let resps = join_all((0..CALLS).map(|id| {
+        let client = client.clone();
+        async move { client.get_item(GetItemRequest { id }).await }
+    }))
+    .await;
+
+    for (id, resp) in (0..CALLS).zip(resps) {
+        assert_eq!(resp.unwrap().item.id, id);
+    }
+    assert_eq!(peak.peak.load(Ordering::SeqCst), MAX_INFLIGHT);
+    let opened = connections.load(Ordering::Relaxed);
+    assert!(
+        opened <= MAX_INFLIGHT,
+        "{} connections opened for max_inflight {}",
+        opened,
+        MAX_INFLIGHT
+    );
+}
+
+#[test]
+#[should_panic(expected = "max_inflight must be positive")]
+fn zero_max_inflight_is_rejected() {
+    Pool::new(0, 0, Duration::from_secs(30));
+}
@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::Duration;
+
+use thrift_sniffer::decode::{self, Message, ThriftValue};
+use thrift_sniffer::pcap_reader;
+use volo_example::S;
+use volo_gen::volo::example::{GetItemRequest, ItemServiceClientBuilder, ItemServiceServer};
+
+// 从抓包中取出 get_item 的请求，重放到进程内的服务端，比较实际响应与抓到的响应
+#[tokio::test]
+async fn replay_get_item() {
+    let payloads = tcp_payloads(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/get_item.pcap"));
+    let messages: Vec<Message> = payloads
+        .iter()
+        .map(|p| decode::decode_message(decode::strip_theader(p).unwrap()).unwrap())
+        .collect();
+    let [request, reply] = messages.as_slice() else {
+        panic!("expected one call and one reply, got {} messages", messages.len());
+    };
+    assert_eq!(request.name, "GetItem");
+    assert_eq!(reply.seq_id, request.seq_id);
+
+    // args { 1: GetItemRequest { 1: id } }
+    let ThriftValue::I64(id) = field(struct_field(&request.body, 1), 1) else {
+        panic!("request id is not an i64");
+    };
+
+    let addr: SocketAddr = "127.0.0.1:19093".parse().unwrap();
+    tokio::spawn(async move {
+        ItemServiceServer::new(S::default())
+            .run(volo::net::Address::from(addr))
+            .await
+            .unwrap();
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let client = ItemServiceClientBuilder::new("replay").address(addr).build();
+    let live = client.get_item(GetItemRequest { id: *id }).await.unwrap().item;
+
+    // result { 0: GetItemResponse { 1: Item } }
+    let captured = struct_field(struct_field(&reply.body, 0), 1);
+    assert_eq!(field(captured, 1), &ThriftValue::I64(live.id));
+    assert_eq!(field(captured, 2), &ThriftValue::String(live.title.as_bytes().to_vec()));
+    assert_eq!(field(captured, 3), &ThriftValue::String(live.content.as_bytes().to_vec()));
+    let ThriftValue::Map(_, _, extra) = field(captured, 10) else {
+        panic!("extra is not a map");
+    };
+    let captured_extra: BTreeMap<&[u8], &[u8]> = extra
+        .iter()
+        .map(|entry| match entry {
+            (ThriftValue::String(k), ThriftValue::String(v)) => (k.as_slice(), v.as_slice()),
+            other => panic!("extra entry is not string to string: {:?}", other),
+        })
+        .collect();
+    let live_extra = live.extra.unwrap_or_default();
+    let live_extra: BTreeMap<&[u8], &[u8]> = live_extra
+        .iter()
+        .map(|(k, v)| (k.as_bytes(), v.as_bytes()))
+        .collect();
+    assert_eq!(captured_extra, live_extra);
+}
+
+fn field(fields: &[(i16, ThriftValue)], id: i16) -> &ThriftValue {
+    fields
+        .iter()
+        .find(|(field_id, _)| *field_id == id)
+        .map(|(_, value)| value)
+        .unwrap_or_else(|| panic!("missing field {}", id))
+}
+
+fn struct_field(fields: &[(i16, ThriftValue)], id: i16) -> &[(i16, ThriftValue)] {
+    match field(fields, id) {
+        ThriftValue::Struct(fields) => fields,
+        other => panic!("field {} is not a struct: {}", id, other),
+    }
+}
+
+// 读出每个以太网帧中非空的 TCP 负载，fixture 只包含以太网 + IPv4 + TCP
+fn tcp_payloads(path: &Path) -> Vec<Vec<u8>> {
+    let mut payloads = Vec::new();
+    pcap_reader::for_each_frame(path, |frame| {
+        let ip = &frame.data[14..];
+        let tcp = &ip[(ip[0] & 0x0f) as usize * 4..];
+        let payload = &tcp[(tcp[12] >> 4) as usize * 4..];
+        if !payload.is_empty() {
+            payloads.push(payload.to_vec());
+        }
+    })
+    .unwrap();
+    payloads
+}
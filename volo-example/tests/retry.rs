@@ -0,0 +1,35 @@
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use volo_example::client::{retry_attempts, Backoff, RetryLayer};
+use volo_gen::volo::example::{GetItemRequest, ItemServiceClientBuilder};
+use volo_thrift::ClientError;
+
+const MAX_ATTEMPTS: u32 = 3;
+
+// 建连一直失败时重试到上限，返回的错误带上总尝试次数，io 错误类型不变
+#[tokio::test]
+async fn exhausted_retries_report_attempts() {
+    // 没有服务监听的端口
+    let addr: SocketAddr = "127.0.0.1:19115".parse().unwrap();
+    let client = ItemServiceClientBuilder::new("retry")
+        .address(addr)
+        .layer_outer(RetryLayer::new(
+            MAX_ATTEMPTS,
+            Backoff::fixed(Duration::from_millis(10)),
+        ))
+        .build();
+
+    let err = client.get_item(GetItemRequest { id: 1 }).await.unwrap_err();
+    assert_eq!(retry_attempts(&err), Some(MAX_ATTEMPTS));
+    match &err {
+        ClientError::Transport(e) => assert_eq!(e.kind(), io::ErrorKind::ConnectionRefused),
+        e => panic!("expected a transport error, got {:?}", e),
+    }
+    assert!(
+        err.to_string().contains("after 3 attempts"),
+        "unexpected message: {}",
+        err
+    );
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use volo_example::server::Drain;
+use volo_example::S;
+use volo_gen::volo::example::{GetItemRequest, ItemServiceClientBuilder, ItemServiceServer};
+
+use common::{SlowLayer, HANDLER_DELAY};
+
+// 慢请求处理期间触发退出信号，run_with_shutdown 要等它完成后才返回
+#[tokio::test]
+async fn in_flight_request_completes_before_shutdown() {
+    let addr: SocketAddr = "127.0.0.1:19097".parse().unwrap();
+    let drain = Drain::default();
+    let server = ItemServiceServer::new(S::default())
+        .layer_front(drain.layer())
+        .layer(SlowLayer);
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    let shutdown = tokio::spawn({
+        let drain = drain.clone();
+        async move {
+            let signal = async {
+                let _ = stopped.await;
+            };
+            drain
+                .run_with_shutdown(
+                    server.run(volo::net::Address::from(addr)),
+                    signal,
+                    Duration::from_secs(5),
+                )
+                .await;
+            Instant::now()
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let client = ItemServiceClientBuilder::new("shutdown").address(addr).build();
+    let call = tokio::spawn(async move { client.get_item(GetItemRequest { id: 1024 }).await });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(drain.in_flight(), 1);
+
+    let signaled = Instant::now();
+    stop.send(()).unwrap();
+    let returned = shutdown.await.unwrap();
+    assert_eq!(drain.in_flight(), 0);
+    assert!(returned - signaled >= HANDLER_DELAY / 2);
+
+    let resp = call.await.unwrap().unwrap();
+    assert_eq!(resp.item.id, 1024);
+}
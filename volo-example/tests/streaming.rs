@@ -0,0 +1,147 @@
+use std::cell::RefCell;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use metainfo::{MetaInfo, METAINFO, RPC_PREFIX_BACKWARD};
+use thrift_sniffer::decode::{self, ttype, Message, ThriftValue};
+use thrift_sniffer::encode;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use volo_example::client::response_warnings;
+use volo_example::server::{StreamingMakeCodec, WARNINGS_HEADER};
+use volo_example::S;
+use volo_gen::volo::example::{GetItemsRequest, ItemServiceClientBuilder, ItemServiceServer};
+use volo_thrift::codec::default::DefaultMakeCodec;
+
+// 远小于默认值，使几千个 Item 的响应就走分块写出
+const THRESHOLD: usize = 4096;
+const CHUNK_SIZE: usize = 512;
+const ITEMS: i64 = 3000;
+
+fn serve(addr: SocketAddr, streaming: bool) {
+    tokio::spawn(async move {
+        let server = ItemServiceServer::new(S::default());
+        let codec = DefaultMakeCodec::default();
+        if streaming {
+            server
+                .make_codec(
+                    StreamingMakeCodec::new(codec)
+                        .threshold(THRESHOLD)
+                        .chunk_size(CHUNK_SIZE),
+                )
+                .run(volo::net::Address::from(addr))
+                .await
+                .unwrap();
+        } else {
+            server
+                .make_codec(codec)
+                .run(volo::net::Address::from(addr))
+                .await
+                .unwrap();
+        }
+    });
+}
+
+// id 0 无效，会被跳过并附带一条 warning
+fn ids() -> Vec<i64> {
+    (0..=ITEMS).collect()
+}
+
+// 分块写出需要多线程 runtime
+#[tokio::test(flavor = "multi_thread")]
+async fn large_response_is_streamed_intact() {
+    let addr: SocketAddr = "127.0.0.1:19118".parse().unwrap();
+    serve(addr, true);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let client = ItemServiceClientBuilder::new("streaming")
+        .address(addr)
+        .build();
+    METAINFO
+        .scope(RefCell::new(MetaInfo::default()), async {
+            let resp = client
+                .get_items(GetItemsRequest { ids: ids() })
+                .await
+                .unwrap();
+            assert_eq!(resp.items.len(), ITEMS as usize);
+            for (i, item) in resp.items.iter().enumerate() {
+                assert_eq!(item.id, i as i64 + 1);
+                assert_eq!(item.title, format!("Item {}", item.id));
+            }
+            assert_eq!(response_warnings(), vec!["id 0: invalid id".to_string()]);
+        })
+        .await;
+}
+
+// GetItems 的 Binary call，按 ttheader 选择 framed 或 TTHeader 传输
+fn get_items_call(ttheader: bool) -> Vec<u8> {
+    let msg = Message {
+        message_type: 1,
+        name: "GetItems".to_string(),
+        seq_id: 7,
+        body: vec![(
+            1,
+            ThriftValue::Struct(vec![(
+                1,
+                ThriftValue::List(
+                    ttype::I64,
+                    ids().into_iter().map(ThriftValue::I64).collect(),
+                ),
+            )]),
+        )],
+    };
+    let payload = encode::encode_message(&msg);
+    let mut frame = Vec::new();
+    if ttheader {
+        // magic、flags、seq id、1 个 4 字节单位的头部：Binary 协议、无 transform、填充
+        let frame_len = 10 + 4 + payload.len();
+        frame.extend_from_slice(&(frame_len as u32).to_be_bytes());
+        frame.extend_from_slice(&[0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x07, 0x00, 0x01]);
+        frame.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]);
+    } else {
+        frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    }
+    frame.extend_from_slice(&payload);
+    frame
+}
+
+async fn call(addr: SocketAddr, request: &[u8]) -> Vec<u8> {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(request).await.unwrap();
+    let mut len = [0; 4];
+    stream.read_exact(&mut len).await.unwrap();
+    let mut frame = len.to_vec();
+    frame.resize(4 + u32::from_be_bytes(len) as usize, 0);
+    stream.read_exact(&mut frame[4..]).await.unwrap();
+    frame
+}
+
+// 分块写出的帧与默认 encoder 的帧解码结果相同，传输方式与请求一致，TTHeader 回包带有 warning
+#[tokio::test(flavor = "multi_thread")]
+async fn streamed_frame_matches_buffered_encoding() {
+    let streaming: SocketAddr = "127.0.0.1:19119".parse().unwrap();
+    let buffered: SocketAddr = "127.0.0.1:19120".parse().unwrap();
+    serve(streaming, true);
+    serve(buffered, false);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    for ttheader in [false, true] {
+        let request = get_items_call(ttheader);
+        let streamed = call(streaming, &request).await;
+        let expected = call(buffered, &request).await;
+        assert!(streamed.len() > THRESHOLD);
+
+        let body = decode::strip_theader(&streamed).unwrap();
+        assert_eq!(
+            decode::decode_message(body).unwrap(),
+            decode::decode_message(decode::strip_theader(&expected).unwrap()).unwrap()
+        );
+        if ttheader {
+            let headers = decode::theader_headers(&streamed).unwrap();
+            let key = format!("{}{}", RPC_PREFIX_BACKWARD, WARNINGS_HEADER);
+            assert!(headers.contains(&(key, "id 0: invalid id".to_string())));
+        } else {
+            assert_eq!(streamed[4..6], [0x80, 0x01]);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rustls::pki_types::{PrivatePkcs8KeyDer, ServerName};
+use rustls::RootCertStore;
+use volo_example::client::TlsMakeTransport;
+use volo_example::server::TlsAddress;
+use volo_example::S;
+use volo_gen::volo::example::{GetItemRequest, ItemServiceClientBuilder, ItemServiceServer};
+
+// 自签名证书，客户端以 localhost 作为 SNI 校验
+fn tls_configs() -> (rustls::ServerConfig, rustls::ClientConfig) {
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let cert_der = cert.cert.der().clone();
+    let key_der = PrivatePkcs8KeyDer::from(cert.key_pair.serialize_der());
+
+    // volo 的 rustls feature 同时启用了 aws-lc-rs，与 src/tls.rs 一样显式使用 ring
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let server = rustls::ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(vec![cert_der.clone()], key_der.into())
+        .unwrap();
+
+    let mut roots = RootCertStore::empty();
+    roots.add(cert_der).unwrap();
+    let client = rustls::ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    (server, client)
+}
+
+#[tokio::test]
+async fn get_item_over_tls() {
+    let (server_config, client_config) = tls_configs();
+    let addr: SocketAddr = "127.0.0.1:19095".parse().unwrap();
+    tokio::spawn(async move {
+        ItemServiceServer::new(S::default())
+            .run(TlsAddress::new(addr, server_config))
+            .await
+            .unwrap();
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let sni = ServerName::try_from("localhost").unwrap();
+    let client = ItemServiceClientBuilder::new("tls")
+        .address(addr)
+        .make_transport(TlsMakeTransport::new(client_config, Some(sni)))
+        .build();
+    let resp = client.get_item(GetItemRequest { id: 1024 }).await.unwrap();
+    assert_eq!(resp.item.id, 1024);
+
+    // 明文客户端连 TLS 服务端：握手失败后连接被关闭，调用应很快返回错误
+    let plain = ItemServiceClientBuilder::new("plain").address(addr).build();
+    let result = tokio::time::timeout(
+        Duration::from_secs(10),
+        plain.get_item(GetItemRequest { id: 1024 }),
+    )
+    .await
+    .expect("plaintext call against TLS server hung");
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn tls_client_against_plaintext_server_fails() {
+    let (_, client_config) = tls_configs();
+    let addr: SocketAddr = "127.0.0.1:19096".parse().unwrap();
+    tokio::spawn(async move {
+        ItemServiceServer::new(S::default())
+            .run(volo::net::Address::from(addr))
+            .await
+            .unwrap();
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let sni = ServerName::try_from("localhost").unwrap();
+    let client = ItemServiceClientBuilder::new("tls")
+        .address(addr)
+        .make_transport(TlsMakeTransport::new(client_config, Some(sni)))
+        .build();
+    let result = tokio::time::timeout(
+        Duration::from_secs(10),
+        client.get_item(GetItemRequest { id: 1024 }),
+    )
+    .await
+    .expect("TLS call against plaintext server hung");
+    assert!(result.is_err());
+}
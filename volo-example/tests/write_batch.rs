@@ -0,0 +1,115 @@
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::future::join_all;
+use tokio::io::{AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use volo_example::client::WriteBatchMakeCodec;
+use volo_example::S;
+use volo_gen::volo::example::{GetItemRequest, ItemServiceClientBuilder, ItemServiceServer};
+use volo_thrift::codec::default::DefaultMakeCodec;
+
+const WINDOW: Duration = Duration::from_millis(20);
+
+// 记录每次 write 收到的数据；broken 为 true 时所有写入失败
+#[derive(Clone, Default)]
+struct RecordingWriter {
+    writes: Arc<Mutex<Vec<Vec<u8>>>>,
+    broken: bool,
+}
+
+impl AsyncWrite for RecordingWriter {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        if self.broken {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        self.writes.lock().unwrap().push(buf.to_vec());
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+// 窗口内的多次写入与 flush 合并为一次 write
+#[tokio::test]
+async fn writes_within_window_are_coalesced() {
+    let recorder = RecordingWriter::default();
+    let (_peer, reader) = tokio::io::duplex(64);
+    let (_reader, mut writer) = WriteBatchMakeCodec::new((), WINDOW).wrap(reader, recorder.clone());
+
+    for msg in [&b"first"[..], b"second", b"third"] {
+        writer.write_all(msg).await.unwrap();
+        writer.flush().await.unwrap();
+    }
+    tokio::time::sleep(WINDOW * 3).await;
+
+    assert_eq!(
+        *recorder.writes.lock().unwrap(),
+        vec![b"firstsecondthird".to_vec()]
+    );
+}
+
+// 写失败后正在等待回包的读取立即返回同一错误，而不是一直挂起
+#[tokio::test]
+async fn write_error_fails_pending_read() {
+    let broken = RecordingWriter {
+        broken: true,
+        ..Default::default()
+    };
+    // 对端一直不发数据也不关闭，没有写错误时读取会永远等待
+    let (_peer, reader) = tokio::io::duplex(64);
+    let (mut reader, mut writer) = WriteBatchMakeCodec::new((), WINDOW).wrap(reader, broken);
+
+    writer.write_all(b"request").await.unwrap();
+    writer.flush().await.unwrap();
+    let mut buf = [0; 16];
+    let err = tokio::time::timeout(WINDOW * 10, reader.read(&mut buf))
+        .await
+        .expect("read should fail once the batch cannot be written")
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+
+    // 之后的写入同样返回错误
+    let err = writer.write_all(b"next").await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+}
+
+// multiplex 客户端使用批量写出，并发调用的回包照常分发
+#[tokio::test]
+async fn multiplex_client_with_write_batching() {
+    let addr: SocketAddr = "127.0.0.1:19106".parse().unwrap();
+    tokio::spawn(async move {
+        ItemServiceServer::new(S::default())
+            .multiplex(true)
+            .run(volo::net::Address::from(addr))
+            .await
+            .unwrap();
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let client = ItemServiceClientBuilder::new("write-batch")
+        .address(addr)
+        .multiplex(true)
+        .make_codec(WriteBatchMakeCodec::new(
+            DefaultMakeCodec::default(),
+            WINDOW,
+        ))
+        .build();
+    let resps = join_all((0..16).map(|id| client.get_item(GetItemRequest { id }))).await;
+    for (id, resp) in (0..16).zip(resps) {
+        assert_eq!(resp.unwrap().item.id, id);
+    }
+}
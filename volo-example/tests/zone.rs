@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_broadcast::Receiver;
+use volo::context::Endpoint;
+use volo::discovery::{Change, Discover, Instance};
+use volo::loadbalance::error::LoadBalanceError;
+use volo::loadbalance::LoadBalance;
+use volo::net::Address;
+use volo_example::client::{Resolved, ResolverDiscover, ZoneAwareLoadBalance, ZONE_TAG};
+use volo_example::server::{ConnContextInit, ConnInfo};
+use volo_example::S;
+use volo_gen::volo::example::{GetItemRequest, ItemServiceClientBuilder, ItemServiceServer};
+use volo_thrift::codec::default::DefaultMakeCodec;
+
+fn instance(addr: &str, zone: &str) -> Arc<Instance> {
+    Arc::new(Instance {
+        address: Address::from(addr.parse::<SocketAddr>().unwrap()),
+        weight: 100,
+        tags: HashMap::from([(ZONE_TAG.into(), zone.to_string().into())]),
+    })
+}
+
+// 实例列表可以随时替换，不支持 watch
+#[derive(Default)]
+struct ManualDiscover {
+    instances: Mutex<Vec<Arc<Instance>>>,
+}
+
+impl Discover for ManualDiscover {
+    type Key = ();
+    type Error = LoadBalanceError;
+
+    async fn discover<'s>(
+        &'s self,
+        _endpoint: &'s Endpoint,
+    ) -> Result<Vec<Arc<Instance>>, Self::Error> {
+        Ok(self.instances.lock().unwrap().clone())
+    }
+
+    fn key(&self, _endpoint: &Endpoint) -> Self::Key {}
+
+    fn watch(&self, _keys: Option<&[Self::Key]>) -> Option<Receiver<Change<Self::Key>>> {
+        None
+    }
+}
+
+async fn pick(lb: &ZoneAwareLoadBalance<()>, discover: &ManualDiscover) -> Vec<Address> {
+    let endpoint = Endpoint::new("item".into());
+    lb.get_picker(&endpoint, discover).await.unwrap().collect()
+}
+
+// 本区实例排在前面，其它区的实例作为溢出的后备；discover 不能 watch 时实例变化在下一次选取生效
+#[tokio::test]
+async fn local_zone_first_and_membership_updates() {
+    let lb = ZoneAwareLoadBalance::default().local_zone("a");
+    let discover = ManualDiscover::default();
+    let a = instance("127.0.0.1:1001", "a");
+    let b = instance("127.0.0.1:1002", "b");
+    *discover.instances.lock().unwrap() = vec![b.clone(), a.clone()];
+    for _ in 0..10 {
+        assert_eq!(
+            pick(&lb, &discover).await,
+            vec![a.address.clone(), b.address.clone()]
+        );
+    }
+
+    // a 迁到 b 区，新实例 c 加入 a 区
+    let moved = instance("127.0.0.1:1001", "b");
+    let c = instance("127.0.0.1:1003", "a");
+    *discover.instances.lock().unwrap() = vec![moved, b, c.clone()];
+    assert_eq!(pick(&lb, &discover).await[0], c.address);
+}
+
+async fn serve(addr: SocketAddr, connections: Arc<AtomicUsize>) {
+    let conn_context = ConnContextInit::on_new_connection(move |_: &ConnInfo| {
+        connections.fetch_add(1, Ordering::Relaxed);
+    });
+    tokio::spawn(async move {
+        ItemServiceServer::new(S::default())
+            .make_codec(conn_context.make_codec(DefaultMakeCodec::default()))
+            .run(volo::net::Address::from(addr))
+            .await
+            .unwrap();
+    });
+}
+
+fn resolved(addr: SocketAddr, zone: &str) -> Resolved {
+    Resolved {
+        addr,
+        weight: 100,
+        zone: Some(zone.to_string()),
+    }
+}
+
+// 两个可用区各一个服务端：调用只落在本区；本区地址不可达时溢出到其它区
+#[tokio::test]
+async fn same_zone_preference_and_cross_zone_failover() {
+    let local: SocketAddr = "127.0.0.1:19110".parse().unwrap();
+    let remote: SocketAddr = "127.0.0.1:19111".parse().unwrap();
+    // 没有服务端监听，建连被拒绝
+    let dead: SocketAddr = "127.0.0.1:19112".parse().unwrap();
+    let local_connections = Arc::new(AtomicUsize::new(0));
+    let remote_connections = Arc::new(AtomicUsize::new(0));
+    serve(local, local_connections.clone()).await;
+    serve(remote, remote_connections.clone()).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let client = ItemServiceClientBuilder::new("zone")
+        .discover(ResolverDiscover::with_resolver(
+            "item",
+            0,
+            vec![resolved(remote, "b"), resolved(local, "a")],
+        ))
+        .load_balance(ZoneAwareLoadBalance::default().local_zone("a"))
+        .build();
+    for id in 0..10 {
+        assert_eq!(
+            client
+                .get_item(GetItemRequest { id })
+                .await
+                .unwrap()
+                .item
+                .id,
+            id
+        );
+    }
+    assert!(local_connections.load(Ordering::Relaxed) > 0);
+    assert_eq!(remote_connections.load(Ordering::Relaxed), 0);
+
+    let client = ItemServiceClientBuilder::new("zone-failover")
+        .discover(ResolverDiscover::with_resolver(
+            "item",
+            0,
+            vec![resolved(remote, "b"), resolved(dead, "a")],
+        ))
+        .load_balance(ZoneAwareLoadBalance::default().local_zone("a"))
+        .retry_count(1)
+        .build();
+    assert_eq!(
+        client
+            .get_item(GetItemRequest { id: 1 })
+            .await
+            .unwrap()
+            .item
+            .id,
+        1
+    );
+    assert!(remote_connections.load(Ordering::Relaxed) > 0);
+}